@@ -3,7 +3,7 @@ Apply a series of transformation functions to given CSV column/s. This can be us
 perform typical data-wrangling tasks and/or to harmonize some values, etc.
 
 It has four subcommands:
- 1. operations*   - 40 string, format, currency, regex & NLP operators.
+ 1. operations*   - 43 string, format, currency, regex & NLP operators.
  2. emptyreplace* - replace empty cells with <--replacement> string.
  3. dynfmt        - Dynamically constructs a new column from other columns using
                     the <--formatstr> template.
@@ -28,7 +28,7 @@ number of transformed columns with the --rename option is the same. e.g.:
 
  $ qsv apply operations trim,upper col1,col2,col3 -r newcol1,newcol2,newcol3 file.csv
 
-It has 40 supported operations:
+It has 43 supported operations:
 
   * len: Return string length
   * lower: Transform to lowercase
@@ -38,11 +38,14 @@ It has 40 supported operations:
   * trim: Trim (drop whitespace left & right of the string)
   * ltrim: Left trim whitespace
   * rtrim: Right trim whitespace
+  * ltrim0: Left trim zeros. A value of only zeros becomes 0.
   * mtrim: Trims --comparand matches left & right of the string (Rust trim_matches)
   * mltrim: Left trim --comparand matches (Rust trim_start_matches)
   * mrtrim: Right trim --comparand matches (Rust trim_end_matches)
   * strip_prefix: Removes specified prefix in --comparand
   * strip_suffix: Remove specified suffix in --comparand
+  * strip_punct: Remove Unicode punctuation
+  * unaccent: Remove accents & other diacritical marks (e.g. é -> e)
   * escape - escape (Rust escape_default)
   * encode62: base62 encode
   * decode62: base62 decode
@@ -329,6 +332,7 @@ use strsim::{
 use strum_macros::EnumString;
 use thousands::{policies, Separable, SeparatorPolicy};
 use titlecase::titlecase;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
 use whatlang::detect;
 
 use crate::{
@@ -362,6 +366,7 @@ enum Operations {
     Len,
     Lower,
     Ltrim,
+    Ltrim0,
     Mltrim,
     Mrtrim,
     Mtrim,
@@ -380,10 +385,12 @@ enum Operations {
     Squeeze,
     Squeeze0,
     Strip_Prefix,
+    Strip_Punct,
     Strip_Suffix,
     Thousands,
     Titlecase,
     Trim,
+    Unaccent,
     Upper,
     Whatlang,
 }
@@ -970,6 +977,60 @@ fn validate_operations(
     Ok(ops_vec) // no validation errors
 }
 
+// the operations that can normalize join keys with --key-transform
+const KEY_TRANSFORM_OPERATIONS: [&str; 10] = [
+    "trim",
+    "ltrim",
+    "rtrim",
+    "ltrim0",
+    "lower",
+    "upper",
+    "squeeze",
+    "squeeze0",
+    "strip_punct",
+    "unaccent",
+];
+
+/// A series of apply operations that normalizes join keys before matching, for the
+/// --key-transform option of the join, joinp & exclude commands.
+pub(crate) struct KeyTransform {
+    ops: SmallVec<[Operations; 4]>,
+}
+
+impl KeyTransform {
+    /// Parses a comma-delimited operation series, e.g. "trim,lower,unaccent".
+    pub(crate) fn new(operations: &str) -> CliResult<Self> {
+        let names: Vec<String> = operations
+            .split(',')
+            .map(|op| op.trim().to_ascii_lowercase())
+            .collect();
+        if let Some(op) = names
+            .iter()
+            .find(|op| !KEY_TRANSFORM_OPERATIONS.contains(&op.as_str()))
+        {
+            return fail_incorrectusage_clierror!(
+                "Invalid --key-transform operation '{op}'. Valid operations are: {}.",
+                KEY_TRANSFORM_OPERATIONS.join(", ")
+            );
+        }
+        let ops = validate_operations(
+            &names.iter().map(String::as_str).collect(),
+            "",
+            "",
+            None,
+            "",
+        )?;
+        Ok(Self { ops })
+    }
+
+    #[inline]
+    pub(crate) fn apply(&self, value: &str) -> String {
+        let mut cell = value.to_owned();
+        apply_operations(&self.ops, &mut cell, "", "", "");
+        cell
+    }
+}
+
 #[inline]
 fn apply_operations(
     ops_vec: &SmallVec<[Operations; 4]>,
@@ -1006,6 +1067,25 @@ fn apply_operations(
             Operations::Rtrim => {
                 *cell = String::from(cell.trim_end());
             },
+            Operations::Ltrim0 => {
+                let trimmed = cell.trim_start_matches('0');
+                *cell = if trimmed.is_empty() && !cell.is_empty() {
+                    String::from("0")
+                } else {
+                    String::from(trimmed)
+                };
+            },
+            Operations::Strip_Punct => {
+                let punctuation: &'static Regex = regex_oncelock!(r"\p{P}");
+                *cell = punctuation.replace_all(cell, "").into_owned();
+            },
+            Operations::Unaccent => {
+                *cell = cell
+                    .nfd()
+                    .filter(|c| !is_combining_mark(*c))
+                    .nfc()
+                    .collect();
+            },
             Operations::Mtrim => {
                 let chars_to_trim: &[char] = &comparand.chars().collect::<Vec<_>>();
                 *cell = String::from(cell.trim_matches(chars_to_trim));
@@ -0,0 +1,482 @@
+static USAGE: &str = r#"
+Compute histograms of the numeric columns of a CSV, or label their values with bins.
+
+The bins are computed from the min, max & interquartile range (IQR) of each column
+in the stats cache (see `qsv stats --help`), creating it if required, using one of
+these methods:
+
+  fixed:     --bins equal-width bins between the min & the max, or bins of --width.
+  fd:        the Freedman–Diaconis rule - equal-width bins, 2 * IQR / n^(1/3) wide,
+             where n is the number of non-null values. Columns with an IQR of zero
+             use the Sturges rule instead. At most 1,000 bins are created.
+  sturges:   the Sturges rule - ceil(log2(n)) + 1 equal-width bins.
+  quantile:  --bins bins with about the same number of values each, with the bin edges
+             at the quantiles of the column. Quantile edges that are equal are merged,
+             so there can be less bins than requested. Note that the values of the
+             binned columns are loaded into memory to compute the quantiles.
+
+Bins include their lower edge and exclude their upper edge, except for the last bin,
+which also includes its upper edge. Only Integer & Float columns are binned - the
+other selected columns are ignored, as are NULLs.
+
+By default, a histogram is written for each binned column, with the following columns:
+
+    field,bin,lower,upper,count,percentage,cumulative_percentage
+
+where "bin" is the bin label (e.g. "[10, 20)") and the percentages are of the non-null
+values of the column.
+
+With --label, the input is written instead, with a "<column>_bin" column holding the
+bin label of the value appended for each binned column. This makes it easy to summarize
+the data by bucketed numeric values with `frequency`, `pivotp`, etc.
+
+As the stats cache is used, the input must be a file and cannot be stdin.
+
+Examples:
+
+  # histograms of all the numeric columns, using the Freedman–Diaconis rule
+  qsv bins data.csv
+
+  # a histogram of the fare column, with 20 equal-width bins
+  qsv bins --select fare --method fixed --bins 20 trips.csv
+
+  # label the fares with their quartile, and count the trips of each quartile
+  qsv bins -s fare -m quantile -b 4 --label trips.csv | qsv frequency -s fare_bin
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_bins.rs.
+
+Usage:
+    qsv bins [options] [<input>]
+    qsv bins --help
+
+bins options:
+    -s, --select <arg>     Select a subset of columns to bin.
+                           See 'qsv select --help' for the format details.
+    -m, --method <arg>     The binning method - fixed, fd, sturges or quantile.
+                           [default: fd]
+    -b, --bins <arg>       The number of bins of the fixed & quantile methods.
+                           [default: 10]
+    --width <arg>          The width of the bins of the fixed method, starting from
+                           the min of each column. Overrides --bins.
+                           A width that creates more than 1,000 bins for a column
+                           is rejected.
+    --label                Append a bin label column to the input for each binned
+                           column, instead of writing histograms.
+    --round <arg>          Round the bin edges & percentages to <arg> decimal places.
+                           [default: 4]
+    --force                Force recomputing the stats cache, even if it's current.
+    -j, --jobs <arg>       The number of jobs to run in parallel when computing
+                           the stats cache.
+                           When not set, the number of jobs is set to the
+                           number of CPUs detected.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row will NOT be interpreted as
+                           column names. The 'field' column of the histograms
+                           will then be 1-based indices instead of header names.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character. (default: ,)
+    --memcheck             Check if there is enough memory to load the entire
+                           CSV into memory for the quantile method using
+                           CONSERVATIVE heuristics.
+"#;
+
+use serde::Deserialize;
+
+use crate::{
+    cmd::stats::StatsData,
+    config::{Config, Delimiter},
+    select::SelectColumns,
+    util,
+    util::{get_stats_records, StatsMode},
+    CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_input:       Option<String>,
+    flag_select:     SelectColumns,
+    flag_method:     String,
+    flag_bins:       usize,
+    flag_width:      Option<f64>,
+    flag_label:      bool,
+    flag_round:      u32,
+    flag_force:      bool,
+    flag_jobs:       Option<usize>,
+    flag_output:     Option<String>,
+    flag_no_headers: bool,
+    flag_delimiter:  Option<Delimiter>,
+    flag_memcheck:   bool,
+}
+
+// the maximum number of bins the Freedman–Diaconis rule & --width create, as columns
+// with outliers far from their IQR, or a tiny --width, would otherwise have a huge
+// number of bins
+const MAX_BINS: usize = 1_000;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BinMethod {
+    Fixed,
+    FreedmanDiaconis,
+    Sturges,
+    Quantile,
+}
+
+/// A binned column, with its bins' edges in increasing order -
+/// bin i is [edges[i], edges[i + 1]), and the last bin also includes its upper edge
+struct BinnedColumn {
+    idx:    usize,
+    name:   String,
+    edges:  Vec<f64>,
+    labels: Vec<String>,
+}
+
+impl BinnedColumn {
+    fn new(idx: usize, name: String, edges: Vec<f64>, round_places: u32) -> Self {
+        let nbins = edges.len() - 1;
+        let labels = edges
+            .windows(2)
+            .enumerate()
+            .map(|(i, edge)| {
+                let (lower, upper) = (
+                    util::round_num(edge[0], round_places),
+                    util::round_num(edge[1], round_places),
+                );
+                if i + 1 == nbins {
+                    format!("[{lower}, {upper}]")
+                } else {
+                    format!("[{lower}, {upper})")
+                }
+            })
+            .collect();
+        Self {
+            idx,
+            name,
+            edges,
+            labels,
+        }
+    }
+
+    /// returns the index of the bin of a value
+    #[inline]
+    fn bin(&self, value: f64) -> usize {
+        self.edges[1..self.edges.len() - 1].partition_point(|edge| *edge <= value)
+    }
+
+    /// returns the value of the column in a record, or None if it's NULL
+    #[inline]
+    fn value(&self, record: &csv::ByteRecord) -> Option<f64> {
+        record.get(self.idx).and_then(parse_value)
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let method = match args.flag_method.to_lowercase().as_str() {
+        "fixed" => BinMethod::Fixed,
+        "fd" | "freedman-diaconis" => BinMethod::FreedmanDiaconis,
+        "sturges" => BinMethod::Sturges,
+        "quantile" => BinMethod::Quantile,
+        _ => {
+            return fail_incorrectusage_clierror!(
+                "Invalid --method: {}. Valid methods are fixed, fd, sturges & quantile.",
+                args.flag_method
+            )
+        },
+    };
+    if args.flag_bins == 0 {
+        return fail_incorrectusage_clierror!("--bins must be greater than zero.");
+    }
+    if let Some(width) = args.flag_width {
+        if method != BinMethod::Fixed {
+            return fail_incorrectusage_clierror!("--width can only be used with --method fixed.");
+        }
+        if !(width.is_finite() && width > 0.0) {
+            return fail_incorrectusage_clierror!("--width must be a positive number.");
+        }
+    }
+
+    let rconfig = Config::new(args.arg_input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(args.flag_select.clone());
+    if rconfig.is_stdin() {
+        return fail_incorrectusage_clierror!(
+            "bins cannot read from stdin, as it uses the stats cache of the input file."
+        );
+    }
+
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let sel = rconfig.selection(&headers)?;
+
+    let mut csv_stats = args.stats_records(args.flag_force)?;
+    if method == BinMethod::FreedmanDiaconis
+        && !args.flag_force
+        && sel.iter().any(|&i| {
+            csv_stats
+                .get(i)
+                .is_some_and(|stats| is_numeric(stats) && stats.iqr.is_none())
+        })
+    {
+        // the stats cache was compiled without the quartiles we need for the IQR
+        csv_stats = args.stats_records(true)?;
+    }
+    if csv_stats.len() != headers.len() {
+        return fail_clierror!(
+            "The stats cache does not match the columns of the input. Use --force to recompute it."
+        );
+    }
+
+    let record_count = util::count_rows(&rconfig)?;
+    let binnable: Vec<(usize, String, &StatsData, f64, f64)> = sel
+        .iter()
+        .filter_map(|&i| {
+            let stats = &csv_stats[i];
+            if !is_numeric(stats) {
+                return None;
+            }
+            // columns with only NULLs have no min & max
+            let min = stats.min.as_deref()?.parse::<f64>().ok()?;
+            let max = stats.max.as_deref()?.parse::<f64>().ok()?;
+            let name = if rconfig.no_headers {
+                (i + 1).to_string()
+            } else {
+                String::from_utf8_lossy(&headers[i]).into_owned()
+            };
+            Some((i, name, stats, min, max))
+        })
+        .collect();
+    if binnable.is_empty() {
+        return fail_incorrectusage_clierror!("No Integer or Float columns to bin were selected.");
+    }
+    if let Some(width) = args.flag_width {
+        #[allow(clippy::cast_precision_loss)]
+        let max_bins = MAX_BINS as f64;
+        if let Some((_, name, ..)) = binnable
+            .iter()
+            .find(|(.., min, max)| (max - min) / width > max_bins)
+        {
+            return fail_incorrectusage_clierror!(
+                "--width {width} creates more than {MAX_BINS} bins for the {name} column. Use a \
+                 larger --width."
+            );
+        }
+    }
+
+    let columns: Vec<BinnedColumn> = if method == BinMethod::Quantile {
+        if let Some(path) = rconfig.path.clone() {
+            util::mem_file_check(&path, false, args.flag_memcheck)?;
+        }
+        let mut values: Vec<Vec<f64>> = vec![Vec::new(); binnable.len()];
+        let mut record = csv::ByteRecord::new();
+        while rdr.read_byte_record(&mut record)? {
+            for ((idx, ..), col_values) in binnable.iter().zip(values.iter_mut()) {
+                if let Some(value) = record.get(*idx).and_then(parse_value) {
+                    col_values.push(value);
+                }
+            }
+        }
+        binnable
+            .into_iter()
+            .zip(values)
+            .map(|((idx, name, _, min, max), mut col_values)| {
+                col_values.sort_unstable_by(f64::total_cmp);
+                let edges =
+                    quantile_edges(&col_values, args.flag_bins).unwrap_or_else(|| vec![min, max]);
+                BinnedColumn::new(idx, name, edges, args.flag_round)
+            })
+            .collect()
+    } else {
+        binnable
+            .into_iter()
+            .map(|(idx, name, stats, min, max)| {
+                let n = record_count.saturating_sub(stats.nullcount);
+                let edges = match method {
+                    BinMethod::Fixed => args.flag_width.map_or_else(
+                        || equal_width_edges(min, max, args.flag_bins),
+                        |width| fixed_width_edges(min, max, width),
+                    ),
+                    BinMethod::FreedmanDiaconis => {
+                        freedman_diaconis_edges(min, max, stats.iqr.unwrap_or_default(), n)
+                    },
+                    _ => equal_width_edges(min, max, sturges_bins(n)),
+                };
+                BinnedColumn::new(idx, name, edges, args.flag_round)
+            })
+            .collect()
+    };
+
+    // (re)start reading the input from its first record
+    let mut rdr = rconfig.reader()?;
+    let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+    let mut record = csv::ByteRecord::new();
+
+    if args.flag_label {
+        if !rconfig.no_headers {
+            let mut label_headers = rdr.byte_headers()?.clone();
+            for column in &columns {
+                label_headers.push_field(format!("{}_bin", column.name).as_bytes());
+            }
+            wtr.write_byte_record(&label_headers)?;
+        }
+        while rdr.read_byte_record(&mut record)? {
+            for column in &columns {
+                match column.value(&record) {
+                    Some(value) => record.push_field(column.labels[column.bin(value)].as_bytes()),
+                    None => record.push_field(b""),
+                }
+            }
+            wtr.write_byte_record(&record)?;
+        }
+        return Ok(wtr.flush()?);
+    }
+
+    let mut counts: Vec<Vec<u64>> = columns
+        .iter()
+        .map(|column| vec![0; column.labels.len()])
+        .collect();
+    while rdr.read_byte_record(&mut record)? {
+        for (column, col_counts) in columns.iter().zip(counts.iter_mut()) {
+            if let Some(value) = column.value(&record) {
+                col_counts[column.bin(value)] += 1;
+            }
+        }
+    }
+
+    wtr.write_record([
+        "field",
+        "bin",
+        "lower",
+        "upper",
+        "count",
+        "percentage",
+        "cumulative_percentage",
+    ])?;
+    let mut itoa_buffer = itoa::Buffer::new();
+    for (column, col_counts) in columns.iter().zip(counts) {
+        #[allow(clippy::cast_precision_loss)]
+        let pct_factor = 100.0 / col_counts.iter().sum::<u64>().max(1) as f64;
+        let mut cumulative_count = 0_u64;
+        for (i, count) in col_counts.into_iter().enumerate() {
+            cumulative_count += count;
+            #[allow(clippy::cast_precision_loss)]
+            let (percentage, cumulative_percentage) = (
+                count as f64 * pct_factor,
+                cumulative_count as f64 * pct_factor,
+            );
+            wtr.write_record([
+                column.name.as_bytes(),
+                column.labels[i].as_bytes(),
+                util::round_num(column.edges[i], args.flag_round).as_bytes(),
+                util::round_num(column.edges[i + 1], args.flag_round).as_bytes(),
+                itoa_buffer.format(count).as_bytes(),
+                util::round_num(percentage, args.flag_round).as_bytes(),
+                util::round_num(cumulative_percentage, args.flag_round).as_bytes(),
+            ])?;
+        }
+    }
+
+    Ok(wtr.flush()?)
+}
+
+impl Args {
+    /// get the stats records of all the columns from the stats cache,
+    /// (re)computing them if the cache is not current, or if force is set
+    fn stats_records(&self, force: bool) -> CliResult<Vec<StatsData>> {
+        let schema_args = util::SchemaArgs {
+            flag_enum_threshold:  0,
+            flag_ignore_case:     false,
+            flag_strict_dates:    false,
+            // we still get all the stats columns so we can use the stats cache
+            flag_pattern_columns: SelectColumns::parse("").unwrap(),
+            flag_dates_whitelist: String::new(),
+            flag_prefer_dmy:      false,
+            flag_force:           force,
+            flag_stdout:          false,
+            flag_jobs:            Some(util::njobs(self.flag_jobs)),
+            flag_no_headers:      self.flag_no_headers,
+            flag_delimiter:       self.flag_delimiter,
+            arg_input:            self.arg_input.clone(),
+            flag_memcheck:        self.flag_memcheck,
+        };
+        let (_, csv_stats) = get_stats_records(&schema_args, StatsMode::Bins)?;
+        Ok(csv_stats)
+    }
+}
+
+#[inline]
+fn parse_value(field: &[u8]) -> Option<f64> {
+    fast_float2::parse::<f64, &[u8]>(util::trim_bs_whitespace(field)).ok()
+}
+
+#[inline]
+fn is_numeric(stats: &StatsData) -> bool {
+    stats.r#type == "Integer" || stats.r#type == "Float"
+}
+
+/// nbins bins of the same width between min & max
+fn equal_width_edges(min: f64, max: f64, nbins: usize) -> Vec<f64> {
+    if max <= min {
+        // a column with a single value has a single bin
+        return vec![min, max];
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let width = (max - min) / nbins as f64;
+    #[allow(clippy::cast_precision_loss)]
+    let mut edges: Vec<f64> = (0..nbins).map(|i| min + i as f64 * width).collect();
+    // use max as the last edge, so it's not lost to floating point errors
+    edges.push(max);
+    edges
+}
+
+/// bins of the given width starting from min, with the last bin including max
+fn fixed_width_edges(min: f64, max: f64, width: f64) -> Vec<f64> {
+    let nbins = ((max - min) / width).ceil().max(1.0) as usize;
+    #[allow(clippy::cast_precision_loss)]
+    (0..=nbins).map(|i| min + i as f64 * width).collect()
+}
+
+/// the number of bins of the Sturges rule for n values
+fn sturges_bins(n: u64) -> usize {
+    #[allow(clippy::cast_precision_loss)]
+    let log2_n = (n.max(1) as f64).log2();
+    log2_n.ceil() as usize + 1
+}
+
+/// equal-width bins, with the bin width of the Freedman–Diaconis rule
+fn freedman_diaconis_edges(min: f64, max: f64, iqr: f64, n: u64) -> Vec<f64> {
+    #[allow(clippy::cast_precision_loss)]
+    let width = 2.0 * iqr / (n.max(1) as f64).cbrt();
+    if width > 0.0 && width.is_finite() {
+        let nbins = ((max - min) / width).ceil().clamp(1.0, MAX_BINS as f64) as usize;
+        equal_width_edges(min, max, nbins)
+    } else {
+        equal_width_edges(min, max, sturges_bins(n))
+    }
+}
+
+/// the edges of nbins quantile bins of the sorted values, interpolating linearly
+/// between the closest ranks, with equal edges merged.
+/// Returns None if there are no values.
+fn quantile_edges(sorted_values: &[f64], nbins: usize) -> Option<Vec<f64>> {
+    let last = sorted_values.len().checked_sub(1)?;
+    #[allow(clippy::cast_precision_loss)]
+    let mut edges: Vec<f64> = (0..=nbins)
+        .map(|i| {
+            let rank = (i * last) as f64 / nbins as f64;
+            let lower = rank.floor() as usize;
+            let upper = rank.ceil() as usize;
+            sorted_values[lower]
+                + (sorted_values[upper] - sorted_values[lower]) * (rank - lower as f64)
+        })
+        .collect();
+    edges.dedup();
+    if edges.len() == 1 {
+        edges.push(edges[0]);
+    }
+    Some(edges)
+}
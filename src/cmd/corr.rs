@@ -0,0 +1,615 @@
+static USAGE: &str = r#"
+Compute the correlation or covariance matrix of the numeric columns of a CSV.
+
+The following methods are supported:
+
+  pearson:     the Pearson correlation coefficient, which measures linear relationships.
+  spearman:    the Spearman rank correlation coefficient, which measures monotonic
+               relationships. Tied values get the average of their ranks.
+  kendall:     the Kendall rank correlation coefficient (tau-b, which accounts for ties).
+  covariance:  the sample covariance.
+
+Pearson correlations & covariances are computed in a single streaming pass, in parallel
+chunks if the CSV is indexed. As the rank correlations need to rank all the values, the
+numeric columns are loaded into memory for the spearman & kendall methods, with the
+pairs of columns correlated in parallel.
+
+The data type of each column is inferred using the stats cache (see `qsv stats --help`),
+creating it if required, and only Integer & Float columns are correlated - the other
+selected columns are ignored. NULLs are ignored pairwise, i.e. the correlation of two
+columns only uses the rows where both columns have a value. Correlations that cannot be
+computed (e.g. with a column with a constant value) are empty.
+
+As the stats cache is used, the input must be a file and cannot be stdin.
+
+The following output formats are supported:
+
+  wide:  the matrix, with a "field" column followed by a column for each numeric column.
+  long:  a row for each pair of numeric columns, with the following columns:
+             field1,field2,n,<method>
+         where n is the number of rows where both columns have a value.
+  json:  a JSON object with the method, the numeric columns & the matrix, e.g.
+             {"method":"pearson","fields":["a","b"],"matrix":[[1.0,0.5],[0.5,1.0]]}
+
+Examples:
+
+  # the Pearson correlation matrix of all the numeric columns
+  qsv corr data.csv
+
+  # the Spearman correlations of each pair of numeric columns
+  qsv corr --method spearman --format long data.csv
+
+  # the covariance matrix of some of the columns, as JSON
+  qsv corr -s price,quantity,discount -m covariance -f json sales.csv
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_corr.rs.
+
+Usage:
+    qsv corr [options] [<input>]
+    qsv corr --help
+
+corr options:
+    -s, --select <arg>     Select a subset of columns to correlate.
+                           See 'qsv select --help' for the format details.
+    -m, --method <arg>     The method - pearson, spearman, kendall or covariance.
+                           [default: pearson]
+    -f, --format <arg>     The output format - wide, long or json.
+                           [default: wide]
+    --round <arg>          Round the correlations & covariances to <arg> decimal places.
+                           [default: 4]
+    --force                Force recomputing the stats cache, even if it's current.
+    -j, --jobs <arg>       The number of jobs to run in parallel.
+                           When not set, the number of jobs is set to the
+                           number of CPUs detected.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row will NOT be interpreted as
+                           column names. The columns will then be named by their
+                           1-based indices.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character. (default: ,)
+    --memcheck             Check if there is enough memory to load the entire
+                           CSV into memory for the rank methods using
+                           CONSERVATIVE heuristics.
+"#;
+
+use std::{fs, io::Write};
+
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use serde::Deserialize;
+use serde_json::json;
+use stats::{merge_all, Commute};
+use threadpool::ThreadPool;
+
+use crate::{
+    cmd::stats::StatsData,
+    config::{Config, Delimiter},
+    index::{Indexed, IndexedSource},
+    select::SelectColumns,
+    util,
+    util::{get_stats_records, StatsMode},
+    CliResult,
+};
+
+#[derive(Clone, Deserialize)]
+struct Args {
+    arg_input:       Option<String>,
+    flag_select:     SelectColumns,
+    flag_method:     String,
+    flag_format:     String,
+    flag_round:      u32,
+    flag_force:      bool,
+    flag_jobs:       Option<usize>,
+    flag_output:     Option<String>,
+    flag_no_headers: bool,
+    flag_delimiter:  Option<Delimiter>,
+    flag_memcheck:   bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CorrMethod {
+    Pearson,
+    Spearman,
+    Kendall,
+    Covariance,
+}
+
+impl CorrMethod {
+    const fn name(self) -> &'static str {
+        match self {
+            CorrMethod::Pearson => "pearson",
+            CorrMethod::Spearman => "spearman",
+            CorrMethod::Kendall => "kendall",
+            CorrMethod::Covariance => "covariance",
+        }
+    }
+}
+
+/// The streaming co-moments of a pair of columns, from which their
+/// Pearson correlation & covariance are computed
+#[derive(Clone, Copy, Default)]
+struct CoMoments {
+    n:      u64,
+    mean_x: f64,
+    mean_y: f64,
+    m2_x:   f64,
+    m2_y:   f64,
+    c_xy:   f64,
+}
+
+impl CoMoments {
+    #[inline]
+    fn add(&mut self, x: f64, y: f64) {
+        self.n += 1;
+        #[allow(clippy::cast_precision_loss)]
+        let n = self.n as f64;
+        let dx = x - self.mean_x;
+        self.mean_x += dx / n;
+        let dy = y - self.mean_y;
+        self.mean_y += dy / n;
+        self.m2_x += dx * (x - self.mean_x);
+        self.m2_y += dy * (y - self.mean_y);
+        self.c_xy += dx * (y - self.mean_y);
+    }
+
+    fn pearson(&self) -> f64 {
+        if self.n < 2 || self.m2_x <= 0.0 || self.m2_y <= 0.0 {
+            return f64::NAN;
+        }
+        (self.c_xy / (self.m2_x * self.m2_y).sqrt()).clamp(-1.0, 1.0)
+    }
+
+    fn covariance(&self) -> f64 {
+        if self.n < 2 {
+            return f64::NAN;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let dof = (self.n - 1) as f64;
+        self.c_xy / dof
+    }
+}
+
+impl Commute for CoMoments {
+    fn merge(&mut self, other: CoMoments) {
+        if other.n == 0 {
+            return;
+        }
+        if self.n == 0 {
+            *self = other;
+            return;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let (n1, n2) = (self.n as f64, other.n as f64);
+        let n = n1 + n2;
+        let dx = other.mean_x - self.mean_x;
+        let dy = other.mean_y - self.mean_y;
+        self.m2_x += other.m2_x + dx * dx * n1 * n2 / n;
+        self.m2_y += other.m2_y + dy * dy * n1 * n2 / n;
+        self.c_xy += other.c_xy + dx * dy * n1 * n2 / n;
+        self.mean_x += dx * n2 / n;
+        self.mean_y += dy * n2 / n;
+        self.n += other.n;
+    }
+}
+
+/// the number of rows & the correlation (or covariance) of each pair of columns,
+/// stored as the upper triangle of the matrix, including its diagonal
+struct CorrMatrix {
+    fields: Vec<String>,
+    pairs:  Vec<(u64, f64)>,
+}
+
+impl CorrMatrix {
+    #[inline]
+    fn get(&self, i: usize, j: usize) -> (u64, f64) {
+        let (i, j) = if i <= j { (i, j) } else { (j, i) };
+        self.pairs[pair_index(self.fields.len(), i, j)]
+    }
+}
+
+/// the index of the pair of columns (i, j), with i <= j, in the upper triangle
+/// of a k x k matrix stored row by row
+#[inline]
+const fn pair_index(k: usize, i: usize, j: usize) -> usize {
+    // the rows before row i hold k + (k - 1) + ... + (k - i + 1) pairs
+    i * (2 * k - i + 1) / 2 + (j - i)
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let method = match args.flag_method.to_lowercase().as_str() {
+        "pearson" => CorrMethod::Pearson,
+        "spearman" => CorrMethod::Spearman,
+        "kendall" => CorrMethod::Kendall,
+        "covariance" | "cov" => CorrMethod::Covariance,
+        _ => {
+            return fail_incorrectusage_clierror!(
+                "Invalid --method: {}. Valid methods are pearson, spearman, kendall & covariance.",
+                args.flag_method
+            )
+        },
+    };
+    let format = args.flag_format.to_lowercase();
+    if !matches!(format.as_str(), "wide" | "long" | "json") {
+        return fail_incorrectusage_clierror!(
+            "Invalid --format: {}. Valid formats are wide, long & json.",
+            args.flag_format
+        );
+    }
+
+    let rconfig = args.rconfig();
+    if rconfig.is_stdin() {
+        return fail_incorrectusage_clierror!(
+            "corr cannot read from stdin, as it uses the stats cache of the input file."
+        );
+    }
+
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let sel = rconfig.selection(&headers)?;
+    drop(rdr);
+
+    let csv_stats = args.stats_records()?;
+    if csv_stats.len() != headers.len() {
+        return fail_clierror!(
+            "The stats cache does not match the columns of the input. Use --force to recompute it."
+        );
+    }
+
+    // only Integer & Float columns are correlated
+    let columns: Vec<usize> = sel
+        .iter()
+        .copied()
+        .filter(|&i| is_numeric(&csv_stats[i]))
+        .collect();
+    if columns.len() < 2 {
+        return fail_incorrectusage_clierror!(
+            "At least two Integer or Float columns are required to compute correlations."
+        );
+    }
+    let fields: Vec<String> = columns
+        .iter()
+        .map(|&i| {
+            if rconfig.no_headers {
+                (i + 1).to_string()
+            } else {
+                String::from_utf8_lossy(&headers[i]).into_owned()
+            }
+        })
+        .collect();
+
+    let njobs = util::njobs(args.flag_jobs);
+    let pairs = match method {
+        CorrMethod::Pearson | CorrMethod::Covariance => {
+            let comoments = match rconfig.indexed()? {
+                Some(ref idx) if njobs > 1 => args.parallel_comoments(idx, &columns, njobs)?,
+                _ => comoments(&columns, rconfig.reader()?.byte_records()),
+            };
+            comoments
+                .iter()
+                .map(|c| {
+                    if method == CorrMethod::Pearson {
+                        (c.n, c.pearson())
+                    } else {
+                        (c.n, c.covariance())
+                    }
+                })
+                .collect()
+        },
+        CorrMethod::Spearman | CorrMethod::Kendall => {
+            if let Some(path) = rconfig.path.clone() {
+                util::mem_file_check(&path, false, args.flag_memcheck)?;
+            }
+            let values = args.load_columns(&columns)?;
+            let k = columns.len();
+            let pair_list: Vec<(usize, usize)> =
+                (0..k).flat_map(|i| (i..k).map(move |j| (i, j))).collect();
+            pair_list
+                .into_par_iter()
+                .map(|(i, j)| rank_correlation(method, &values[i], &values[j]))
+                .collect()
+        },
+    };
+
+    let matrix = CorrMatrix { fields, pairs };
+    args.write_matrix(&matrix, method, &format)
+}
+
+impl Args {
+    fn rconfig(&self) -> Config {
+        Config::new(self.arg_input.as_ref())
+            .delimiter(self.flag_delimiter)
+            .no_headers(self.flag_no_headers)
+            .select(self.flag_select.clone())
+    }
+
+    /// get the stats records of all the columns from the stats cache,
+    /// computing them if the cache is not current
+    fn stats_records(&self) -> CliResult<Vec<StatsData>> {
+        let schema_args = util::SchemaArgs {
+            flag_enum_threshold:  0,
+            flag_ignore_case:     false,
+            flag_strict_dates:    false,
+            // we still get all the stats columns so we can use the stats cache
+            flag_pattern_columns: SelectColumns::parse("").unwrap(),
+            flag_dates_whitelist: String::new(),
+            flag_prefer_dmy:      false,
+            flag_force:           self.flag_force,
+            flag_stdout:          false,
+            flag_jobs:            Some(util::njobs(self.flag_jobs)),
+            flag_no_headers:      self.flag_no_headers,
+            flag_delimiter:       self.flag_delimiter,
+            arg_input:            self.arg_input.clone(),
+            flag_memcheck:        self.flag_memcheck,
+        };
+        let (_, csv_stats) = get_stats_records(&schema_args, StatsMode::Corr)?;
+        Ok(csv_stats)
+    }
+
+    fn parallel_comoments(
+        &self,
+        idx: &Indexed<IndexedSource, fs::File>,
+        columns: &[usize],
+        njobs: usize,
+    ) -> CliResult<Vec<CoMoments>> {
+        let idx_count = idx.count() as usize;
+        let chunk_size = util::chunk_size(idx_count, njobs);
+        let nchunks = util::num_of_chunks(idx_count, chunk_size);
+
+        let pool = ThreadPool::new(njobs);
+        let (send, recv) = crossbeam_channel::bounded(0);
+        for i in 0..nchunks {
+            let (send, args, columns) = (send.clone(), self.clone(), columns.to_vec());
+            pool.execute(move || {
+                // safety: we know the file is indexed and seekable
+                let mut idx = args.rconfig().indexed().unwrap().unwrap();
+                idx.seek((i * chunk_size) as u64).unwrap();
+                let it = idx.byte_records().take(chunk_size);
+                send.send(comoments(&columns, it)).unwrap();
+            });
+        }
+        drop(send);
+        Ok(merge_all(recv.iter())
+            .unwrap_or_else(|| vec![CoMoments::default(); pair_count(columns.len())]))
+    }
+
+    /// load the values of the columns into memory, with NaN for NULLs
+    fn load_columns(&self, columns: &[usize]) -> CliResult<Vec<Vec<f64>>> {
+        let mut rdr = self.rconfig().reader()?;
+        let mut values: Vec<Vec<f64>> = vec![Vec::new(); columns.len()];
+        let mut record = csv::ByteRecord::new();
+        while rdr.read_byte_record(&mut record)? {
+            for (&col, col_values) in columns.iter().zip(values.iter_mut()) {
+                col_values.push(record.get(col).and_then(parse_value).unwrap_or(f64::NAN));
+            }
+        }
+        Ok(values)
+    }
+
+    fn write_matrix(&self, matrix: &CorrMatrix, method: CorrMethod, format: &str) -> CliResult<()> {
+        let k = matrix.fields.len();
+        let round = |value: f64| util::round_num(value, self.flag_round);
+
+        if format == "json" {
+            let rows: Vec<Vec<serde_json::Value>> = (0..k)
+                .map(|i| {
+                    (0..k)
+                        .map(|j| {
+                            round(matrix.get(i, j).1)
+                                .parse::<f64>()
+                                .map_or(serde_json::Value::Null, serde_json::Value::from)
+                        })
+                        .collect()
+                })
+                .collect();
+            let json = json!({
+                "method": method.name(),
+                "fields": matrix.fields,
+                "matrix": rows,
+            });
+            let mut out: Box<dyn Write> = match self.flag_output {
+                Some(ref output) => Box::new(fs::File::create(output)?),
+                None => Box::new(std::io::stdout()),
+            };
+            writeln!(out, "{json}")?;
+            return Ok(out.flush()?);
+        }
+
+        let mut wtr = Config::new(self.flag_output.as_ref()).writer()?;
+        if format == "long" {
+            wtr.write_record(["field1", "field2", "n", method.name()])?;
+            let mut itoa_buffer = itoa::Buffer::new();
+            for i in 0..k {
+                for j in i + 1..k {
+                    let (n, value) = matrix.get(i, j);
+                    wtr.write_record([
+                        matrix.fields[i].as_bytes(),
+                        matrix.fields[j].as_bytes(),
+                        itoa_buffer.format(n).as_bytes(),
+                        round(value).as_bytes(),
+                    ])?;
+                }
+            }
+        } else {
+            let mut header = vec!["field".to_string()];
+            header.extend(matrix.fields.iter().cloned());
+            wtr.write_record(&header)?;
+            for i in 0..k {
+                let mut row = vec![matrix.fields[i].clone()];
+                row.extend((0..k).map(|j| round(matrix.get(i, j).1)));
+                wtr.write_record(&row)?;
+            }
+        }
+        Ok(wtr.flush()?)
+    }
+}
+
+#[inline]
+fn is_numeric(stats: &StatsData) -> bool {
+    stats.r#type == "Integer" || stats.r#type == "Float"
+}
+
+#[inline]
+fn parse_value(field: &[u8]) -> Option<f64> {
+    fast_float2::parse::<f64, &[u8]>(util::trim_bs_whitespace(field))
+        .ok()
+        .filter(|value| !value.is_nan())
+}
+
+/// the number of pairs of k columns, including each column with itself
+#[inline]
+const fn pair_count(k: usize) -> usize {
+    k * (k + 1) / 2
+}
+
+/// the co-moments of each pair of columns, in the order of CorrMatrix.pairs
+fn comoments<I>(columns: &[usize], it: I) -> Vec<CoMoments>
+where
+    I: Iterator<Item = csv::Result<csv::ByteRecord>>,
+{
+    let k = columns.len();
+    let mut comoments = vec![CoMoments::default(); pair_count(k)];
+    let mut values: Vec<Option<f64>> = vec![None; k];
+    for row in it {
+        // safety: we know the row is not empty
+        let row = row.unwrap();
+        for (value, &col) in values.iter_mut().zip(columns) {
+            *value = row.get(col).and_then(parse_value);
+        }
+        let mut pair = 0;
+        for i in 0..k {
+            let Some(x) = values[i] else {
+                pair += k - i;
+                continue;
+            };
+            for y in &values[i..] {
+                if let Some(y) = y {
+                    comoments[pair].add(x, *y);
+                }
+                pair += 1;
+            }
+        }
+    }
+    comoments
+}
+
+/// the number of rows where both columns have a value, and their
+/// Spearman or Kendall correlation
+fn rank_correlation(method: CorrMethod, x: &[f64], y: &[f64]) -> (u64, f64) {
+    let (x, y): (Vec<f64>, Vec<f64>) = x
+        .iter()
+        .zip(y)
+        .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+        .map(|(x, y)| (*x, *y))
+        .unzip();
+    let n = x.len() as u64;
+    if method == CorrMethod::Kendall {
+        return (n, kendall_tau_b(&x, &y));
+    }
+    let mut comoments = CoMoments::default();
+    for (rank_x, rank_y) in ranks(&x).into_iter().zip(ranks(&y)) {
+        comoments.add(rank_x, rank_y);
+    }
+    (n, comoments.pearson())
+}
+
+/// the ranks of the values, starting from 1, with tied values getting the average
+/// of their ranks
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_unstable_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        // the values at positions start..end are tied, so they share the average
+        // of the ranks start + 1 ..= end
+        #[allow(clippy::cast_precision_loss)]
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Kendall's tau-b, using Knight's O(n log n) algorithm
+fn kendall_tau_b(x: &[f64], y: &[f64]) -> f64 {
+    let mut pairs: Vec<(f64, f64)> = x.iter().copied().zip(y.iter().copied()).collect();
+    pairs.sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    let x_ties = tied_pairs(pairs.iter().map(|pair| pair.0));
+    let joint_ties = tied_pairs(pairs.iter().copied());
+
+    // after sorting by x, the discordant pairs are the swaps needed to sort by y
+    let mut ys: Vec<f64> = pairs.into_iter().map(|pair| pair.1).collect();
+    let mut buf = vec![0.0; ys.len()];
+    let swaps = sort_counting_swaps(&mut ys, &mut buf);
+    let y_ties = tied_pairs(ys.into_iter());
+
+    let n = x.len() as u64;
+    let total = n * n.saturating_sub(1) / 2;
+    #[allow(clippy::cast_precision_loss)]
+    let (con_minus_dis, denominator) = (
+        total as f64 - x_ties as f64 - y_ties as f64 + joint_ties as f64 - 2.0 * swaps as f64,
+        ((total - x_ties) as f64 * (total - y_ties) as f64).sqrt(),
+    );
+    if denominator > 0.0 {
+        (con_minus_dis / denominator).clamp(-1.0, 1.0)
+    } else {
+        f64::NAN
+    }
+}
+
+/// the number of pairs of tied values in the runs of equal values of a sorted iterator
+fn tied_pairs<T: PartialEq>(mut sorted: impl Iterator<Item = T>) -> u64 {
+    let Some(mut prev) = sorted.next() else {
+        return 0;
+    };
+    let mut tied = 0;
+    let mut run = 1_u64;
+    for value in sorted {
+        if value == prev {
+            run += 1;
+        } else {
+            tied += run * (run - 1) / 2;
+            run = 1;
+            prev = value;
+        }
+    }
+    tied + run * (run - 1) / 2
+}
+
+/// merge sorts the values, returning the number of swaps of adjacent values needed
+fn sort_counting_swaps(values: &mut [f64], buf: &mut [f64]) -> u64 {
+    let n = values.len();
+    if n < 2 {
+        return 0;
+    }
+    let mid = n / 2;
+    let mut swaps = sort_counting_swaps(&mut values[..mid], &mut buf[..mid])
+        + sort_counting_swaps(&mut values[mid..], &mut buf[mid..]);
+    let (mut i, mut j, mut k) = (0, mid, 0);
+    while i < mid && j < n {
+        if values[j] < values[i] {
+            buf[k] = values[j];
+            j += 1;
+            swaps += (mid - i) as u64;
+        } else {
+            buf[k] = values[i];
+            i += 1;
+        }
+        k += 1;
+    }
+    buf[k..k + mid - i].copy_from_slice(&values[i..mid]);
+    k += mid - i;
+    buf[k..n].copy_from_slice(&values[j..n]);
+    values.copy_from_slice(&buf[..n]);
+    swaps
+}
@@ -70,6 +70,8 @@ Common options:
 use log::info;
 use serde::Deserialize;
 
+#[cfg(feature = "polars")]
+use crate::formats::InputFormat;
 use crate::{
     config::{Config, Delimiter},
     util, CliError, CliResult,
@@ -151,9 +153,13 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     (idx.count(), empty_record_stats)
                 },
                 None => {
-                    // if --no-polars or its a snappy compressed file, use the regular CSV reader
+                    // if --no-polars or its a compressed or non-CSV file,
+                    // use the regular CSV reader
                     #[cfg(feature = "polars")]
-                    if args.flag_no_polars || conf.is_snappy() {
+                    if args.flag_no_polars
+                        || conf.is_compressed()
+                        || conf.input_format() != InputFormat::Csv
+                    {
                         count_input(&conf, count_delims_mode)?
                     } else {
                         let count = polars_count_input(&conf, args.flag_low_memory)?;
@@ -0,0 +1,444 @@
+static USAGE: &str = r#"
+Compute a two-way contingency table (cross-tabulation) of the values of two columns.
+
+The table has a row for each distinct value of <row-col>, and a column for each distinct
+value of <col-col>, both sorted, with "Total" margins. By default, the cells are the number
+of rows with each pair of values. Use --values to show percentages or expected counts instead:
+
+  count:      the number of rows with the pair of values.
+  row_pct:    the percentage of the rows of the row value.
+  col_pct:    the percentage of the rows of the column value.
+  total_pct:  the percentage of all the rows.
+  expected:   the count expected if the two columns were independent,
+              i.e. row total * column total / total.
+
+With --chi2, Pearson's chi-square test of independence of the two columns is also computed,
+and its results are written to stderr as JSON, e.g.
+
+  {"chi2":10.3,"dof":4,"p_value":0.0356,"cramers_v":0.1859,"n":1000}
+
+where Cramér's V measures the strength of the association, from 0 (none) to 1 (perfect).
+Note that the chi-square test is not reliable when expected counts are small (i.e. < 5).
+
+Examples:
+
+  # the number of complaints of each type per borough
+  qsv crosstab Borough "Complaint Type" nyc311.csv
+
+  # the percentage of the complaints of each borough by type, with the chi-square test
+  qsv crosstab --values row_pct --chi2 Borough "Complaint Type" nyc311.csv
+
+  # all the measures of each pair of values, one per row
+  qsv crosstab --long Borough "Complaint Type" nyc311.csv
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_crosstab.rs.
+
+Usage:
+    qsv crosstab [options] <row-col> <col-col> [<input>]
+    qsv crosstab --help
+
+crosstab arguments:
+    <row-col>              The column whose values are the rows of the table.
+    <col-col>              The column whose values are the columns of the table.
+    <input>                The CSV file to read. If not given, input is read from STDIN.
+
+crosstab options:
+    -v, --values <arg>     The values of the cells - count, row_pct, col_pct,
+                           total_pct or expected.
+                           [default: count]
+    --long                 Write a row for each pair of values instead, with the following
+                           columns: <row-col>,<col-col>,count,expected,row_pct,col_pct,total_pct
+                           The margins are not included, and --values is ignored.
+    --no-margins           Don't include the "Total" row & column.
+    --no-nulls             Ignore the rows where either column is empty.
+                           Otherwise, empty values are shown as "(NULL)".
+    --chi2                 Compute the chi-square test of independence, writing its
+                           results to stderr as JSON.
+    --round <arg>          Round percentages, expected counts & test results to
+                           <arg> decimal places.
+                           [default: 4]
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row will NOT be interpreted as
+                           column names. The columns will then be named by their
+                           1-based indices.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character. (default: ,)
+"#;
+
+use ahash::AHashMap;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    config::{Config, Delimiter},
+    select::SelectColumns,
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_row_col:     SelectColumns,
+    arg_col_col:     SelectColumns,
+    arg_input:       Option<String>,
+    flag_values:     String,
+    flag_long:       bool,
+    flag_no_margins: bool,
+    flag_no_nulls:   bool,
+    flag_chi2:       bool,
+    flag_round:      u32,
+    flag_output:     Option<String>,
+    flag_no_headers: bool,
+    flag_delimiter:  Option<Delimiter>,
+}
+
+const NULL_VAL: &[u8] = b"(NULL)";
+const TOTAL: &[u8] = b"Total";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CellValue {
+    Count,
+    RowPct,
+    ColPct,
+    TotalPct,
+    Expected,
+}
+
+/// A contingency table, with its distinct row & column values sorted
+struct Crosstab {
+    row_values: Vec<Vec<u8>>,
+    col_values: Vec<Vec<u8>>,
+    // the counts, indexed by row * col_values.len() + col
+    counts:     Vec<u64>,
+    row_totals: Vec<u64>,
+    col_totals: Vec<u64>,
+    total:      u64,
+}
+
+impl Crosstab {
+    #[inline]
+    fn count(&self, row: usize, col: usize) -> u64 {
+        self.counts[row * self.col_values.len() + col]
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn expected(&self, row: usize, col: usize) -> f64 {
+        self.row_totals[row] as f64 * self.col_totals[col] as f64 / self.total as f64
+    }
+
+    /// the value of a cell, with None for the margins
+    #[allow(clippy::cast_precision_loss)]
+    fn value(&self, value: CellValue, row: Option<usize>, col: Option<usize>) -> f64 {
+        let count = match (row, col) {
+            (Some(row), Some(col)) => self.count(row, col),
+            (Some(row), None) => self.row_totals[row],
+            (None, Some(col)) => self.col_totals[col],
+            (None, None) => self.total,
+        } as f64;
+        let pct = |total: u64| count * 100.0 / total as f64;
+        match value {
+            CellValue::Count => count,
+            CellValue::RowPct => pct(row.map_or(self.total, |row| self.row_totals[row])),
+            CellValue::ColPct => pct(col.map_or(self.total, |col| self.col_totals[col])),
+            CellValue::TotalPct => pct(self.total),
+            CellValue::Expected => match (row, col) {
+                (Some(row), Some(col)) => self.expected(row, col),
+                _ => count,
+            },
+        }
+    }
+
+    /// Pearson's chi-square statistic, its degrees of freedom, p-value & Cramér's V
+    #[allow(clippy::cast_precision_loss)]
+    fn chi2(&self) -> (f64, usize, f64, f64) {
+        let (nrows, ncols) = (self.row_values.len(), self.col_values.len());
+        if nrows == 0 || ncols == 0 || self.total == 0 {
+            // an empty table, e.g. with no rows or only NULLs with --no-nulls
+            return (f64::NAN, 0, f64::NAN, f64::NAN);
+        }
+        let mut chi2 = 0.0;
+        for row in 0..self.row_values.len() {
+            for col in 0..self.col_values.len() {
+                let expected = self.expected(row, col);
+                let diff = self.count(row, col) as f64 - expected;
+                chi2 += diff * diff / expected;
+            }
+        }
+        let dof = (nrows - 1) * (ncols - 1);
+        if dof == 0 {
+            // a single row or column has no association to test
+            return (chi2, dof, f64::NAN, f64::NAN);
+        }
+        let p_value = gamma_q(dof as f64 / 2.0, chi2 / 2.0);
+        let cramers_v = (chi2 / (self.total as f64 * (nrows.min(ncols) - 1) as f64)).sqrt();
+        (chi2, dof, p_value, cramers_v)
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let value = match args.flag_values.to_lowercase().as_str() {
+        "count" => CellValue::Count,
+        "row_pct" => CellValue::RowPct,
+        "col_pct" => CellValue::ColPct,
+        "total_pct" => CellValue::TotalPct,
+        "expected" => CellValue::Expected,
+        _ => {
+            return fail_incorrectusage_clierror!(
+                "Invalid --values: {}. Valid values are count, row_pct, col_pct, total_pct & \
+                 expected.",
+                args.flag_values
+            )
+        },
+    };
+
+    let rconfig = Config::new(args.arg_input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+
+    let mut columns = [0_usize; 2];
+    let mut names: [Vec<u8>; 2] = Default::default();
+    for (i, sel) in [&args.arg_row_col, &args.arg_col_col]
+        .into_iter()
+        .enumerate()
+    {
+        let sel = sel.selection(&headers, !rconfig.no_headers)?;
+        if sel.len() != 1 {
+            return fail_incorrectusage_clierror!(
+                "<row-col> and <col-col> must each select exactly one column."
+            );
+        }
+        columns[i] = sel[0];
+        names[i] = if rconfig.no_headers {
+            (sel[0] + 1).to_string().into_bytes()
+        } else {
+            headers[sel[0]].to_vec()
+        };
+    }
+
+    let crosstab = args.crosstab(&mut rdr, columns)?;
+    let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+    let round = |value: f64| util::round_num(value, args.flag_round);
+    let (nrows, ncols) = (crosstab.row_values.len(), crosstab.col_values.len());
+
+    if args.flag_long {
+        wtr.write_record([
+            names[0].as_slice(),
+            names[1].as_slice(),
+            b"count",
+            b"expected",
+            b"row_pct",
+            b"col_pct",
+            b"total_pct",
+        ])?;
+        let mut itoa_buffer = itoa::Buffer::new();
+        for row in 0..nrows {
+            for col in 0..ncols {
+                wtr.write_record([
+                    crosstab.row_values[row].as_slice(),
+                    crosstab.col_values[col].as_slice(),
+                    itoa_buffer.format(crosstab.count(row, col)).as_bytes(),
+                    round(crosstab.expected(row, col)).as_bytes(),
+                    round(crosstab.value(CellValue::RowPct, Some(row), Some(col))).as_bytes(),
+                    round(crosstab.value(CellValue::ColPct, Some(row), Some(col))).as_bytes(),
+                    round(crosstab.value(CellValue::TotalPct, Some(row), Some(col))).as_bytes(),
+                ])?;
+            }
+        }
+    } else {
+        let margins = !args.flag_no_margins;
+        let mut record = csv::ByteRecord::new();
+        record.push_field(&names[0]);
+        for col_value in &crosstab.col_values {
+            record.push_field(col_value);
+        }
+        if margins {
+            record.push_field(TOTAL);
+        }
+        wtr.write_byte_record(&record)?;
+
+        // the Total row has no row index
+        let rows = (0..nrows).map(Some).chain(margins.then_some(None));
+        for row in rows {
+            record.clear();
+            record.push_field(row.map_or(TOTAL, |row| crosstab.row_values[row].as_slice()));
+            let cols = (0..ncols).map(Some).chain(margins.then_some(None));
+            for col in cols {
+                record.push_field(round(crosstab.value(value, row, col)).as_bytes());
+            }
+            wtr.write_byte_record(&record)?;
+        }
+    }
+    wtr.flush()?;
+
+    if args.flag_chi2 {
+        let (chi2, dof, p_value, cramers_v) = crosstab.chi2();
+        let round_json = |value: f64| {
+            round(value)
+                .parse::<f64>()
+                .map_or(serde_json::Value::Null, serde_json::Value::from)
+        };
+        winfo!(
+            "{}",
+            json!({
+                "chi2": round_json(chi2),
+                "dof": dof,
+                "p_value": round_json(p_value),
+                "cramers_v": round_json(cramers_v),
+                "n": crosstab.total,
+            })
+        );
+    }
+    Ok(())
+}
+
+impl Args {
+    /// counts the rows with each pair of values of the two columns
+    fn crosstab(
+        &self,
+        rdr: &mut csv::Reader<Box<dyn std::io::Read + Send>>,
+        [row_col, col_col]: [usize; 2],
+    ) -> CliResult<Crosstab> {
+        let mut pair_counts: AHashMap<(Vec<u8>, Vec<u8>), u64> = AHashMap::new();
+        let mut record = csv::ByteRecord::new();
+        while rdr.read_byte_record(&mut record)? {
+            let (row_value, col_value) = (&record[row_col], &record[col_col]);
+            if self.flag_no_nulls && (row_value.is_empty() || col_value.is_empty()) {
+                continue;
+            }
+            let null_or = |value: &[u8]| {
+                if value.is_empty() {
+                    NULL_VAL.to_vec()
+                } else {
+                    value.to_vec()
+                }
+            };
+            *pair_counts
+                .entry((null_or(row_value), null_or(col_value)))
+                .or_default() += 1;
+        }
+
+        let mut row_values: Vec<Vec<u8>> = pair_counts.keys().map(|(row, _)| row.clone()).collect();
+        row_values.sort_unstable();
+        row_values.dedup();
+        let mut col_values: Vec<Vec<u8>> = pair_counts.keys().map(|(_, col)| col.clone()).collect();
+        col_values.sort_unstable();
+        col_values.dedup();
+
+        let row_index: AHashMap<&[u8], usize> = row_values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (value.as_slice(), i))
+            .collect();
+        let col_index: AHashMap<&[u8], usize> = col_values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (value.as_slice(), i))
+            .collect();
+
+        let ncols = col_values.len();
+        let mut counts = vec![0; row_values.len() * ncols];
+        let mut row_totals = vec![0; row_values.len()];
+        let mut col_totals = vec![0; ncols];
+        let mut total = 0;
+        for ((row_value, col_value), count) in &pair_counts {
+            let (row, col) = (
+                row_index[row_value.as_slice()],
+                col_index[col_value.as_slice()],
+            );
+            counts[row * ncols + col] = *count;
+            row_totals[row] += count;
+            col_totals[col] += count;
+            total += count;
+        }
+
+        Ok(Crosstab {
+            row_values,
+            col_values,
+            counts,
+            row_totals,
+            col_totals,
+            total,
+        })
+    }
+}
+
+/// the natural log of the gamma function, using the Lanczos approximation
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000_000_000_190_015;
+    let mut y = x;
+    for coefficient in COEFFICIENTS {
+        y += 1.0;
+        series += coefficient / y;
+    }
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+/// the regularized upper incomplete gamma function Q(a, x), i.e. the p-value
+/// of a chi-square statistic of 2x with 2a degrees of freedom
+fn gamma_q(a: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 1000;
+    const EPSILON: f64 = 1e-15;
+    const TINY: f64 = 1e-300;
+
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let ln_prefix = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        // the series of the lower incomplete gamma function P(a, x) converges faster
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut ap = a;
+        for _ in 0..MAX_ITERATIONS {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        (1.0 - sum * ln_prefix.exp()).max(0.0)
+    } else {
+        // otherwise, evaluate the continued fraction of Q(a, x) with Lentz's method
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / TINY;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..=MAX_ITERATIONS {
+            #[allow(clippy::cast_precision_loss)]
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = b + an / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPSILON {
+                break;
+            }
+        }
+        ln_prefix.exp() * h
+    }
+}
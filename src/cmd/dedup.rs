@@ -12,6 +12,35 @@ Either way, the output will not only be deduplicated, it will also be sorted.
 
 A duplicate count will also be sent to <stderr>.
 
+FUZZY DEDUPLICATION:
+
+With --fuzzy <metric>:<threshold>, rows are instead considered duplicates if the
+similarity of their selected columns is >= threshold, e.g. "Acme Corp" & "ACME Corp."
+when cleaning vendor and customer lists. The similarity of two rows is the mean of the
+similarities of their selected columns (after trimming whitespace, and lowercasing
+with --ignore-case), between 0.0 & 1.0. The metrics are:
+
+  jarowinkler (jw):  Jaro-Winkler similarity
+  levenshtein (lev): Normalized Levenshtein similarity
+  damerau (dl):      Normalized Damerau-Levenshtein similarity
+  tokenset (ts):     Token set similarity - the normalized Levenshtein similarity of
+                     the sorted words the values have in common, with and without
+                     their other sorted words, so word order and extra words matter
+                     less (e.g. "Acme Corp" & "Corp Acme Inc")
+
+Near-duplicates are clustered transitively (i.e. if A is similar to B, and B to C,
+A, B & C are in the same cluster), and only the first row of each cluster is kept.
+The output is in the original order of the rows and is not sorted.
+
+Comparing every pair of rows is quadratic, so use --blocking to only compare rows with
+the same values in the blocking columns (e.g. the same postal code or country).
+
+With --dupes-output, the clusters of near-duplicates are written with two additional
+leading columns: "cluster_id" and "similarity", the similarity of each row to the
+first row of its cluster.
+
+  $ qsv dedup --fuzzy jw:0.9 --select name --blocking city -i vendors.csv
+
 For examples, see https://github.com/dathere/qsv/blob/master/tests/test_dedup.rs.
 
 Usage:
@@ -25,9 +54,39 @@ dedup options:
                                See 'qsv select --help' for the format details.
     -N, --numeric              Compare according to string numerical value
     -i, --ignore-case          Compare strings disregarding case.
+    --collation <locale>       Compare strings using the collation rules of a locale,
+                               e.g. "de" or "de:primary", so rows that only differ in
+                               accents and case are duplicates with "de:primary".
+                               With --sorted, the input must be sorted with the same
+                               --collation. See 'qsv sort --help' for the format details.
+                               Cannot be used with --numeric, --ignore-case or --fuzzy.
     --sorted                   The input is already sorted. Do not load the CSV into
                                memory to sort it first. Meant to be used in tandem and
                                after an extsort.
+    --fuzzy <arg>              Deduplicate near-duplicate rows, in the form
+                               <metric>:<threshold>, e.g. "jw:0.9".
+                               See FUZZY DEDUPLICATION above.
+                               Cannot be used with --numeric, --sorted, --keep or --merge.
+    --blocking <cols>          With --fuzzy, only compare rows with the same values
+                               in these columns.
+                               See 'qsv select --help' for the format details.
+    --keep <arg>               Which row of each group of duplicates to keep:
+                                 first: the first row, in input order.
+                                 last: the last row, in input order.
+                                 max:<col>: the row with the largest value in <col>.
+                                 min:<col>: the row with the smallest value in <col>.
+                               Values are compared as numbers if they're both numeric,
+                               and as strings otherwise (e.g. ISO 8601 timestamps).
+                               Rows with an empty <col> value are only kept if all the
+                               rows of the group are empty, and ties keep the first row.
+                               e.g. to keep the latest snapshot of each record of a CDC
+                               feed: --select id --keep max:updated_at
+                               If neither --keep nor --merge is set, the last row of each
+                               group is kept, or the first one with --sorted.
+    --merge                    Coalesce the duplicates of each group into the row kept
+                               by --keep (default: first), filling its empty fields
+                               with the values of the other rows of the group,
+                               preferring the rows --keep would have kept.
     -D, --dupes-output <file>  Write duplicates to <file>.
     -H, --human-readable       Comma separate duplicate count.
     -j, --jobs <arg>           The number of jobs to run in parallel when sorting
@@ -51,16 +110,21 @@ Common options:
                                CSV into memory using CONSERVATIVE heuristics.
 "#;
 
-use std::cmp;
+use std::{cmp, collections::BTreeSet, io};
 
+use ahash::AHashMap;
 use csv::ByteRecord;
-use rayon::slice::ParallelSliceMut;
+use rayon::{
+    iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator},
+    slice::ParallelSliceMut,
+};
 use serde::Deserialize;
+use strsim::{jaro_winkler, normalized_damerau_levenshtein, normalized_levenshtein};
 
 use crate::{
-    cmd::sort::{iter_cmp, iter_cmp_num},
+    cmd::sort::{iter_cmp, iter_cmp_num, Collation},
     config::{Config, Delimiter},
-    select::SelectColumns,
+    select::{SelectColumns, Selection},
     util, CliResult,
 };
 #[derive(Deserialize)]
@@ -69,7 +133,12 @@ struct Args {
     flag_select:         SelectColumns,
     flag_numeric:        bool,
     flag_ignore_case:    bool,
+    flag_collation:      Option<String>,
     flag_sorted:         bool,
+    flag_fuzzy:          Option<String>,
+    flag_blocking:       Option<SelectColumns>,
+    flag_keep:           Option<String>,
+    flag_merge:          bool,
     flag_dupes_output:   Option<String>,
     flag_output:         Option<String>,
     flag_no_headers:     bool,
@@ -84,13 +153,105 @@ struct Args {
 enum ComparisonMode {
     Numeric,
     IgnoreCase,
+    Collated(Collation),
     Normal,
 }
 
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum FuzzyMetric {
+    JaroWinkler,
+    Levenshtein,
+    Damerau,
+    TokenSet,
+}
+
+impl FuzzyMetric {
+    /// the metric with the given name or alias, e.g. "jarowinkler" or "jw"
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "jarowinkler" | "jaro-winkler" | "jw" => Some(FuzzyMetric::JaroWinkler),
+            "levenshtein" | "lev" => Some(FuzzyMetric::Levenshtein),
+            "damerau" | "damerau-levenshtein" | "dl" => Some(FuzzyMetric::Damerau),
+            "tokenset" | "token-set" | "ts" => Some(FuzzyMetric::TokenSet),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn similarity(self, a: &str, b: &str) -> f64 {
+        match self {
+            FuzzyMetric::JaroWinkler => jaro_winkler(a, b),
+            FuzzyMetric::Levenshtein => normalized_levenshtein(a, b),
+            FuzzyMetric::Damerau => normalized_damerau_levenshtein(a, b),
+            FuzzyMetric::TokenSet => token_set_similarity(a, b),
+        }
+    }
+}
+
+/// the best normalized Levenshtein similarity of the sorted common words of two values,
+/// and the sorted common words followed by the sorted other words of each value
+fn token_set_similarity(a: &str, b: &str) -> f64 {
+    let tokens_a: BTreeSet<&str> = a.split_whitespace().collect();
+    let tokens_b: BTreeSet<&str> = b.split_whitespace().collect();
+
+    let common = tokens_a
+        .intersection(&tokens_b)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let with_rest = |rest: Vec<&str>| {
+        if rest.is_empty() {
+            common.clone()
+        } else if common.is_empty() {
+            rest.join(" ")
+        } else {
+            format!("{common} {}", rest.join(" "))
+        }
+    };
+    let all_a = with_rest(tokens_a.difference(&tokens_b).copied().collect());
+    let all_b = with_rest(tokens_b.difference(&tokens_a).copied().collect());
+
+    let mut similarity = normalized_levenshtein(&all_a, &all_b);
+    if !common.is_empty() {
+        similarity = similarity
+            .max(normalized_levenshtein(&common, &all_a))
+            .max(normalized_levenshtein(&common, &all_b));
+    }
+    similarity
+}
+
+/// parses a --fuzzy <metric>:<threshold> spec
+fn parse_fuzzy(spec: &str) -> CliResult<(FuzzyMetric, f64)> {
+    let Some((metric, threshold)) = spec.split_once(':') else {
+        return fail_incorrectusage_clierror!(
+            "--fuzzy must be in the form <metric>:<threshold>, e.g. \"jw:0.9\"."
+        );
+    };
+    let Some(metric) = FuzzyMetric::from_name(metric) else {
+        return fail_incorrectusage_clierror!(
+            "Unknown --fuzzy metric \"{metric}\". Valid metrics are jarowinkler (jw), levenshtein \
+             (lev), damerau (dl) and tokenset (ts)."
+        );
+    };
+    match threshold.trim().parse::<f64>() {
+        Ok(threshold) if (0.0..=1.0).contains(&threshold) => Ok((metric, threshold)),
+        _ => fail_incorrectusage_clierror!(
+            "--fuzzy threshold must be a number between 0.0 and 1.0, got \"{threshold}\"."
+        ),
+    }
+}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
-    let compare_mode = if args.flag_numeric {
+    let compare_mode = if let Some(ref locale) = args.flag_collation {
+        if args.flag_numeric || args.flag_ignore_case || args.flag_fuzzy.is_some() {
+            return fail_incorrectusage_clierror!(
+                "--collation cannot be used with --numeric, --ignore-case or --fuzzy."
+            );
+        }
+        ComparisonMode::Collated(Collation::parse(locale)?)
+    } else if args.flag_numeric {
         ComparisonMode::Numeric
     } else if args.flag_ignore_case {
         ComparisonMode::IgnoreCase
@@ -98,6 +259,24 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         ComparisonMode::Normal
     };
 
+    let fuzzy = match args.flag_fuzzy {
+        Some(ref spec) => {
+            if args.flag_numeric || args.flag_sorted || args.flag_keep.is_some() || args.flag_merge
+            {
+                return fail_incorrectusage_clierror!(
+                    "--fuzzy cannot be used with --numeric, --sorted, --keep or --merge."
+                );
+            }
+            Some(parse_fuzzy(spec)?)
+        },
+        None => {
+            if args.flag_blocking.is_some() {
+                return fail_incorrectusage_clierror!("--blocking can only be used with --fuzzy.");
+            }
+            None
+        },
+    };
+
     let rconfig = Config::new(args.arg_input.as_ref())
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
@@ -108,16 +287,88 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let dupes_output = args.flag_dupes_output.is_some();
     let mut dupewtr = Config::new(args.flag_dupes_output.as_ref()).writer()?;
 
-    let headers = rdr.byte_headers()?;
-    if dupes_output {
-        dupewtr.write_byte_record(headers)?;
-    }
-    let sel = rconfig.selection(headers)?;
-
-    rconfig.write_headers(&mut rdr, &mut wtr)?;
+    let headers = rdr.byte_headers()?.clone();
+    let sel = rconfig.selection(&headers)?;
+    let keep = match args.flag_keep {
+        Some(ref spec) => Some(Keep::parse(spec, &headers, args.flag_no_headers)?),
+        None if args.flag_merge => Some(Keep::First),
+        None => None,
+    };
     let mut dupe_count = 0_usize;
 
-    if args.flag_sorted {
+    if let Some((metric, threshold)) = fuzzy {
+        let block_sel = match args.flag_blocking {
+            Some(ref blocking) => Some(blocking.selection(&headers, !args.flag_no_headers)?),
+            None => None,
+        };
+        rconfig.write_headers(&mut rdr, &mut wtr)?;
+        if dupes_output && !args.flag_no_headers {
+            let mut dupe_headers = ByteRecord::from(vec!["cluster_id", "similarity"]);
+            dupe_headers.extend(&headers);
+            dupewtr.write_byte_record(&dupe_headers)?;
+        }
+
+        // we're loading the entire file into memory, we need to check avail mem
+        if let Some(path) = rconfig.path.clone() {
+            util::mem_file_check(&path, false, args.flag_memcheck)?;
+        }
+        util::njobs(args.flag_jobs);
+
+        let all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
+        dupe_count = fuzzy_dedup(
+            &all,
+            &sel,
+            block_sel.as_ref(),
+            metric,
+            threshold,
+            args.flag_ignore_case,
+            &mut wtr,
+            dupes_output.then_some(&mut dupewtr),
+        )?;
+    } else if let Some(keep) = keep {
+        if dupes_output {
+            dupewtr.write_byte_record(&headers)?;
+        }
+        rconfig.write_headers(&mut rdr, &mut wtr)?;
+
+        let merge = args.flag_merge;
+        let dupewtr = dupes_output.then_some(&mut dupewtr);
+        dupe_count = if args.flag_sorted {
+            dedup_survivors(
+                rdr.byte_records(),
+                &sel,
+                &compare_mode,
+                keep,
+                merge,
+                &mut wtr,
+                dupewtr,
+            )?
+        } else {
+            // we're loading the entire file into memory, we need to check avail mem
+            if let Some(path) = rconfig.path.clone() {
+                util::mem_file_check(&path, false, args.flag_memcheck)?;
+            }
+            util::njobs(args.flag_jobs);
+
+            // the sort is stable, so the duplicates of each group stay in input order
+            let mut all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
+            all.par_sort_by(|r1, r2| compare_keys(&compare_mode, &sel, r1, r2));
+            dedup_survivors(
+                all.into_iter().map(Ok),
+                &sel,
+                &compare_mode,
+                keep,
+                merge,
+                &mut wtr,
+                dupewtr,
+            )?
+        };
+    } else if args.flag_sorted {
+        if dupes_output {
+            dupewtr.write_byte_record(&headers)?;
+        }
+        rconfig.write_headers(&mut rdr, &mut wtr)?;
+
         let mut record = ByteRecord::new();
         let mut next_record = ByteRecord::new();
 
@@ -134,6 +385,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 ComparisonMode::Normal => iter_cmp(a, b),
                 ComparisonMode::Numeric => iter_cmp_num(a, b),
                 ComparisonMode::IgnoreCase => iter_cmp_ignore_case(a, b),
+                ComparisonMode::Collated(ref collation) => collation.iter_cmp(a, b),
             };
             match comparison {
                 cmp::Ordering::Equal => {
@@ -158,6 +410,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             }
         }
     } else {
+        if dupes_output {
+            dupewtr.write_byte_record(&headers)?;
+        }
+        rconfig.write_headers(&mut rdr, &mut wtr)?;
+
         // we're loading the entire file into memory, we need to check avail mem
         if let Some(path) = rconfig.path.clone() {
             util::mem_file_check(&path, false, args.flag_memcheck)?;
@@ -188,6 +445,13 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     iter_cmp_ignore_case(a, b)
                 });
             },
+            ComparisonMode::Collated(ref collation) => {
+                all.par_sort_by(|r1, r2| {
+                    let a = sel.select(r1);
+                    let b = sel.select(r2);
+                    collation.iter_cmp(a, b)
+                });
+            },
         }
 
         for (current, current_record) in all.iter().enumerate() {
@@ -225,6 +489,16 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                             wtr.write_byte_record(current_record)?;
                         }
                     },
+                    ComparisonMode::Collated(ref collation) => {
+                        if collation.iter_cmp(a, b) == cmp::Ordering::Equal {
+                            dupe_count += 1;
+                            if dupes_output {
+                                dupewtr.write_byte_record(current_record)?;
+                            }
+                        } else {
+                            wtr.write_byte_record(current_record)?;
+                        }
+                    },
                 }
             } else {
                 wtr.write_byte_record(current_record)?;
@@ -250,6 +524,386 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     Ok(())
 }
 
+/// A --keep survivorship rule, choosing which row of a group of duplicates is kept.
+#[derive(Clone, Copy, Debug)]
+pub enum Keep {
+    First,
+    Last,
+    Max(usize),
+    Min(usize),
+}
+
+impl Keep {
+    /// parses a --keep rule, resolving its column against the headers
+    pub fn parse(spec: &str, headers: &ByteRecord, no_headers: bool) -> CliResult<Self> {
+        let (rule, column) = match spec.split_once(':') {
+            Some((rule, column)) => (rule, Some(column)),
+            None => (spec, None),
+        };
+        match (rule.trim().to_ascii_lowercase().as_str(), column) {
+            ("first", None) => Ok(Keep::First),
+            ("last", None) => Ok(Keep::Last),
+            (rule @ ("max" | "min"), Some(column)) => {
+                let sel = SelectColumns::parse(column)?.selection(headers, !no_headers)?;
+                if sel.len() != 1 {
+                    return fail_incorrectusage_clierror!(
+                        "--keep {rule}:<col> must select exactly one column."
+                    );
+                }
+                Ok(if rule == "max" {
+                    Keep::Max(sel[0])
+                } else {
+                    Keep::Min(sel[0])
+                })
+            },
+            _ => fail_incorrectusage_clierror!(
+                "Invalid --keep rule \"{spec}\". Valid rules are first, last, max:<col> and \
+                 min:<col>."
+            ),
+        }
+    }
+
+    /// whether a later duplicate should replace the current survivor of its group
+    #[inline]
+    fn prefers(self, current: &ByteRecord, candidate: &ByteRecord) -> bool {
+        let (col, wanted) = match self {
+            Keep::First => return false,
+            Keep::Last => return true,
+            Keep::Max(col) => (col, cmp::Ordering::Greater),
+            Keep::Min(col) => (col, cmp::Ordering::Less),
+        };
+        let current = util::trim_bs_whitespace(current.get(col).unwrap_or_default());
+        let candidate = util::trim_bs_whitespace(candidate.get(col).unwrap_or_default());
+        if candidate.is_empty() {
+            false
+        } else if current.is_empty() {
+            true
+        } else {
+            compare_values(candidate, current) == wanted
+        }
+    }
+}
+
+/// compares two values as numbers if they're both numeric, and as strings otherwise
+#[inline]
+fn compare_values(a: &[u8], b: &[u8]) -> cmp::Ordering {
+    match (
+        fast_float2::parse::<f64, &[u8]>(a),
+        fast_float2::parse::<f64, &[u8]>(b),
+    ) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+/// The row kept of a group of duplicates so far, per its --keep rule.
+/// With --merge, the empty fields of the kept row are filled with the values
+/// of the other rows of the group.
+pub struct Survivor {
+    keep:   Keep,
+    merge:  bool,
+    row:    ByteRecord,
+    merged: ByteRecord,
+    index:  usize,
+}
+
+impl Survivor {
+    pub fn new(keep: Keep, merge: bool, row: ByteRecord, index: usize) -> Self {
+        let merged = if merge {
+            row.clone()
+        } else {
+            ByteRecord::new()
+        };
+        Self {
+            keep,
+            merge,
+            row,
+            merged,
+            index,
+        }
+    }
+
+    /// adds a later duplicate at the given input index to the group,
+    /// returning the row that isn't kept
+    pub fn add(&mut self, row: ByteRecord, index: usize) -> ByteRecord {
+        if self.keep.prefers(&self.row, &row) {
+            if self.merge {
+                let mut merged = row.clone();
+                coalesce(&mut merged, &self.merged);
+                self.merged = merged;
+            }
+            self.index = index;
+            std::mem::replace(&mut self.row, row)
+        } else {
+            if self.merge {
+                coalesce(&mut self.merged, &row);
+            }
+            row
+        }
+    }
+
+    /// the input index of the kept row
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// the kept row, merged with --merge
+    pub const fn row(&self) -> &ByteRecord {
+        if self.merge {
+            &self.merged
+        } else {
+            &self.row
+        }
+    }
+}
+
+/// fills the empty fields of a row with the values of another row
+#[inline]
+fn coalesce(row: &mut ByteRecord, other: &ByteRecord) {
+    if !row.iter().any(<[u8]>::is_empty) {
+        return;
+    }
+    *row = row
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            if field.is_empty() {
+                other.get(i).unwrap_or_default()
+            } else {
+                field
+            }
+        })
+        .collect();
+}
+
+/// compares the selected columns of two rows per the comparison mode
+#[inline]
+fn compare_keys(
+    compare_mode: &ComparisonMode,
+    sel: &Selection,
+    r1: &ByteRecord,
+    r2: &ByteRecord,
+) -> cmp::Ordering {
+    let a = sel.select(r1);
+    let b = sel.select(r2);
+    match compare_mode {
+        ComparisonMode::Normal => iter_cmp(a, b),
+        ComparisonMode::Numeric => iter_cmp_num(a, b),
+        ComparisonMode::IgnoreCase => iter_cmp_ignore_case(a, b),
+        ComparisonMode::Collated(collation) => collation.iter_cmp(a, b),
+    }
+}
+
+/// writes the row kept by --keep/--merge of each group of consecutive duplicates of the
+/// sorted records, and the other rows to dupewtr. Returns the number of duplicates.
+fn dedup_survivors<I>(
+    records: I,
+    sel: &Selection,
+    compare_mode: &ComparisonMode,
+    keep: Keep,
+    merge: bool,
+    wtr: &mut csv::Writer<Box<dyn io::Write + 'static>>,
+    mut dupewtr: Option<&mut csv::Writer<Box<dyn io::Write + 'static>>>,
+) -> CliResult<usize>
+where
+    I: Iterator<Item = csv::Result<ByteRecord>>,
+{
+    let mut dupe_count = 0_usize;
+    // the first row of the current group, which its duplicates are compared with
+    let mut group_first = ByteRecord::new();
+    let mut survivor: Option<Survivor> = None;
+    for (index, record) in records.enumerate() {
+        let record = record?;
+        if let Some(ref mut current) = survivor {
+            match compare_keys(compare_mode, sel, &group_first, &record) {
+                cmp::Ordering::Equal => {
+                    dupe_count += 1;
+                    let dupe = current.add(record, index);
+                    if let Some(ref mut dupewtr) = dupewtr {
+                        dupewtr.write_byte_record(&dupe)?;
+                    }
+                    continue;
+                },
+                cmp::Ordering::Less => wtr.write_byte_record(current.row())?,
+                cmp::Ordering::Greater => {
+                    return fail_clierror!(
+                        r#"Aborting! Input not sorted! Current record is greater than Next record.
+  Compare mode: {compare_mode:?};  Select columns index/es (0-based): {sel:?}
+  Current: {group_first:?}
+     Next: {record:?}
+"#
+                    );
+                },
+            }
+        }
+        group_first.clone_from(&record);
+        survivor = Some(Survivor::new(keep, merge, record, index));
+    }
+    if let Some(current) = survivor {
+        wtr.write_byte_record(current.row())?;
+    }
+    Ok(dupe_count)
+}
+
+/// the values a row is compared with in --fuzzy mode - trimmed, and lowercased
+/// with --ignore-case
+pub(crate) fn fuzzy_values<'a>(
+    record: &'a ByteRecord,
+    sel: &'a Selection,
+    ignore_case: bool,
+) -> impl Iterator<Item = String> + 'a {
+    sel.select(record).map(move |field| {
+        let value = String::from_utf8_lossy(field);
+        if ignore_case {
+            value.trim().to_lowercase()
+        } else {
+            value.trim().to_string()
+        }
+    })
+}
+
+/// the mean similarity of the selected values of two rows
+#[inline]
+pub(crate) fn row_similarity(metric: FuzzyMetric, a: &[String], b: &[String]) -> f64 {
+    if a.is_empty() {
+        return 1.0;
+    }
+    let total: f64 = a.iter().zip(b).map(|(x, y)| metric.similarity(x, y)).sum();
+    #[allow(clippy::cast_precision_loss)]
+    let len = a.len() as f64;
+    total / len
+}
+
+/// A union-find of the near-duplicate rows, with each cluster rooted at its first row.
+/// Only the rows that are not roots are stored, so rows without matches take no memory.
+#[derive(Default)]
+struct RowClusters(AHashMap<usize, usize>);
+
+impl RowClusters {
+    /// finds the root of a row's cluster, halving the path to it
+    fn root(&mut self, mut i: usize) -> usize {
+        while let Some(&parent) = self.0.get(&i) {
+            let Some(&grandparent) = self.0.get(&parent) else {
+                return parent;
+            };
+            self.0.insert(i, grandparent);
+            i = grandparent;
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (root_a, root_b) = (self.root(a), self.root(b));
+        match root_a.cmp(&root_b) {
+            cmp::Ordering::Less => {
+                self.0.insert(root_b, root_a);
+            },
+            cmp::Ordering::Greater => {
+                self.0.insert(root_a, root_b);
+            },
+            cmp::Ordering::Equal => {},
+        }
+    }
+
+    fn merge(mut self, mut other: RowClusters) -> RowClusters {
+        if self.0.len() < other.0.len() {
+            std::mem::swap(&mut self, &mut other);
+        }
+        for (row, parent) in other.0 {
+            self.union(row, parent);
+        }
+        self
+    }
+}
+
+/// clusters near-duplicate rows, writing the first row of each cluster to wtr, and
+/// the clusters with more than one row to dupewtr. Returns the number of duplicates.
+#[allow(clippy::too_many_arguments)]
+fn fuzzy_dedup(
+    all: &[ByteRecord],
+    sel: &Selection,
+    block_sel: Option<&Selection>,
+    metric: FuzzyMetric,
+    threshold: f64,
+    ignore_case: bool,
+    wtr: &mut csv::Writer<Box<dyn io::Write + 'static>>,
+    dupewtr: Option<&mut csv::Writer<Box<dyn io::Write + 'static>>>,
+) -> CliResult<usize> {
+    let values: Vec<Vec<String>> = all
+        .par_iter()
+        .map(|record| fuzzy_values(record, sel, ignore_case).collect())
+        .collect();
+
+    // only rows in the same block are compared
+    let mut blocks: AHashMap<Vec<String>, Vec<usize>> = AHashMap::new();
+    for (i, record) in all.iter().enumerate() {
+        let block_key = match block_sel {
+            Some(block_sel) => fuzzy_values(record, block_sel, ignore_case).collect(),
+            None => Vec::new(),
+        };
+        blocks.entry(block_key).or_default().push(i);
+    }
+
+    let blocks: Vec<Vec<usize>> = blocks.into_values().collect();
+    let values = &values;
+    // the matches are unioned as they are found, into a union-find per parallel job
+    // that is merged with the others afterwards, so memory doesn't grow with the
+    // number of matches
+    let mut row_clusters = blocks
+        .par_iter()
+        .flat_map(|rows| {
+            (0..rows.len()).into_par_iter().fold(
+                RowClusters::default,
+                move |mut row_clusters, x| {
+                    for y in x + 1..rows.len() {
+                        let (a, b) = (rows[x], rows[y]);
+                        if row_similarity(metric, &values[a], &values[b]) >= threshold {
+                            row_clusters.union(a, b);
+                        }
+                    }
+                    row_clusters
+                },
+            )
+        })
+        .reduce(RowClusters::default, RowClusters::merge);
+
+    let mut clusters: AHashMap<usize, Vec<usize>> = AHashMap::new();
+    let mut dupe_count = 0_usize;
+    for (i, record) in all.iter().enumerate() {
+        let root = row_clusters.root(i);
+        if root == i {
+            wtr.write_byte_record(record)?;
+        } else {
+            dupe_count += 1;
+            clusters.entry(root).or_default().push(i);
+        }
+    }
+
+    if let Some(dupewtr) = dupewtr {
+        let mut roots: Vec<usize> = clusters.keys().copied().collect();
+        roots.sort_unstable();
+        let mut itoa_buffer = itoa::Buffer::new();
+        let mut dupe_record = ByteRecord::new();
+        for (cluster_id, root) in roots.into_iter().enumerate() {
+            let cluster_id = itoa_buffer.format(cluster_id + 1).to_owned();
+            for &i in std::iter::once(&root).chain(&clusters[&root]) {
+                let similarity = if i == root {
+                    1.0
+                } else {
+                    row_similarity(metric, &values[root], &values[i])
+                };
+                dupe_record.clear();
+                dupe_record.push_field(cluster_id.as_bytes());
+                dupe_record.push_field(util::round_num(similarity, 4).as_bytes());
+                dupe_record.extend(&all[i]);
+                dupewtr.write_byte_record(&dupe_record)?;
+            }
+        }
+    }
+
+    Ok(dupe_count)
+}
+
 /// Try comparing `a` and `b` ignoring the case
 #[inline]
 pub fn iter_cmp_ignore_case<'a, L, R>(mut a: L, mut b: R) -> cmp::Ordering
@@ -2,6 +2,13 @@ static USAGE: &str = r#"
 Find the difference between two CSVs with ludicrous speed.
 
 Note that diff does not support stdin. A file path is required for both arguments.
+(except with --external, see below)
+
+By default, diff loads both CSVs into memory. To diff CSVs that are larger than memory,
+use --external. It sorts both CSVs by --key using an external merge sort (the same one
+used by `qsv extsort`), and then diffs them in a single streaming pass, so memory use is
+bounded by --memory-limit. The diff result has the same schema, but is sorted by key
+(as strings) instead of by line.
 
 Examples:
 
@@ -42,6 +49,31 @@ Find the difference between two CSVs. Both CSVs have no headers, but the result
 headers, so generic headers will be used in the form of: _col_1, _col_2, etc.:
     qsv diff --no-headers-left --no-headers-right left.csv right.csv
 
+Find the difference between two CSVs, ignoring differences in numbers of up to 0.01,
+in case and in leading/trailing whitespace, and ignoring the "updated_at" column:
+    qsv diff --numeric-tolerance 0.01 --ignore-case --trim \
+      --ignore-columns updated_at left.csv right.csv
+
+Summarize the difference between two CSVs, with the number of added, deleted & modified
+rows, and the number of modified rows of each column, most modified columns first:
+    qsv diff --summary left.csv right.csv
+
+Find the difference between two CSVs as JSONL, with one object per added, deleted
+or modified row, and the old & new values of each modified field:
+    qsv diff --format jsonl left.csv right.csv
+
+Find the difference between two CSVs as an HTML report:
+    qsv diff --format html -o diff.html left.csv right.csv
+
+Find the difference between two CSVs and apply it to the left CSV to reproduce the
+right CSV (see 'qsv patch --help'):
+    qsv diff -o delta.csv left.csv right.csv
+    qsv patch -o new-right.csv left.csv delta.csv
+
+Find the difference between two CSVs that are larger than memory, using the "id"
+column as the key, at most 2GB of memory and /scratch for the sorted chunks:
+    qsv diff --external --memory-limit 2000 --tmp-dir /scratch -k id left.csv right.csv
+
 For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_diff.rs
 
 Usage:
@@ -89,28 +121,88 @@ diff options:
     --drop-equal-fields         Drop values of equal fields in modified rows of the CSV
                                 diff result (and replace them with the empty string).
                                 Key field values will not be dropped.
+    --numeric-tolerance <arg>   Consider two numeric field values equal if they differ
+                                by at most <arg>, e.g. 1.0 and 1.00 with a tolerance of 0.
+    -i, --ignore-case           Compare field values disregarding case.
+    --trim                      Ignore leading and trailing whitespace in field values.
+    --ignore-columns <arg>      Columns to ignore when comparing rows, so a row that only
+                                differs in these columns is not modified.
+                                See 'qsv select --help' for the format details.
+                                Columns are selected using the left CSV's headers.
+                                Note that --ignore-case and --trim also apply to the key
+                                columns, but --numeric-tolerance doesn't.
+    --summary                   Instead of the diff result, write a summary of the
+                                difference, with the columns type, name & count:
+                                the number of "added", "deleted" and "modified" rows,
+                                followed by the number of modified rows of each column
+                                with any changes, most modified columns first.
+    --format <arg>              The format of the diff result. One of:
+                                  csv - a CSV with a "diffresult" column, with "+"
+                                        for added rows and "-" for deleted rows.
+                                        A modified row is written as a deleted row
+                                        followed by an added row.
+                                  jsonl - one JSON object per line, with "op" (add,
+                                        delete or modify) and "key" (the key fields).
+                                        Added and deleted rows have the "row", while
+                                        modified rows have the "changes", the "old" and
+                                        "new" value of each modified field.
+                                  json-patch - a JSON Patch (RFC 6902) array of operations
+                                        on a JSON object with the rows by key, where
+                                        the path of a row is "/<key>" (multiple key
+                                        fields are joined with a comma) and the path of
+                                        a field is "/<key>/<column name>".
+                                        The "test" operations check the old values.
+                                  html - an HTML document with a table of the added,
+                                        deleted and modified rows, showing the old
+                                        & new values of each modified field.
+                                --drop-equal-fields, --no-headers-output & --delimiter-output
+                                only apply to the csv format.
+                                [default: csv]
     -j, --jobs <arg>            The number of jobs to run in parallel.
                                 When not set, the number of jobs is set to the number
                                 of CPUs detected.
 
+                                EXTERNAL MODE OPTIONS:
+    --external                  Externally sort both CSVs by --key and diff them in a
+                                single streaming pass, for CSVs larger than memory.
+                                One of the CSVs can be stdin.
+                                The key values should be unique in each CSV.
+                                Note that invalid UTF-8 is replaced with U+FFFD.
+                                --sort-columns is not supported.
+    --memory-limit <arg>        The maximum amount of memory to buffer the external
+                                merge sort.
+                                If less than 50, this is a percentage of total memory.
+                                If more than 50, this is the memory in MB to allocate,
+                                capped at 90 percent of total memory.
+                                [default: 20]
+    --tmp-dir <arg>             The directory to use for externally sorting file
+                                segments. [default: ./]
+
 Common options:
     -h, --help                  Display this message
     -o, --output <file>         Write output to <file> instead of stdout.
 "#;
 
-use std::io::{self, Write};
+use std::{
+    cmp::Ordering,
+    io::{self, Write},
+    path,
+};
 
+use ahash::AHashMap;
 use csv::ByteRecord;
 use csv_diff::{
-    csv_diff::CsvByteDiffBuilder, csv_headers::Headers, diff_result::DiffByteRecords,
-    diff_row::DiffByteRecord,
+    csv_diff::CsvByteDiffBuilder, diff_result::DiffByteRecords, diff_row::DiffByteRecord,
 };
+use ext_sort::{ExternalSorter, ExternalSorterBuilder, LimitedBufferBuilder};
 use serde::Deserialize;
 
 use super::rename::rename_headers_all_generic;
 use crate::{
     clitypes::CliError,
+    cmd::extdedup::calculate_memory_limit,
     config::{Config, Delimiter},
+    select::SelectColumns,
     util, CliResult,
 };
 
@@ -129,6 +221,23 @@ struct Args {
     flag_key:               Option<String>,
     flag_sort_columns:      Option<String>,
     flag_drop_equal_fields: bool,
+    flag_numeric_tolerance: Option<f64>,
+    flag_ignore_case:       bool,
+    flag_trim:              bool,
+    flag_ignore_columns:    Option<SelectColumns>,
+    flag_summary:           bool,
+    flag_format:            String,
+    flag_external:          bool,
+    flag_memory_limit:      Option<u64>,
+    flag_tmp_dir:           Option<String>,
+}
+
+#[derive(PartialEq)]
+enum DiffFormat {
+    Csv,
+    Jsonl,
+    JsonPatch,
+    Html,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
@@ -142,7 +251,18 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         .delimiter(args.flag_delimiter_right)
         .no_headers(args.flag_no_headers_right);
 
-    if rconfig_left.is_stdin() || rconfig_right.is_stdin() {
+    if args.flag_external {
+        if rconfig_left.is_stdin() && rconfig_right.is_stdin() {
+            return fail_incorrectusage_clierror!(
+                "diff --external supports stdin for only one of the arguments."
+            );
+        }
+        if args.flag_sort_columns.is_some() {
+            return fail_incorrectusage_clierror!(
+                "--sort-columns cannot be used with --external. The diff result is sorted by key."
+            );
+        }
+    } else if rconfig_left.is_stdin() || rconfig_right.is_stdin() {
         return fail_incorrectusage_clierror!(
             "diff does not support stdin. A file path is required for both arguments."
         );
@@ -151,12 +271,25 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut csv_rdr_left = rconfig_left.reader()?;
     let mut csv_rdr_right = rconfig_right.reader()?;
 
-    let headers_left = csv_rdr_left.byte_headers()?;
-    let headers_right = csv_rdr_right.byte_headers()?;
+    let headers_left = csv_rdr_left.byte_headers()?.clone();
+    let headers_right = csv_rdr_right.byte_headers()?.clone();
+
+    if args
+        .flag_numeric_tolerance
+        .is_some_and(|tolerance| tolerance < 0.0)
+    {
+        return fail_incorrectusage_clierror!("--numeric-tolerance cannot be negative.");
+    }
+    let ignore_columns = match args.flag_ignore_columns {
+        Some(ref ignore_columns) => ignore_columns
+            .selection(&headers_left, !args.flag_no_headers_left)?
+            .to_vec(),
+        None => Vec::new(),
+    };
 
     let primary_key_cols: Vec<usize> = match args.flag_key {
         None => vec![0],
-        Some(s) => {
+        Some(ref s) => {
             // check if the key is a comma separated list of numbers
             if s.chars().all(|c: char| c.is_numeric() || c == ',') {
                 s.split(',')
@@ -214,6 +347,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let sort_cols = args
         .flag_sort_columns
+        .as_ref()
         .map(|s| {
             // check if the sort columns are a comma separated list of numbers
             if s.chars().all(|c: char| c.is_numeric() || c == ',') {
@@ -244,9 +378,59 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         })
         .transpose()?;
 
-    let wtr = Config::new(args.flag_output.as_ref())
-        .delimiter(args.flag_delimiter_output)
-        .writer()?;
+    let diff_format = match args.flag_format.to_lowercase().as_str() {
+        "csv" => DiffFormat::Csv,
+        "jsonl" => DiffFormat::Jsonl,
+        "json-patch" => DiffFormat::JsonPatch,
+        "html" => DiffFormat::Html,
+        _ => {
+            return fail_incorrectusage_clierror!(
+                "Invalid --format: {}. Must be one of csv, jsonl, json-patch or html.",
+                args.flag_format
+            )
+        },
+    };
+    if args.flag_summary && diff_format != DiffFormat::Csv {
+        return fail_incorrectusage_clierror!("--summary can only be used with the csv format.");
+    }
+
+    let wconfig = Config::new(args.flag_output.as_ref()).delimiter(args.flag_delimiter_output);
+
+    let comparison = FieldComparison {
+        numeric_tolerance: args.flag_numeric_tolerance,
+        ignore_case: args.flag_ignore_case,
+        trim: args.flag_trim,
+        ignore_columns,
+        key_fields: primary_key_cols.clone(),
+    };
+
+    if args.flag_external {
+        let diff_headers = if !args.flag_no_headers_left {
+            Some(headers_left.clone())
+        } else if !args.flag_no_headers_right {
+            Some(headers_right.clone())
+        } else {
+            None
+        };
+        let num_columns = headers_left.len();
+
+        let mut diff_result_writer = DiffResultWriter::new(
+            &args,
+            &diff_format,
+            &wconfig,
+            column_names(diff_headers.as_ref(), num_columns),
+            primary_key_cols,
+        )?;
+        diff_result_writer.write_headers(diff_headers.as_ref(), Some(&num_columns))?;
+        external_diff(
+            &args,
+            csv_rdr_left,
+            csv_rdr_right,
+            &comparison,
+            &mut diff_result_writer,
+        )?;
+        return diff_result_writer.finish();
+    }
 
     util::njobs(args.flag_jobs);
 
@@ -272,13 +456,768 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         },
     }
 
-    let mut csv_diff_writer = CsvDiffWriter::new(
-        wtr,
-        args.flag_no_headers_output,
-        args.flag_drop_equal_fields,
+    let diff_headers = {
+        let headers = diff_byte_records.headers();
+        headers
+            .headers_left()
+            .or_else(|| headers.headers_right())
+            .cloned()
+    };
+    let num_columns = diff_byte_records.num_columns();
+
+    let diff_rows = comparison.diff_rows(diff_byte_records);
+
+    let mut diff_result_writer = DiffResultWriter::new(
+        &args,
+        &diff_format,
+        &wconfig,
+        column_names(diff_headers.as_ref(), num_columns.unwrap_or_default()),
         primary_key_cols,
-    );
-    Ok(csv_diff_writer.write_diff_byte_records(diff_byte_records)?)
+    )?;
+    diff_result_writer.write_headers(diff_headers.as_ref(), num_columns.as_ref())?;
+    for diff_row in &diff_rows {
+        diff_result_writer.write_row(diff_row)?;
+    }
+    diff_result_writer.finish()
+}
+
+/// the column names of the diff result, with generic names (_col_1, _col_2, etc.)
+/// if neither CSV has headers
+fn column_names(headers: Option<&ByteRecord>, num_columns: usize) -> Vec<String> {
+    match headers {
+        Some(headers) => headers
+            .iter()
+            .map(|h| String::from_utf8_lossy(h).into_owned())
+            .collect(),
+        None => (1..=num_columns).map(|i| format!("_col_{i}")).collect(),
+    }
+}
+
+/// a record as a JSON object with the column names as keys
+fn record_json(names: &[String], record: &ByteRecord, indices: &[usize]) -> serde_json::Value {
+    let mut map = serde_json::Map::with_capacity(indices.len());
+    for &i in indices {
+        map.insert(
+            names
+                .get(i)
+                .cloned()
+                .unwrap_or_else(|| format!("_col_{}", i + 1)),
+            String::from_utf8_lossy(record.get(i).unwrap_or_default()).into(),
+        );
+    }
+    serde_json::Value::Object(map)
+}
+
+/// a diff row as a JSONL object
+fn diff_row_json(names: &[String], key_fields: &[usize], diff_row: &DiffRow) -> serde_json::Value {
+    match diff_row {
+        DiffRow::Add(add) => serde_json::json!({
+            "op": "add",
+            "key": record_json(names, add, key_fields),
+            "row": record_json(names, add, &(0..add.len()).collect::<Vec<_>>()),
+        }),
+        DiffRow::Delete(delete) => serde_json::json!({
+            "op": "delete",
+            "key": record_json(names, delete, key_fields),
+            "row": record_json(names, delete, &(0..delete.len()).collect::<Vec<_>>()),
+        }),
+        DiffRow::Modify {
+            delete,
+            add,
+            field_indices,
+        } => {
+            let mut changes = serde_json::Map::with_capacity(field_indices.len());
+            for &i in field_indices {
+                changes.insert(
+                    names
+                        .get(i)
+                        .cloned()
+                        .unwrap_or_else(|| format!("_col_{}", i + 1)),
+                    serde_json::json!({
+                        "old": String::from_utf8_lossy(delete.get(i).unwrap_or_default()),
+                        "new": String::from_utf8_lossy(add.get(i).unwrap_or_default()),
+                    }),
+                );
+            }
+            serde_json::json!({
+                "op": "modify",
+                "key": record_json(names, delete, key_fields),
+                "changes": changes,
+            })
+        },
+    }
+}
+
+/// the JSON Pointer (RFC 6901) reference token of a row, its key fields joined with a comma
+fn json_pointer_key(record: &ByteRecord, key_fields: &[usize]) -> String {
+    key_fields
+        .iter()
+        .map(|&i| json_pointer_escape(&String::from_utf8_lossy(record.get(i).unwrap_or_default())))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn json_pointer_escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// the JSON Patch operations of a diff row
+fn json_patch_ops(
+    names: &[String],
+    key_fields: &[usize],
+    diff_row: &DiffRow,
+) -> Vec<serde_json::Value> {
+    match diff_row {
+        DiffRow::Add(add) => vec![serde_json::json!({
+            "op": "add",
+            "path": format!("/{}", json_pointer_key(add, key_fields)),
+            "value": record_json(names, add, &(0..add.len()).collect::<Vec<_>>()),
+        })],
+        DiffRow::Delete(delete) => {
+            let path = format!("/{}", json_pointer_key(delete, key_fields));
+            vec![
+                serde_json::json!({
+                    "op": "test",
+                    "path": path,
+                    "value": record_json(names, delete, &(0..delete.len()).collect::<Vec<_>>()),
+                }),
+                serde_json::json!({"op": "remove", "path": path}),
+            ]
+        },
+        DiffRow::Modify {
+            delete,
+            add,
+            field_indices,
+        } => {
+            let key = json_pointer_key(delete, key_fields);
+            let mut ops = Vec::with_capacity(field_indices.len() * 2);
+            for &i in field_indices {
+                let name = names
+                    .get(i)
+                    .cloned()
+                    .unwrap_or_else(|| format!("_col_{}", i + 1));
+                let path = format!("/{key}/{}", json_pointer_escape(&name));
+                ops.push(serde_json::json!({
+                    "op": "test",
+                    "path": path,
+                    "value": String::from_utf8_lossy(delete.get(i).unwrap_or_default()),
+                }));
+                ops.push(serde_json::json!({
+                    "op": "replace",
+                    "path": path,
+                    "value": String::from_utf8_lossy(add.get(i).unwrap_or_default()),
+                }));
+            }
+            ops
+        },
+    }
+}
+
+fn html_escape(bytes: &[u8]) -> String {
+    let value = String::from_utf8_lossy(bytes);
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn write_html_header<W: Write>(wtr: &mut W, names: &[String]) -> io::Result<()> {
+    writeln!(
+        wtr,
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>qsv diff</title>
+<style>
+table {{ border-collapse: collapse; font-family: sans-serif; font-size: 0.9em; }}
+th, td {{ border: 1px solid #ccc; padding: 0.2em 0.5em; }}
+th {{ background: #eee; }}
+tr.added {{ background: #e6ffec; }}
+tr.deleted {{ background: #ffebe9; }}
+td.modified {{ background: #fff8c5; }}
+del {{ color: #b31d28; }}
+ins {{ color: #22863a; text-decoration: none; }}
+</style>
+</head>
+<body>
+<table>
+<thead>
+<tr><th>diffresult</th>{}</tr>
+</thead>
+<tbody>"#,
+        names
+            .iter()
+            .map(|name| format!("<th>{}</th>", html_escape(name.as_bytes())))
+            .collect::<String>()
+    )
+}
+
+fn write_html_row<W: Write>(wtr: &mut W, diff_row: &DiffRow) -> io::Result<()> {
+    match diff_row {
+        DiffRow::Add(add) => {
+            write!(wtr, r#"<tr class="added"><td>+</td>"#)?;
+            for field in add {
+                write!(wtr, "<td>{}</td>", html_escape(field))?;
+            }
+        },
+        DiffRow::Delete(delete) => {
+            write!(wtr, r#"<tr class="deleted"><td>-</td>"#)?;
+            for field in delete {
+                write!(wtr, "<td>{}</td>", html_escape(field))?;
+            }
+        },
+        DiffRow::Modify {
+            delete,
+            add,
+            field_indices,
+        } => {
+            write!(wtr, r#"<tr class="modified"><td>~</td>"#)?;
+            for (i, field) in add.iter().enumerate() {
+                if field_indices.contains(&i) {
+                    write!(
+                        wtr,
+                        r#"<td class="modified"><del>{}</del> <ins>{}</ins></td>"#,
+                        html_escape(delete.get(i).unwrap_or_default()),
+                        html_escape(field)
+                    )?;
+                } else {
+                    write!(wtr, "<td>{}</td>", html_escape(field))?;
+                }
+            }
+        },
+    }
+    writeln!(wtr, "</tr>")
+}
+
+/// Writes the diff result row by row, in the --format or as a --summary.
+enum DiffResultWriter {
+    Csv(CsvDiffWriter<Box<dyn Write>>),
+    Summary {
+        wtr:           csv::Writer<Box<dyn Write>>,
+        names:         Vec<String>,
+        added:         u64,
+        deleted:       u64,
+        modified:      u64,
+        column_counts: Vec<u64>,
+    },
+    Jsonl {
+        wtr:        Box<dyn Write>,
+        names:      Vec<String>,
+        key_fields: Vec<usize>,
+    },
+    JsonPatch {
+        wtr:         Box<dyn Write>,
+        names:       Vec<String>,
+        key_fields:  Vec<usize>,
+        ops_written: bool,
+    },
+    Html {
+        wtr:   Box<dyn Write>,
+        names: Vec<String>,
+    },
+}
+
+impl DiffResultWriter {
+    fn new(
+        args: &Args,
+        diff_format: &DiffFormat,
+        wconfig: &Config,
+        names: Vec<String>,
+        key_fields: Vec<usize>,
+    ) -> CliResult<Self> {
+        if args.flag_summary {
+            return Ok(Self::Summary {
+                wtr: wconfig.writer()?,
+                names,
+                added: 0,
+                deleted: 0,
+                modified: 0,
+                column_counts: Vec::new(),
+            });
+        }
+        Ok(match diff_format {
+            DiffFormat::Csv => Self::Csv(CsvDiffWriter::new(
+                wconfig.writer()?,
+                args.flag_no_headers_output,
+                args.flag_drop_equal_fields,
+                key_fields,
+            )),
+            DiffFormat::Jsonl => Self::Jsonl {
+                wtr: wconfig.io_writer()?,
+                names,
+                key_fields,
+            },
+            DiffFormat::JsonPatch => Self::JsonPatch {
+                wtr: wconfig.io_writer()?,
+                names,
+                key_fields,
+                ops_written: false,
+            },
+            DiffFormat::Html => Self::Html {
+                wtr: wconfig.io_writer()?,
+                names,
+            },
+        })
+    }
+
+    fn write_headers(
+        &mut self,
+        headers: Option<&ByteRecord>,
+        num_columns: Option<&usize>,
+    ) -> CliResult<()> {
+        match self {
+            Self::Csv(csv_diff_writer) => csv_diff_writer.write_headers(headers, num_columns)?,
+            Self::JsonPatch { wtr, .. } => wtr.write_all(b"[")?,
+            Self::Html { wtr, names } => write_html_header(wtr, names)?,
+            Self::Summary { .. } | Self::Jsonl { .. } => {},
+        }
+        Ok(())
+    }
+
+    fn write_row(&mut self, diff_row: &DiffRow) -> CliResult<()> {
+        match self {
+            Self::Csv(csv_diff_writer) => csv_diff_writer.write_diff_row(diff_row)?,
+            Self::Summary {
+                added,
+                deleted,
+                modified,
+                column_counts,
+                ..
+            } => match diff_row {
+                DiffRow::Add(_) => *added += 1,
+                DiffRow::Delete(_) => *deleted += 1,
+                DiffRow::Modify { field_indices, .. } => {
+                    *modified += 1;
+                    for &i in field_indices {
+                        if i >= column_counts.len() {
+                            column_counts.resize(i + 1, 0);
+                        }
+                        column_counts[i] += 1;
+                    }
+                },
+            },
+            Self::Jsonl {
+                wtr,
+                names,
+                key_fields,
+            } => {
+                serde_json::to_writer(&mut *wtr, &diff_row_json(names, key_fields, diff_row))?;
+                wtr.write_all(b"\n")?;
+            },
+            Self::JsonPatch {
+                wtr,
+                names,
+                key_fields,
+                ops_written,
+            } => {
+                for op in json_patch_ops(names, key_fields, diff_row) {
+                    if *ops_written {
+                        wtr.write_all(b",")?;
+                    }
+                    serde_json::to_writer(&mut *wtr, &op)?;
+                    *ops_written = true;
+                }
+            },
+            Self::Html { wtr, .. } => write_html_row(wtr, diff_row)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> CliResult<()> {
+        match self {
+            Self::Csv(mut csv_diff_writer) => csv_diff_writer.csv_writer.flush()?,
+            Self::Summary {
+                mut wtr,
+                names,
+                added,
+                deleted,
+                modified,
+                column_counts,
+            } => {
+                wtr.write_record(["type", "name", "count"])?;
+                let mut itoa_buffer = itoa::Buffer::new();
+                for (name, count) in [
+                    ("added", added),
+                    ("deleted", deleted),
+                    ("modified", modified),
+                ] {
+                    wtr.write_record(["rows", name, itoa_buffer.format(count)])?;
+                }
+
+                // the most modified columns first, in column order for ties
+                let mut columns: Vec<(usize, u64)> = column_counts
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, count)| *count > 0)
+                    .collect();
+                columns.sort_by(|a, b| b.1.cmp(&a.1));
+                for (i, count) in columns {
+                    let name = names
+                        .get(i)
+                        .cloned()
+                        .unwrap_or_else(|| format!("_col_{}", i + 1));
+                    wtr.write_record(["column", name.as_str(), itoa_buffer.format(count)])?;
+                }
+                wtr.flush()?;
+            },
+            Self::Jsonl { mut wtr, .. } => wtr.flush()?,
+            Self::JsonPatch { mut wtr, .. } => {
+                wtr.write_all(b"]\n")?;
+                wtr.flush()?;
+            },
+            Self::Html { mut wtr, .. } => {
+                writeln!(wtr, "</tbody>\n</table>\n</body>\n</html>")?;
+                wtr.flush()?;
+            },
+        }
+        Ok(())
+    }
+}
+
+const RW_BUFFER_CAPACITY: usize = 1_000_000; // 1 MB
+const SORT_SAMPLE_ROWS: usize = 1_000;
+
+/// escapes the separators of the sort lines (\x1e between the key and the fields,
+/// \x1f between fields) in a field
+fn escape_sort_field(field: &str, line: &mut String) {
+    for c in field.chars() {
+        match c {
+            '\\' => line.push_str("\\\\"),
+            '\x1e' => line.push_str("\\s"),
+            '\x1f' => line.push_str("\\u"),
+            _ => line.push(c),
+        }
+    }
+}
+
+fn unescape_sort_field(field: &str) -> String {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('s') => unescaped.push('\x1e'),
+                Some('u') => unescaped.push('\x1f'),
+                Some(c) => unescaped.push(c),
+                None => {},
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+/// a row as a line to externally sort, with its (normalized) key first,
+/// so the lines can be sorted by key
+fn encode_sort_line(comparison: &FieldComparison, record: &ByteRecord) -> String {
+    let mut line = String::with_capacity(record.as_slice().len() * 2);
+    for (i, key) in comparison.normalized_key(record).iter().enumerate() {
+        if i > 0 {
+            line.push('\x1f');
+        }
+        escape_sort_field(key, &mut line);
+    }
+    line.push('\x1e');
+    for (i, field) in record.iter().enumerate() {
+        if i > 0 {
+            line.push('\x1f');
+        }
+        escape_sort_field(&String::from_utf8_lossy(field), &mut line);
+    }
+    line
+}
+
+fn sort_line_key(line: &str) -> &str {
+    line.split_once('\x1e').map_or(line, |(key, _)| key)
+}
+
+fn decode_sort_line(line: &str) -> (String, ByteRecord) {
+    let (key, fields) = line.split_once('\x1e').unwrap_or((line, ""));
+    let record: ByteRecord = fields.split('\x1f').map(unescape_sort_field).collect();
+    (key.to_string(), record)
+}
+
+type SortedLines = Box<dyn Iterator<Item = CliResult<String>>>;
+
+/// externally sorts the rows of a CSV by key.
+/// Returns the sorter too, as it owns the temporary directory of the sorted chunks.
+fn external_sort(
+    args: &Args,
+    rdr: csv::Reader<Box<dyn io::Read + Send>>,
+    comparison: &FieldComparison,
+) -> CliResult<(
+    ExternalSorter<String, io::Error, LimitedBufferBuilder>,
+    SortedLines,
+)> {
+    let tmp_dir = args.flag_tmp_dir.as_deref().unwrap_or("./");
+    if !path::Path::new(tmp_dir).exists() {
+        return fail_clierror!("tmp-dir '{tmp_dir}' does not exist");
+    }
+
+    let mut lines = rdr.into_byte_records().map(|record| {
+        record
+            .map(|record| encode_sort_line(comparison, &record))
+            .map_err(io::Error::from)
+    });
+
+    // the sort buffer is limited by the number of lines,
+    // so estimate it from the length of the first lines
+    let sample: Vec<io::Result<String>> = lines.by_ref().take(SORT_SAMPLE_ROWS).collect();
+    let sample_len: usize = sample.iter().flatten().map(String::len).sum();
+    let avg_line_len = sample_len / sample.len().max(1) + std::mem::size_of::<String>();
+    let mem_limited_buffer_bytes = calculate_memory_limit(args.flag_memory_limit) as usize;
+    let buffer_lines = (mem_limited_buffer_bytes / avg_line_len).max(SORT_SAMPLE_ROWS);
+    log::info!("{buffer_lines} lines used for in memory mergesort buffer...");
+
+    let sorter: ExternalSorter<String, io::Error, LimitedBufferBuilder> =
+        match ExternalSorterBuilder::new()
+            .with_tmp_dir(path::Path::new(tmp_dir))
+            .with_buffer(LimitedBufferBuilder::new(buffer_lines, false))
+            .with_rw_buf_size(RW_BUFFER_CAPACITY)
+            .with_threads_number(util::njobs(args.flag_jobs))
+            .build()
+        {
+            Ok(sorter) => sorter,
+            Err(e) => {
+                return fail_clierror!("cannot create external sorter: {e}");
+            },
+        };
+
+    let sorted = match sorter.sort_by(sample.into_iter().chain(lines), |a, b| {
+        sort_line_key(a).cmp(sort_line_key(b))
+    }) {
+        Ok(sorted) => sorted,
+        Err(e) => {
+            return fail!(format!("cannot do external sort: {e:?}"));
+        },
+    };
+    let sorted: SortedLines =
+        Box::new(sorted.map(|line| {
+            line.map_err(|e| CliError::Other(format!("cannot do external sort: {e:?}")))
+        }));
+    Ok((sorter, sorted))
+}
+
+fn next_sorted_row(sorted: &mut SortedLines) -> CliResult<Option<(String, ByteRecord)>> {
+    Ok(match sorted.next() {
+        Some(line) => Some(decode_sort_line(&line?)),
+        None => None,
+    })
+}
+
+/// diffs two CSVs by externally sorting them by key, and then merging them in
+/// a single streaming pass, writing the diff rows as they are found
+fn external_diff(
+    args: &Args,
+    rdr_left: csv::Reader<Box<dyn io::Read + Send>>,
+    rdr_right: csv::Reader<Box<dyn io::Read + Send>>,
+    comparison: &FieldComparison,
+    diff_result_writer: &mut DiffResultWriter,
+) -> CliResult<()> {
+    let (_sorter_left, mut sorted_left) = external_sort(args, rdr_left, comparison)?;
+    let (_sorter_right, mut sorted_right) = external_sort(args, rdr_right, comparison)?;
+
+    let mut left = next_sorted_row(&mut sorted_left)?;
+    let mut right = next_sorted_row(&mut sorted_right)?;
+    loop {
+        let ordering = match (&left, &right) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((key_left, _)), Some((key_right, _))) => key_left.cmp(key_right),
+        };
+        match ordering {
+            Ordering::Less => {
+                if let Some((_, delete)) = left.take() {
+                    diff_result_writer.write_row(&DiffRow::Delete(delete))?;
+                }
+                left = next_sorted_row(&mut sorted_left)?;
+            },
+            Ordering::Greater => {
+                if let Some((_, add)) = right.take() {
+                    diff_result_writer.write_row(&DiffRow::Add(add))?;
+                }
+                right = next_sorted_row(&mut sorted_right)?;
+            },
+            Ordering::Equal => {
+                if let (Some((_, delete)), Some((_, add))) = (left.take(), right.take()) {
+                    let field_indices = comparison.modified_fields(&delete, &add);
+                    if !field_indices.is_empty() {
+                        diff_result_writer.write_row(&DiffRow::Modify {
+                            delete,
+                            add,
+                            field_indices,
+                        })?;
+                    }
+                }
+                left = next_sorted_row(&mut sorted_left)?;
+                right = next_sorted_row(&mut sorted_right)?;
+            },
+        }
+    }
+    Ok(())
+}
+
+/// A row of the diff result.
+enum DiffRow {
+    Add(ByteRecord),
+    Delete(ByteRecord),
+    Modify {
+        delete:        ByteRecord,
+        add:           ByteRecord,
+        field_indices: Vec<usize>,
+    },
+}
+
+/// How field values are compared, so differences that aren't changes (per
+/// --numeric-tolerance, --ignore-case, --trim & --ignore-columns) are ignored.
+struct FieldComparison {
+    numeric_tolerance: Option<f64>,
+    ignore_case:       bool,
+    trim:              bool,
+    ignore_columns:    Vec<usize>,
+    key_fields:        Vec<usize>,
+}
+
+impl FieldComparison {
+    /// whether two field values are equal, per the comparison options
+    fn fields_equal(&self, a: &[u8], b: &[u8]) -> bool {
+        let (a, b) = if self.trim {
+            (util::trim_bs_whitespace(a), util::trim_bs_whitespace(b))
+        } else {
+            (a, b)
+        };
+        if a == b {
+            return true;
+        }
+        if let Some(tolerance) = self.numeric_tolerance {
+            if let (Ok(x), Ok(y)) = (
+                fast_float2::parse::<f64, &[u8]>(a),
+                fast_float2::parse::<f64, &[u8]>(b),
+            ) {
+                return (x - y).abs() <= tolerance;
+            }
+        }
+        self.ignore_case
+            && String::from_utf8_lossy(a).to_lowercase()
+                == String::from_utf8_lossy(b).to_lowercase()
+    }
+
+    /// the indices of the fields of two rows that aren't equal, except ignored columns
+    fn modified_fields(&self, delete: &ByteRecord, add: &ByteRecord) -> Vec<usize> {
+        (0..delete.len().max(add.len()))
+            .filter(|i| {
+                !self.ignore_columns.contains(i)
+                    && !self.fields_equal(
+                        delete.get(*i).unwrap_or_default(),
+                        add.get(*i).unwrap_or_default(),
+                    )
+            })
+            .collect()
+    }
+
+    /// the key of a row, normalized with --ignore-case & --trim
+    fn normalized_key(&self, record: &ByteRecord) -> Vec<String> {
+        self.key_fields
+            .iter()
+            .map(|&i| {
+                let field = record.get(i).unwrap_or_default();
+                let field = if self.trim {
+                    util::trim_bs_whitespace(field)
+                } else {
+                    field
+                };
+                let value = String::from_utf8_lossy(field);
+                if self.ignore_case {
+                    value.to_lowercase()
+                } else {
+                    value.into_owned()
+                }
+            })
+            .collect()
+    }
+
+    /// converts the diff byte records to diff rows, dropping the modifications that
+    /// aren't changes per the comparison options
+    fn diff_rows(&self, diff_byte_records: DiffByteRecords) -> Vec<DiffRow> {
+        let mut rows: Vec<Option<DiffRow>> = diff_byte_records
+            .into_iter()
+            .filter_map(|dbr| match dbr {
+                DiffByteRecord::Add(add) => Some(DiffRow::Add(add.byte_record().clone())),
+                DiffByteRecord::Delete(delete) => {
+                    Some(DiffRow::Delete(delete.byte_record().clone()))
+                },
+                DiffByteRecord::Modify {
+                    delete,
+                    add,
+                    field_indices,
+                } => {
+                    let field_indices: Vec<usize> = field_indices
+                        .into_iter()
+                        .filter(|&i| {
+                            !self.ignore_columns.contains(&i)
+                                && !self.fields_equal(
+                                    delete.byte_record().get(i).unwrap_or_default(),
+                                    add.byte_record().get(i).unwrap_or_default(),
+                                )
+                        })
+                        .collect();
+                    (!field_indices.is_empty()).then(|| DiffRow::Modify {
+                        delete: delete.byte_record().clone(),
+                        add: add.byte_record().clone(),
+                        field_indices,
+                    })
+                },
+            })
+            .map(Some)
+            .collect();
+
+        if self.ignore_case || self.trim {
+            // a deleted and an added row whose keys only differ in case or whitespace
+            // are the same row, modified at the position of the deleted row
+            let mut deleted: AHashMap<Vec<String>, usize> = AHashMap::new();
+            for (i, row) in rows.iter().enumerate() {
+                if let Some(DiffRow::Delete(delete)) = row {
+                    deleted.entry(self.normalized_key(delete)).or_insert(i);
+                }
+            }
+            if !deleted.is_empty() {
+                for i in 0..rows.len() {
+                    let Some(DiffRow::Add(ref add)) = rows[i] else {
+                        continue;
+                    };
+                    let Some(delete_idx) = deleted.remove(&self.normalized_key(add)) else {
+                        continue;
+                    };
+                    let Some(DiffRow::Add(add)) = rows[i].take() else {
+                        unreachable!()
+                    };
+                    let Some(DiffRow::Delete(delete)) = rows[delete_idx].take() else {
+                        unreachable!()
+                    };
+                    let field_indices = self.modified_fields(&delete, &add);
+                    if !field_indices.is_empty() {
+                        rows[delete_idx] = Some(DiffRow::Modify {
+                            delete,
+                            add,
+                            field_indices,
+                        });
+                    }
+                }
+            }
+        }
+        rows.into_iter().flatten().collect()
+    }
 }
 
 struct CsvDiffWriter<W: Write> {
@@ -303,22 +1242,21 @@ impl<W: Write> CsvDiffWriter<W> {
         }
     }
 
-    fn write_headers(&mut self, headers: &Headers, num_columns: Option<&usize>) -> csv::Result<()> {
-        match (headers.headers_left(), headers.headers_right()) {
-            (Some(lbh), Some(_rbh)) => {
-                // currently, `diff` can only handle two CSVs that have the same
-                // headers ordering, so in this case we can either choose the left
-                // or right headers, because both are the same
-                if !self.no_headers {
-                    lbh.write_diffresult_header(&mut self.csv_writer)?;
-                }
-            },
-            (Some(bh), None) | (None, Some(bh)) => {
+    fn write_headers(
+        &mut self,
+        headers: Option<&ByteRecord>,
+        num_columns: Option<&usize>,
+    ) -> csv::Result<()> {
+        match headers {
+            // currently, `diff` can only handle two CSVs that have the same
+            // headers ordering, so if both have headers, we can either choose the
+            // left or right headers, because both are the same
+            Some(bh) => {
                 if !self.no_headers {
                     bh.write_diffresult_header(&mut self.csv_writer)?;
                 }
             },
-            (None, None) => {
+            None => {
                 if let (Some(&num_cols), false) = (num_columns.filter(|&&c| c > 0), self.no_headers)
                 {
                     let headers_generic = rename_headers_all_generic(num_cols);
@@ -332,29 +1270,17 @@ impl<W: Write> CsvDiffWriter<W> {
         Ok(())
     }
 
-    fn write_diff_byte_records(&mut self, diff_byte_records: DiffByteRecords) -> io::Result<()> {
-        self.write_headers(
-            diff_byte_records.headers(),
-            diff_byte_records.num_columns().as_ref(),
-        )?;
-        for dbr in diff_byte_records {
-            self.write_diff_byte_record(&dbr)?;
-        }
-        self.csv_writer.flush()?;
-        Ok(())
-    }
-
-    fn write_diff_byte_record(&mut self, diff_byte_record: &DiffByteRecord) -> csv::Result<()> {
+    fn write_diff_row(&mut self, diff_row: &DiffRow) -> csv::Result<()> {
         let add_sign: &[u8] = &b"+"[..];
         let remove_sign: &[u8] = &b"-"[..];
 
-        match diff_byte_record {
-            DiffByteRecord::Add(add) => {
+        match diff_row {
+            DiffRow::Add(add) => {
                 let mut vec = vec![add_sign];
-                vec.extend(add.byte_record());
+                vec.extend(add);
                 self.csv_writer.write_record(vec)
             },
-            DiffByteRecord::Modify {
+            DiffRow::Modify {
                 delete,
                 add,
                 field_indices,
@@ -362,12 +1288,12 @@ impl<W: Write> CsvDiffWriter<W> {
                 let vec_del = if self.drop_equal_fields {
                     self.fill_modified_and_drop_equal_fields(
                         remove_sign,
-                        delete.byte_record(),
+                        delete,
                         field_indices.as_slice(),
                     )
                 } else {
                     let mut tmp = vec![remove_sign];
-                    tmp.extend(delete.byte_record());
+                    tmp.extend(delete);
                     tmp
                 };
 
@@ -376,20 +1302,20 @@ impl<W: Write> CsvDiffWriter<W> {
                 let vec_add = if self.drop_equal_fields {
                     self.fill_modified_and_drop_equal_fields(
                         add_sign,
-                        add.byte_record(),
+                        add,
                         field_indices.as_slice(),
                     )
                 } else {
                     let mut tmp = vec![add_sign];
-                    tmp.extend(add.byte_record());
+                    tmp.extend(add);
                     tmp
                 };
 
                 self.csv_writer.write_record(vec_add)
             },
-            DiffByteRecord::Delete(del) => {
+            DiffRow::Delete(del) => {
                 let mut vec = vec![remove_sign];
-                vec.extend(del.byte_record());
+                vec.extend(del);
                 self.csv_writer.write_record(vec)
             },
         }
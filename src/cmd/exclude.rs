@@ -22,6 +22,8 @@ Examples:
     qsv exclude id records.csv id previously-processed.csv --output new-records.csv
     qsv exclude -v id records.csv id previously-processed.csv -o intersection.csv
     qsv exclude --ignore-case id records.csv id previously-processed.csv
+    qsv exclude --key-transform strip_punct,squeeze,lower name records.csv \
+       name previously-processed.csv
     qsv exclude id records.csv id previously-processed.csv |
        qsv sort > new-sorted-records.csv
     qsv exclude id records.csv id previously-processed.csv | qsv sort |
@@ -40,6 +42,12 @@ input arguments:
 
 exclude options:
     -i, --ignore-case      When set, matching is done case insensitively.
+    --key-transform <ops>  Normalize the values to match with a comma-delimited series
+                           of `qsv apply operations`, applied in order before matching.
+                           The original values are written to the output.
+                           Valid operations are trim, ltrim, rtrim, ltrim0, lower,
+                           upper, squeeze, squeeze0, strip_punct and unaccent.
+                           Requires the apply feature.
     -v                     When set, matching rows will be the only ones included,
                            forming set intersection, instead of the ones discarded.
 
@@ -64,21 +72,22 @@ use crate::{
     index::Indexed,
     select::{SelectColumns, Selection},
     util,
-    util::ByteString,
+    util::{ByteString, KeyTransform},
     CliResult,
 };
 
 #[derive(Deserialize)]
 struct Args {
-    arg_columns1:     SelectColumns,
-    arg_input1:       String,
-    arg_columns2:     SelectColumns,
-    arg_input2:       String,
-    flag_v:           bool,
-    flag_output:      Option<String>,
-    flag_no_headers:  bool,
-    flag_ignore_case: bool,
-    flag_delimiter:   Option<Delimiter>,
+    arg_columns1:       SelectColumns,
+    arg_input1:         String,
+    arg_columns2:       SelectColumns,
+    arg_input2:         String,
+    flag_v:             bool,
+    flag_output:        Option<String>,
+    flag_no_headers:    bool,
+    flag_ignore_case:   bool,
+    flag_key_transform: Option<String>,
+    flag_delimiter:     Option<Delimiter>,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
@@ -89,13 +98,14 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 }
 
 struct IoState<R, W: io::Write> {
-    wtr:        csv::Writer<W>,
-    rdr1:       csv::Reader<R>,
-    sel1:       Selection,
-    rdr2:       csv::Reader<R>,
-    sel2:       Selection,
-    no_headers: bool,
-    casei:      bool,
+    wtr:           csv::Writer<W>,
+    rdr1:          csv::Reader<R>,
+    sel1:          Selection,
+    rdr2:          csv::Reader<R>,
+    sel2:          Selection,
+    no_headers:    bool,
+    casei:         bool,
+    key_transform: Option<KeyTransform>,
 }
 
 impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
@@ -112,10 +122,20 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
         #[allow(unused_assignments)]
         let mut curr_row = csv::ByteRecord::new();
 
-        let validx = ValueIndex::new(self.rdr2, &self.sel2, self.casei)?;
+        let validx = ValueIndex::new(
+            self.rdr2,
+            &self.sel2,
+            self.casei,
+            self.key_transform.as_ref(),
+        )?;
         for row in self.rdr1.byte_records() {
             curr_row = row?;
-            let key = get_row_key(&self.sel1, &curr_row, self.casei);
+            let key = get_row_key(
+                &self.sel1,
+                &curr_row,
+                self.casei,
+                self.key_transform.as_ref(),
+            );
             if let Some(_rows) = validx.values.get(&key) {
                 if invert {
                     self.wtr.write_record(curr_row.iter())?;
@@ -150,6 +170,11 @@ impl Args {
             sel2,
             no_headers: rconf1.no_headers,
             casei: self.flag_ignore_case,
+            key_transform: self
+                .flag_key_transform
+                .as_deref()
+                .map(KeyTransform::new)
+                .transpose()?,
         })
     }
 
@@ -186,7 +211,12 @@ struct ValueIndex<R> {
 }
 
 impl<R: io::Read + io::Seek> ValueIndex<R> {
-    fn new(mut rdr: csv::Reader<R>, sel: &Selection, casei: bool) -> CliResult<ValueIndex<R>> {
+    fn new(
+        mut rdr: csv::Reader<R>,
+        sel: &Selection,
+        casei: bool,
+        key_transform: Option<&KeyTransform>,
+    ) -> CliResult<ValueIndex<R>> {
         let mut val_idx = AHashMap::with_capacity(10000);
         let mut row_idx = io::Cursor::new(Vec::with_capacity(8 * 10000));
         let (mut rowi, mut count) = (0_usize, 0_usize);
@@ -215,10 +245,7 @@ impl<R: io::Read + io::Seek> ValueIndex<R> {
             // indexes in one pass.
             row_idx.write_u64::<BigEndian>(row.position().unwrap().byte())?;
 
-            let fields: Vec<_> = sel
-                .select(&row)
-                .map(|v| util::transform(v, casei))
-                .collect();
+            let fields = get_row_key(sel, &row, casei, key_transform);
             match val_idx.entry(fields) {
                 Entry::Vacant(v) => {
                     let mut rows = Vec::with_capacity(4);
@@ -263,6 +290,18 @@ impl<R> fmt::Debug for ValueIndex<R> {
 }
 
 #[inline]
-fn get_row_key(sel: &Selection, row: &csv::ByteRecord, casei: bool) -> Vec<ByteString> {
-    sel.select(row).map(|v| util::transform(v, casei)).collect()
+fn get_row_key(
+    sel: &Selection,
+    row: &csv::ByteRecord,
+    casei: bool,
+    key_transform: Option<&KeyTransform>,
+) -> Vec<ByteString> {
+    sel.select(row)
+        .map(|v| match (key_transform, simdutf8::basic::from_utf8(v)) {
+            (Some(key_transform), Ok(s)) => {
+                util::transform(key_transform.apply(s.trim()).as_bytes(), casei)
+            },
+            _ => util::transform(v, casei),
+        })
+        .collect()
 }
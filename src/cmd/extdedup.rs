@@ -18,6 +18,23 @@ This command has TWO modes of operation.
 
 A duplicate count will be sent to <stderr>.
 
+PERSISTENT CACHE:
+
+With --cache-file, the on-disk hash table is loaded from and saved to <file>, so an
+incremental file (e.g. a daily extract) can be deduplicated against every key seen in
+previous runs. If <file> doesn't exist, it is created.
+ * --update (the default) also inserts the new keys of the input into the cache.
+ * --check-only only answers "seen before?" - rows with keys already in the cache are
+   duplicates, and the cache is neither changed nor saved. As no keys are inserted,
+   rows that are only duplicated within the input itself are NOT deduplicated.
+
+  $ qsv extdedup --select id --cache-file ids.odht day1.csv day1-deduped.csv
+  $ qsv extdedup --select id --cache-file ids.odht day2.csv day2-deduped.csv
+  $ qsv extdedup --select id --cache-file ids.odht --check-only day3.csv day3-new.csv
+
+A cache file should only be used with the same --select columns (or LINE MODE) it was
+created with, as it only stores the keys.
+
 Usage:
     qsv extdedup [options] [<input>] [<output>]
     qsv extdedup --help
@@ -33,6 +50,27 @@ extdedup options:
                                Note that the file will NOT be a valid CSV.
                                It is a list of duplicate lines, with the row number of the
                                duplicate separated by a tab from the duplicate line itself.
+    --keep <arg>               CSV MODE ONLY: which row of each group of duplicates to keep:
+                                 first: the first row (default).
+                                 last: the last row.
+                                 max:<col>: the row with the largest value in <col>.
+                                 min:<col>: the row with the smallest value in <col>.
+                               See 'qsv dedup --help' for details.
+                               Except for "first", the input is read twice and the kept
+                               rows are held in memory, so it can't be stdin and memory
+                               is proportional to the number of distinct keys.
+                               Each kept row is written at the position of its input row.
+    --merge                    CSV MODE ONLY: coalesce the duplicates of each group into
+                               the row kept by --keep, filling its empty fields with the
+                               values of the other rows of the group.
+                               Like --keep, it reads the input twice and holds the
+                               kept rows in memory.
+    --cache-file <file>        Load the on-disk hash table from <file>, and save it back
+                               with the new keys after deduping. See PERSISTENT CACHE above.
+                               Cannot be used with --keep or --merge.
+    --update                   Insert the new keys into --cache-file. This is the default.
+    --check-only               Only look up the keys in --cache-file, without inserting the
+                               new keys nor saving the cache.
     -H, --human-readable       Comma separate duplicate count.
     --memory-limit <arg>       The maximum amount of memory to buffer the on-disk hash table.
                                If less than 50, this is a percentage of total memory.
@@ -56,17 +94,20 @@ Common options:
 use std::{
     fs,
     io::{self, stdin, stdout, BufRead, Write},
+    path::Path,
 };
 
+use ahash::AHashMap;
 use indicatif::HumanCount;
 use serde::Deserialize;
 use sysinfo::System;
 
 use crate::{
+    cmd::dedup::{Keep, Survivor},
     config,
     config::{Config, Delimiter},
     odhtcache,
-    select::SelectColumns,
+    select::{SelectColumns, Selection},
     util, CliResult,
 };
 
@@ -79,6 +120,11 @@ struct Args {
     flag_delimiter:      Option<Delimiter>,
     flag_no_output:      bool,
     flag_dupes_output:   Option<String>,
+    flag_keep:           Option<String>,
+    flag_merge:          bool,
+    flag_cache_file:     Option<String>,
+    flag_update:         bool,
+    flag_check_only:     bool,
     flag_human_readable: bool,
     flag_memory_limit:   Option<u64>,
     flag_quiet:          bool,
@@ -97,6 +143,29 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let quiet = args.flag_quiet;
     let human_readable = args.flag_human_readable;
 
+    if args.flag_select.is_none() && (args.flag_keep.is_some() || args.flag_merge) {
+        return fail_incorrectusage_clierror!(
+            "--keep and --merge can only be used in CSV MODE, with --select."
+        );
+    }
+
+    if args.flag_cache_file.is_some() {
+        if args.flag_keep.is_some() || args.flag_merge {
+            return fail_incorrectusage_clierror!(
+                "--cache-file cannot be used with --keep or --merge."
+            );
+        }
+        if args.flag_update && args.flag_check_only {
+            return fail_incorrectusage_clierror!(
+                "--update and --check-only cannot be used together."
+            );
+        }
+    } else if args.flag_update || args.flag_check_only {
+        return fail_incorrectusage_clierror!(
+            "--update and --check-only can only be used with --cache-file."
+        );
+    }
+
     let dupes_count = if args.flag_select.is_some() {
         dedup_csv(args, mem_limited_buffer_bytes)?
     } else {
@@ -123,7 +192,7 @@ fn dedup_csv(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes
     let rconfig = Config::new(args.arg_input.as_ref())
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
-        .select(args.flag_select.unwrap());
+        .select(args.flag_select.clone().unwrap());
 
     let mut rdr = rconfig.reader()?;
     let mut wtr = Config::new(args.arg_output.as_ref()).writer()?;
@@ -138,9 +207,34 @@ fn dedup_csv(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes
         dupewtr.write_byte_record(&dupe_headers)?;
     }
 
-    let mut dedup_cache = odhtcache::ExtDedupCache::new(mem_limited_buffer);
-    let mut dupes_count = 0_u64;
     let sel = rconfig.selection(&headers)?;
+    let keep = match args.flag_keep {
+        Some(ref spec) => Keep::parse(spec, &headers, args.flag_no_headers)?,
+        None => Keep::First,
+    };
+    if args.flag_merge || !matches!(keep, Keep::First) {
+        if rconfig.is_stdin() {
+            return fail_incorrectusage_clierror!(
+                "--keep and --merge read the input twice, so it cannot be stdin."
+            );
+        }
+        rconfig.write_headers(&mut rdr, &mut wtr)?;
+        let dupes_count = dedup_csv_survivors(
+            &rconfig,
+            &sel,
+            keep,
+            args.flag_merge,
+            &mut wtr,
+            dupes_output.then_some(&mut dupewtr),
+        )?;
+        dupewtr.flush()?;
+        wtr.flush()?;
+        return Ok(dupes_count);
+    }
+
+    let mut dedup_cache = load_dedup_cache(&args, mem_limited_buffer)?;
+    let check_only = args.flag_check_only;
+    let mut dupes_count = 0_u64;
 
     rconfig.write_headers(&mut rdr, &mut wtr)?;
 
@@ -152,16 +246,7 @@ fn dedup_csv(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes
 
     for (row_idx, row) in rdr.byte_records().enumerate() {
         curr_row.clone_from(&row?);
-        key.clear();
-        for field in sel.select(&curr_row) {
-            if let Ok(s_utf8) = simdutf8::basic::from_utf8(field) {
-                key.push_str(s_utf8);
-            } else {
-                utf8_string.clear();
-                utf8_string.push_str(&String::from_utf8_lossy(field));
-                key.push_str(&utf8_string);
-            }
-        }
+        row_key(&sel, &curr_row, &mut key, &mut utf8_string);
 
         if dedup_cache.contains(&key) {
             dupes_count += 1;
@@ -172,17 +257,104 @@ fn dedup_csv(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes
                 dupewtr.write_byte_record(&dupe_row)?;
             }
         } else {
-            dedup_cache.insert(&key);
+            if !check_only {
+                dedup_cache.insert(&key);
+            }
             wtr.write_byte_record(&curr_row)?;
         }
     }
 
     dupewtr.flush()?;
     wtr.flush()?;
+    save_dedup_cache(&args, &mut dedup_cache)?;
 
     Ok(dupes_count)
 }
 
+/// loads the --cache-file hash table, or creates a new cache for this run
+fn load_dedup_cache(args: &Args, mem_limited_buffer: u64) -> CliResult<odhtcache::ExtDedupCache> {
+    Ok(match args.flag_cache_file {
+        Some(ref cache_file) => {
+            odhtcache::ExtDedupCache::load(Path::new(cache_file), mem_limited_buffer)?
+        },
+        None => odhtcache::ExtDedupCache::new(mem_limited_buffer),
+    })
+}
+
+/// saves the cache to --cache-file, unless --check-only is set
+fn save_dedup_cache(args: &Args, dedup_cache: &mut odhtcache::ExtDedupCache) -> CliResult<()> {
+    if let Some(ref cache_file) = args.flag_cache_file {
+        if !args.flag_check_only {
+            dedup_cache.save(Path::new(cache_file))?;
+        }
+    }
+    Ok(())
+}
+
+/// builds the dedup key of a row from its selected columns
+#[inline]
+fn row_key(sel: &Selection, row: &csv::ByteRecord, key: &mut String, utf8_string: &mut String) {
+    key.clear();
+    for field in sel.select(row) {
+        if let Ok(s_utf8) = simdutf8::basic::from_utf8(field) {
+            key.push_str(s_utf8);
+        } else {
+            utf8_string.clear();
+            utf8_string.push_str(&String::from_utf8_lossy(field));
+            key.push_str(utf8_string);
+        }
+    }
+}
+
+/// dedups the CSV with --keep/--merge in two passes. The first pass finds the row kept
+/// of each group of duplicates, and the second writes each kept row at the position
+/// of its input row, and the other rows to dupewtr.
+fn dedup_csv_survivors(
+    rconfig: &Config,
+    sel: &Selection,
+    keep: Keep,
+    merge: bool,
+    wtr: &mut csv::Writer<Box<dyn Write + 'static>>,
+    mut dupewtr: Option<&mut csv::Writer<Box<dyn Write + 'static>>>,
+) -> Result<u64, crate::clitypes::CliError> {
+    let mut key = String::with_capacity(20);
+    let mut utf8_string = String::with_capacity(20);
+
+    let mut survivors: AHashMap<String, Survivor> = AHashMap::new();
+    let mut rdr = rconfig.reader()?;
+    for (row_idx, row) in rdr.byte_records().enumerate() {
+        let row = row?;
+        row_key(sel, &row, &mut key, &mut utf8_string);
+        if let Some(survivor) = survivors.get_mut(&key) {
+            survivor.add(row, row_idx);
+        } else {
+            survivors.insert(key.clone(), Survivor::new(keep, merge, row, row_idx));
+        }
+    }
+
+    let mut dupes_count = 0_u64;
+    let mut dupe_row = csv::ByteRecord::new();
+    let mut rdr = rconfig.reader()?;
+    for (row_idx, row) in rdr.byte_records().enumerate() {
+        let row = row?;
+        row_key(sel, &row, &mut key, &mut utf8_string);
+        // safety: every key was added in the first pass
+        let survivor = &survivors[&key];
+        if survivor.index() == row_idx {
+            wtr.write_byte_record(survivor.row())?;
+        } else {
+            dupes_count += 1;
+            if let Some(ref mut dupewtr) = dupewtr {
+                dupe_row.clear();
+                dupe_row.push_field(itoa::Buffer::new().format(row_idx + 1).as_bytes());
+                dupe_row.extend(row.iter());
+                dupewtr.write_byte_record(&dupe_row)?;
+            }
+        }
+    }
+    Ok(dupes_count)
+}
+
 fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes::CliError> {
     let input_reader: Box<dyn BufRead> = match &args.arg_input {
         Some(input_path) => {
@@ -211,7 +383,7 @@ fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clityp
     };
     let mut write_dupes = false;
     #[cfg(target_family = "unix")]
-    let mut dupes_writer = if let Some(dupes_output) = args.flag_dupes_output {
+    let mut dupes_writer = if let Some(ref dupes_output) = args.flag_dupes_output {
         write_dupes = true;
         io::BufWriter::with_capacity(
             config::DEFAULT_WTR_BUFFER_CAPACITY,
@@ -224,7 +396,7 @@ fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clityp
         )
     };
     #[cfg(target_family = "windows")]
-    let mut dupes_writer = if let Some(dupes_output) = args.flag_dupes_output {
+    let mut dupes_writer = if let Some(ref dupes_output) = args.flag_dupes_output {
         write_dupes = true;
         io::BufWriter::with_capacity(
             config::DEFAULT_WTR_BUFFER_CAPACITY,
@@ -236,7 +408,7 @@ fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clityp
             fs::File::create("nul")?,
         )
     };
-    let mut dedup_cache = odhtcache::ExtDedupCache::new(mem_limited_buffer);
+    let mut dedup_cache = load_dedup_cache(&args, mem_limited_buffer)?;
     let mut dupes_count = 0_u64;
     let mut line_work = String::with_capacity(1024);
     for (row_idx, line) in input_reader.lines().enumerate() {
@@ -247,7 +419,9 @@ fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clityp
                 writeln!(dupes_writer, "{row_idx}\t{line_work}")?;
             }
         } else {
-            dedup_cache.insert(&line_work);
+            if !args.flag_check_only {
+                dedup_cache.insert(&line_work);
+            }
             if args.flag_no_output {
                 continue;
             }
@@ -256,6 +430,7 @@ fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clityp
     }
     dupes_writer.flush()?;
     output_writer.flush()?;
+    save_dedup_cache(&args, &mut dedup_cache)?;
 
     Ok(dupes_count)
 }
@@ -4,10 +4,11 @@ Sort an arbitrarily large CSV/text file using a multithreaded external sort algo
 This command has TWO modes of operation.
 
  * CSV MODE
-   when --select is set, it sorts based on the given column/s. Requires an index.
-   See `qsv select --help` for select syntax details.
+   when --select or --by is set, it sorts based on the given column/s. Requires an index.
+   See `qsv select --help` for select syntax details, and `qsv sort --help` for
+   the --by sort specification.
  * LINE MODE
-   when --select is NOT set, it sorts any input text file (not just CSVs) on a
+   when --select and --by are NOT set, it sorts any input text file (not just CSVs) on a
    line-by-line basis. If sorting a non-CSV file, be sure to set --no-headers, 
    otherwise, the first line will not be included in the external sort.
 
@@ -20,6 +21,14 @@ External sort option:
                            Note that the outputs will remain at the full width of the CSV.
                            If --select is NOT set, extsort will work in LINE MODE, sorting
                            the input as a text file on a line-by-line basis.
+    --by <spec>            Sort by a typed, multi-key sort specification (CSV MODE),
+                           e.g. "date:desc:date,amount:asc:num,name:asc:natural".
+                           See 'qsv sort --help' for the format details.
+                           Cannot be used with --select or --reverse.
+    --collation <locale>   Compare using the collation rules of a locale, e.g. "de"
+                           or "de:primary", instead of byte-wise.
+                           See 'qsv sort --help' for the format details.
+                           Cannot be used with --by.
     -R, --reverse          Reverse order
     --memory-limit <arg>   The maximum amount of memory to buffer the external merge sort.
                            If less than 50, this is a percentage of total memory.
@@ -54,7 +63,10 @@ use ext_sort::{ExternalSorter, ExternalSorterBuilder, LimitedBufferBuilder};
 use serde::Deserialize;
 
 use crate::{
-    cmd::extdedup::calculate_memory_limit,
+    cmd::{
+        extdedup::calculate_memory_limit,
+        sort::{Collation, SortSpec},
+    },
     config,
     config::{Config, Delimiter},
     select::SelectColumns,
@@ -66,6 +78,8 @@ struct Args {
     arg_input:         Option<String>,
     arg_output:        Option<String>,
     flag_select:       Option<SelectColumns>,
+    flag_by:           Option<String>,
+    flag_collation:    Option<String>,
     flag_reverse:      bool,
     flag_delimiter:    Option<Delimiter>,
     flag_jobs:         Option<usize>,
@@ -78,6 +92,18 @@ const RW_BUFFER_CAPACITY: usize = 1_000_000; // 1 MB
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
+    if args.flag_by.is_some() && (args.flag_select.is_some() || args.flag_reverse) {
+        return fail_incorrectusage_clierror!("--by cannot be used with --select or --reverse.");
+    }
+    let collation = match args.flag_collation {
+        Some(ref locale) => {
+            if args.flag_by.is_some() {
+                return fail_incorrectusage_clierror!("--collation cannot be used with --by.");
+            }
+            Some(Collation::parse(locale)?)
+        },
+        None => None,
+    };
 
     // check if tmp dir exists
     let tmp_dir = match &args.flag_tmp_dir {
@@ -112,10 +138,10 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             },
         };
 
-    if args.flag_select.is_some() {
-        sort_csv(&args, &tmp_dir, &sorter)
+    if args.flag_select.is_some() || args.flag_by.is_some() {
+        sort_csv(&args, &tmp_dir, &sorter, collation.as_ref())
     } else {
-        sort_lines(&args, &sorter)
+        sort_lines(&args, &sorter, collation.as_ref())
     }
 }
 
@@ -123,11 +149,16 @@ fn sort_csv(
     args: &Args,
     tmp_dir: &str,
     sorter: &ExternalSorter<String, io::Error, LimitedBufferBuilder>,
+    collation: Option<&Collation>,
 ) -> Result<(), crate::clitypes::CliError> {
     let rconfig = Config::new(args.arg_input.as_ref())
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
-        .select(args.flag_select.clone().unwrap());
+        .select(
+            args.flag_select
+                .clone()
+                .unwrap_or_else(|| SelectColumns::parse("").unwrap()),
+        );
 
     let mut idxfile = if let Ok(idx) = rconfig.indexed() {
         if idx.is_none() {
@@ -145,6 +176,15 @@ fn sort_csv(
 
     let headers = input_rdr.byte_headers()?.clone();
     let sel = rconfig.selection(&headers)?;
+    let sort_spec = match args.flag_by {
+        Some(ref by) => Some(SortSpec::parse(
+            by,
+            &headers,
+            args.flag_no_headers,
+            rconfig.get_dmy_preference(),
+        )?),
+        None => None,
+    };
 
     let mut sort_key = String::with_capacity(20);
     let mut utf8_string = String::with_capacity(20);
@@ -153,19 +193,23 @@ fn sort_csv(
     let rowcount = idxfile.count();
     let width = rowcount.to_string().len();
 
-    // first pass. get the selected columns, and the record position
-    // then write them to a temp text file with the selected columns and the position
+    // first pass. get the selected columns (or the encoded --by sort values), and the record
+    // position then write them to a temp text file with the selected columns and the position
     // separated by "|". Pad the position with leading zeroes, so it will always be the same width
     for row in input_rdr.byte_records() {
         curr_row.clone_from(&row?);
         sort_key.clear();
-        for field in sel.select(&curr_row) {
-            if let Ok(s_utf8) = simdutf8::basic::from_utf8(field) {
-                sort_key.push_str(s_utf8);
-            } else {
-                utf8_string.clear();
-                utf8_string.push_str(&String::from_utf8_lossy(field));
-                sort_key.push_str(&utf8_string);
+        if let Some(ref spec) = sort_spec {
+            SortSpec::encode_values(&spec.values(&curr_row), &mut sort_key);
+        } else {
+            for field in sel.select(&curr_row) {
+                if let Ok(s_utf8) = simdutf8::basic::from_utf8(field) {
+                    sort_key.push_str(s_utf8);
+                } else {
+                    utf8_string.clear();
+                    utf8_string.push_str(&String::from_utf8_lossy(field));
+                    sort_key.push_str(&utf8_string);
+                }
             }
         }
         let idx_position = curr_row.position().unwrap();
@@ -182,8 +226,27 @@ fn sort_csv(
     );
 
     let reverse_flag = args.flag_reverse;
+    let sort_spec = sort_spec.as_ref();
     let compare = |a: &String, b: &String| {
-        if reverse_flag {
+        if let Some(spec) = sort_spec {
+            // compare the --by sort values, then the positions, so the sort is stable
+            let (key_a, position_a) = a.split_at(a.len() - width - 1);
+            let (key_b, position_b) = b.split_at(b.len() - width - 1);
+            spec.compare_encoded(key_a, key_b)
+                .then_with(|| position_a.cmp(position_b))
+        } else if let Some(collation) = collation {
+            // compare the selected columns per the collation, then the positions
+            let (key_a, position_a) = a.split_at(a.len() - width - 1);
+            let (key_b, position_b) = b.split_at(b.len() - width - 1);
+            let ordering = collation
+                .compare(key_a.as_bytes(), key_b.as_bytes())
+                .then_with(|| position_a.cmp(position_b));
+            if reverse_flag {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        } else if reverse_flag {
             a.cmp(b).reverse()
         } else {
             a.cmp(b)
@@ -251,6 +314,7 @@ fn sort_csv(
 fn sort_lines(
     args: &Args,
     sorter: &ExternalSorter<String, io::Error, LimitedBufferBuilder>,
+    collation: Option<&Collation>,
 ) -> Result<(), crate::clitypes::CliError> {
     let mut input_rdr: Box<dyn BufRead> = match &args.arg_input {
         Some(input_path) => {
@@ -294,10 +358,14 @@ fn sort_lines(
 
     let reverse_flag = args.flag_reverse;
     let compare = |a: &String, b: &String| {
+        let ordering = match collation {
+            Some(collation) => collation.compare(a.as_bytes(), b.as_bytes()),
+            None => a.cmp(b),
+        };
         if reverse_flag {
-            a.cmp(b).reverse()
+            ordering.reverse()
         } else {
-            a.cmp(b)
+            ordering
         }
     };
 
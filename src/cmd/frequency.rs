@@ -19,7 +19,9 @@ column cardinalities. This short-circuits frequency compilation for columns with
 all unique values (i.e. where rowcount == cardinality), eliminating the need to
 maintain an in-memory hashmap for ID columns. This allows `frequency` to handle
 larger-than-memory datasets with the added benefit of also making it faster when
-working with datasets with ID columns.
+working with datasets with ID columns. Note that if the stats cache was created with
+`stats --approx-cardinality`, only the columns whose cardinality was counted exactly
+can be short-circuited, as estimated cardinalities can't confirm that ALL values are unique.
 
 STATS_MODE "none" NOTES:
 
@@ -37,6 +39,40 @@ STATS_MODE "none" NOTES:
     This is useful when you want to apply limits only to columns with a large number
     of unique items and not to columns with a small number of unique items.
 
+JSON OUTPUT:
+
+    With --json, the frequency tables are written as a JSON object:
+
+    {
+      "fieldcount": 2,
+      "fields": [
+        {
+          "field": "h1",
+          "cardinality": 3,
+          "nullcount": 1,
+          "frequencies": [
+            {"value": "a", "count": 2, "percentage": 50.0},
+            ...
+          ]
+        },
+        ...
+      ]
+    }
+
+    The cardinality is the number of distinct values of the field (NULL included) and
+    the nullcount is the number of NULLs (0 with --no-nulls), regardless of --limit.
+    With --groupby, the fields are nested under each group instead:
+
+    {
+      "groupby": ["region"],
+      "groups": [
+        {"group": {"region": "east"}, "rowcount": 3, "fields": [...]},
+        ...
+      ]
+    }
+
+    --json and --groupby cannot be used with --weight or --approx.
+
 For examples, see https://github.com/dathere/qsv/blob/master/tests/test_frequency.rs.
 
 Usage:
@@ -84,7 +120,40 @@ frequency options:
     --no-trim               Don't trim whitespace from values when computing frequencies.
                             The default is to trim leading and trailing whitespaces.
     --no-nulls              Don't include NULLs in the frequency table.
+    --weight <col>          Sum the numeric values of the given column as the weight of
+                            each value, instead of counting the rows (e.g. survey sampling
+                            weights). The "count" column is then the sum of the weights,
+                            rounded to 4 decimal places, and the percentages are of the
+                            total weight. Rows with empty, non-numeric or negative weights
+                            are ignored, and the weight column is excluded from the
+                            frequency tables. The stats cache is not used to short-circuit
+                            columns with all unique values, and --unq-limit is ignored.
     -i, --ignore-case       Ignore case when computing frequencies.
+    --groupby <cols>        Compute the frequency tables within each group of rows with the
+                            same values in the given columns, in a single pass. The group
+                            columns are prepended to the output, the groups are sorted by
+                            their values, and the percentages are of the rows of each group.
+                            The group columns are excluded from the frequency tables, and the
+                            stats cache is not used to short-circuit columns with all unique
+                            values. See 'qsv select --help' for the format details.
+    --json                  Output the frequency tables as JSON instead of CSV, with the
+                            values of each field nested under it, along with the field's
+                            cardinality and null count. See JSON OUTPUT below.
+    --approx                Compute approximate frequency tables in memory independent of the
+                            cardinality of the columns, keeping a Space-Saving summary of the
+                            most frequent values of each column. Use this for columns with too
+                            many distinct values to fit into memory (e.g. street addresses).
+                            The counts are upper bounds of the frequencies, and an additional
+                            "count_error" column has the maximum overestimate of each count,
+                            i.e. the frequency of a value is between count - count_error and
+                            count. Any value occurring more than N / --approx-capacity times,
+                            where N is the number of rows, is guaranteed to be listed.
+                            Cannot be used with --asc or --weight. The "Other" category
+                            is not included, and --unq-limit & --stats-mode are ignored.
+    --approx-capacity <n>   The number of values tracked per column with --approx. The larger
+                            the capacity, the more accurate the counts, with memory proportional
+                            to the capacity. At least --limit values are tracked.
+                            [default: 1000]
     --stats-mode <arg>      The stats mode to use when computing frequencies with cardinalities.
                             Having column cardinalities short-circuits frequency compilation and
                             eliminates memory usage for columns with all unique values.
@@ -119,18 +188,25 @@ Common options:
                            CSV into memory using CONSERVATIVE heuristics.
 "#;
 
-use std::{fs, io, sync::OnceLock};
+use std::{
+    collections::hash_map::Entry,
+    fs,
+    io::{self, Write},
+    sync::OnceLock,
+};
 
+use ahash::AHashMap;
 use crossbeam_channel;
 use indicatif::HumanCount;
 use rust_decimal::prelude::*;
 use serde::Deserialize;
-use stats::{merge_all, Frequencies};
+use serde_json::json;
+use stats::{merge_all, Commute, Frequencies};
 use threadpool::ThreadPool;
 
 use crate::{
     config::{Config, Delimiter},
-    index::Indexed,
+    index::{Indexed, IndexedSource},
     select::{SelectColumns, Selection},
     util,
     util::{get_stats_records, ByteString, StatsMode},
@@ -152,6 +228,11 @@ pub struct Args {
     pub flag_no_trim:         bool,
     pub flag_no_nulls:        bool,
     pub flag_ignore_case:     bool,
+    pub flag_weight:          Option<SelectColumns>,
+    pub flag_approx:          bool,
+    pub flag_approx_capacity: usize,
+    pub flag_groupby:         Option<SelectColumns>,
+    pub flag_json:            bool,
     pub flag_stats_mode:      String,
     pub flag_all_unique_text: String,
     pub flag_jobs:            Option<usize>,
@@ -163,34 +244,64 @@ pub struct Args {
 
 const NULL_VAL: &[u8] = b"(NULL)";
 const NON_UTF8_ERR: &str = "<Non-UTF8 ERROR>";
+// the number of decimal places the sums of --weight values are rounded to
+const WEIGHT_DEC_PLACES: u32 = 4;
 
 static UNIQUE_COLUMNS: OnceLock<Vec<usize>> = OnceLock::new();
 static FREQ_ROW_COUNT: OnceLock<u64> = OnceLock::new();
+static WEIGHT_COLUMN: OnceLock<usize> = OnceLock::new();
+static GROUPBY_COLUMNS: OnceLock<Selection> = OnceLock::new();
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
     let rconfig = args.rconfig();
 
-    // we're loading the entire file into memory, we need to check avail mem
-    if let Some(path) = rconfig.path.clone() {
+    if args.flag_approx {
+        if args.flag_asc || args.flag_weight.is_some() {
+            return fail_incorrectusage_clierror!(
+                "--approx cannot be used with --asc or --weight."
+            );
+        }
+        if args.flag_json || args.flag_groupby.is_some() {
+            return fail_incorrectusage_clierror!(
+                "--json and --groupby cannot be used with --approx."
+            );
+        }
+        if args.flag_approx_capacity == 0 {
+            return fail_incorrectusage_clierror!("--approx-capacity must be greater than zero.");
+        }
+    } else if (args.flag_json || args.flag_groupby.is_some()) && args.flag_weight.is_some() {
+        return fail_incorrectusage_clierror!("--json and --groupby cannot be used with --weight.");
+    } else if let Some(path) = rconfig.path.clone() {
+        // we're loading the entire file into memory, we need to check avail mem
         util::mem_file_check(&path, false, args.flag_memcheck)?;
     }
 
-    let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+    let wconfig = Config::new(args.flag_output.as_ref());
+    if args.flag_weight.is_some() {
+        return args.write_weighted_frequencies(&mut wconfig.writer()?);
+    }
+    if args.flag_approx {
+        return args.write_approx_frequencies(&mut wconfig.writer()?);
+    }
+    if args.flag_groupby.is_some() {
+        return args.write_grouped_frequencies(&wconfig);
+    }
+
     let (headers, tables) = match args.rconfig().indexed()? {
         Some(ref mut idx) if util::njobs(args.flag_jobs) > 1 => args.parallel_ftables(idx),
         _ => args.sequential_ftables(),
     }?;
+    if args.flag_json {
+        return args.write_json_frequencies(&wconfig, &headers, tables);
+    }
+
+    let mut wtr = wconfig.writer()?;
 
     #[allow(unused_assignments)]
     let mut header_vec: Vec<u8> = Vec::with_capacity(tables.len());
     let mut itoa_buffer = itoa::Buffer::new();
-    let mut pct_decimal: Decimal;
-    let mut final_pct_decimal: Decimal;
     let mut pct_string: String;
-    let mut pct_scale: u32;
-    let mut current_scale: u32;
-    let abs_dec_places = args.flag_pct_dec_places.unsigned_abs() as u32;
     let mut row: Vec<&[u8]>;
     let mut all_unique_header: bool;
 
@@ -211,51 +322,18 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             header.to_vec()
         };
 
-        let mut sorted_counts: Vec<(Vec<u8>, u64, f64)>;
+        let sorted_counts: Vec<(Vec<u8>, u64, f64)>;
         all_unique_header = all_unique_headers.contains(&i);
 
         if all_unique_header {
             // if the column has all unique values, we don't need to sort the counts
             sorted_counts = vec![(all_unique_text.to_vec(), row_count, 100.0_f64)];
         } else {
-            sorted_counts = args.counts(&ftab);
-
-            // if not --other_sorted and the first value is "Other (", rotate it to the end
-            if !args.flag_other_sorted
-                && sorted_counts.first().is_some_and(|(value, _, _)| {
-                    value.starts_with(format!("{} (", args.flag_other_text).as_bytes())
-                })
-            {
-                sorted_counts.rotate_left(1);
-            }
+            sorted_counts = args.sorted_counts(&ftab);
         };
 
         for (value, count, percentage) in sorted_counts {
-            pct_decimal = Decimal::from_f64(percentage).unwrap_or_default();
-            pct_scale = if args.flag_pct_dec_places < 0 {
-                current_scale = pct_decimal.scale();
-                if current_scale > abs_dec_places {
-                    current_scale
-                } else {
-                    abs_dec_places
-                }
-            } else {
-                abs_dec_places
-            };
-            final_pct_decimal = pct_decimal
-                .round_dp_with_strategy(
-                    pct_scale,
-                    rust_decimal::RoundingStrategy::MidpointAwayFromZero,
-                )
-                .normalize();
-            pct_string = if final_pct_decimal.fract().to_string().len() > abs_dec_places as usize {
-                final_pct_decimal
-                    .round_dp_with_strategy(abs_dec_places, RoundingStrategy::MidpointAwayFromZero)
-                    .normalize()
-                    .to_string()
-            } else {
-                final_pct_decimal.to_string()
-            };
+            pct_string = args.format_pct(percentage);
             row = vec![
                 &*header_vec,
                 &*value,
@@ -271,6 +349,203 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 type Headers = csv::ByteRecord;
 type FTable = Frequencies<Vec<u8>>;
 type FTables = Vec<Frequencies<Vec<u8>>>;
+type WeightedFTables = Vec<WeightedFrequencies>;
+type ApproxFTables = Vec<SpaceSaving>;
+
+/// The frequency tables of each --groupby group, keyed by the values of the group columns,
+/// along with the number of rows of the group.
+#[derive(Clone, Default)]
+struct GroupedFTables(AHashMap<Vec<Vec<u8>>, (u64, FTables)>);
+
+impl Commute for GroupedFTables {
+    #[inline]
+    fn merge(&mut self, other: GroupedFTables) {
+        for (group, (rowcount, tables)) in other.0 {
+            match self.0.entry(group) {
+                Entry::Occupied(mut entry) => {
+                    let (self_rowcount, self_tables) = entry.get_mut();
+                    *self_rowcount += rowcount;
+                    self_tables.merge(tables);
+                },
+                Entry::Vacant(entry) => {
+                    entry.insert((rowcount, tables));
+                },
+            }
+        }
+    }
+}
+
+/// WeightedFrequencies sums the --weight values of each distinct value of a column.
+#[derive(Clone, Default)]
+struct WeightedFrequencies(AHashMap<Vec<u8>, f64>);
+
+impl Commute for WeightedFrequencies {
+    #[inline]
+    fn merge(&mut self, other: WeightedFrequencies) {
+        for (value, weight) in other.0 {
+            *self.0.entry(value).or_default() += weight;
+        }
+    }
+}
+
+/// A counter of a SpaceSaving summary. Its count overestimates the frequency
+/// of its value by at most its error.
+#[derive(Clone)]
+struct Counter {
+    value: Vec<u8>,
+    count: u64,
+    error: u64,
+}
+
+/// A Space-Saving summary (Metwally et al.) of the most frequent values of a column,
+/// with at most capacity counters. A value without a counter takes over the counter
+/// with the smallest count, inheriting its count as its error.
+#[derive(Clone)]
+struct SpaceSaving {
+    capacity:  usize,
+    total:     u64,
+    // a binary min-heap of the counters, ordered by count
+    counters:  Vec<Counter>,
+    // the position of the counter of each value in the heap
+    positions: AHashMap<Vec<u8>, usize>,
+}
+
+impl SpaceSaving {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            total: 0,
+            counters: Vec::with_capacity(capacity),
+            positions: AHashMap::with_capacity(capacity),
+        }
+    }
+
+    #[inline]
+    fn add(&mut self, value: Vec<u8>) {
+        self.total += 1;
+        if let Some(&pos) = self.positions.get(&value) {
+            self.counters[pos].count += 1;
+            self.sift_down(pos);
+        } else if self.counters.len() < self.capacity {
+            let pos = self.counters.len();
+            self.positions.insert(value.clone(), pos);
+            self.counters.push(Counter {
+                value,
+                count: 1,
+                error: 0,
+            });
+            self.sift_up(pos);
+        } else {
+            // evict the value with the smallest count
+            let min = &mut self.counters[0];
+            self.positions.remove(&min.value);
+            min.error = min.count;
+            min.count += 1;
+            min.value.clone_from(&value);
+            self.positions.insert(value, 0);
+            self.sift_down(0);
+        }
+    }
+
+    /// the most a value without a counter may have occurred
+    #[inline]
+    fn min_count(&self) -> u64 {
+        if self.counters.len() < self.capacity {
+            0
+        } else {
+            self.counters.first().map_or(0, |counter| counter.count)
+        }
+    }
+
+    #[inline]
+    fn swap(&mut self, a: usize, b: usize) {
+        self.counters.swap(a, b);
+        // safety: every counter's value has a position
+        *self.positions.get_mut(&self.counters[a].value).unwrap() = a;
+        *self.positions.get_mut(&self.counters[b].value).unwrap() = b;
+    }
+
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if self.counters[pos].count >= self.counters[parent].count {
+                break;
+            }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        let len = self.counters.len();
+        loop {
+            let left = 2 * pos + 1;
+            if left >= len {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < len && self.counters[right].count < self.counters[left].count {
+                right
+            } else {
+                left
+            };
+            if self.counters[pos].count <= self.counters[child].count {
+                break;
+            }
+            self.swap(pos, child);
+            pos = child;
+        }
+    }
+}
+
+impl Commute for SpaceSaving {
+    fn merge(&mut self, other: SpaceSaving) {
+        // a value missing from a full summary may have occurred up to its min count times,
+        // so it's added to the count & error of the values only in the other summary
+        let (self_min, other_min) = (self.min_count(), other.min_count());
+        let mut merged: AHashMap<Vec<u8>, (u64, u64)> = self
+            .counters
+            .drain(..)
+            .map(|c| (c.value, (c.count + other_min, c.error + other_min)))
+            .collect();
+        for Counter {
+            value,
+            count,
+            error,
+        } in other.counters
+        {
+            merged
+                .entry(value)
+                .and_modify(|(merged_count, merged_error)| {
+                    *merged_count = *merged_count - other_min + count;
+                    *merged_error = *merged_error - other_min + error;
+                })
+                .or_insert((count + self_min, error + self_min));
+        }
+
+        // keep the counters with the largest counts. Sorted by ascending count,
+        // the counters are also a valid min-heap
+        let mut counters: Vec<Counter> = merged
+            .into_iter()
+            .map(|(value, (count, error))| Counter {
+                value,
+                count,
+                error,
+            })
+            .collect();
+        counters.sort_unstable_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        counters.truncate(self.capacity);
+        counters.reverse();
+
+        self.positions = counters
+            .iter()
+            .enumerate()
+            .map(|(pos, c)| (c.value.clone(), pos))
+            .collect();
+        self.counters = counters;
+        self.total += other.total;
+    }
+}
 
 impl Args {
     pub fn rconfig(&self) -> Config {
@@ -280,6 +555,406 @@ impl Args {
             .select(self.flag_select.clone())
     }
 
+    /// formats a percentage, rounded to --pct-dec-places
+    fn format_pct(&self, percentage: f64) -> String {
+        let abs_dec_places = self.flag_pct_dec_places.unsigned_abs() as u32;
+        let pct_decimal = Decimal::from_f64(percentage).unwrap_or_default();
+        let pct_scale = if self.flag_pct_dec_places < 0 {
+            let current_scale = pct_decimal.scale();
+            if current_scale > abs_dec_places {
+                current_scale
+            } else {
+                abs_dec_places
+            }
+        } else {
+            abs_dec_places
+        };
+        let final_pct_decimal = pct_decimal
+            .round_dp_with_strategy(
+                pct_scale,
+                rust_decimal::RoundingStrategy::MidpointAwayFromZero,
+            )
+            .normalize();
+        if final_pct_decimal.fract().to_string().len() > abs_dec_places as usize {
+            final_pct_decimal
+                .round_dp_with_strategy(abs_dec_places, RoundingStrategy::MidpointAwayFromZero)
+                .normalize()
+                .to_string()
+        } else {
+            final_pct_decimal.to_string()
+        }
+    }
+
+    /// computes the --weight frequency tables, and writes them with the sum of the weights
+    /// of each value as its count
+    fn write_weighted_frequencies(
+        &self,
+        wtr: &mut csv::Writer<Box<dyn io::Write + 'static>>,
+    ) -> CliResult<()> {
+        let (headers, tables) = match self.rconfig().indexed()? {
+            Some(ref mut idx) if util::njobs(self.flag_jobs) > 1 => {
+                self.parallel_weighted_ftables(idx)
+            },
+            _ => self.sequential_weighted_ftables(),
+        }?;
+
+        wtr.write_record(vec!["field", "value", "count", "percentage"])?;
+        for (i, (header, ftab)) in headers.iter().zip(tables).enumerate() {
+            let header_vec = if self.flag_no_headers {
+                (i + 1).to_string().into_bytes()
+            } else {
+                header.to_vec()
+            };
+            for (value, weight, percentage) in self.weighted_counts(&ftab) {
+                wtr.write_record([
+                    &*header_vec,
+                    &*value,
+                    util::round_num(weight, WEIGHT_DEC_PLACES).as_bytes(),
+                    self.format_pct(percentage).as_bytes(),
+                ])?;
+            }
+        }
+        Ok(wtr.flush()?)
+    }
+
+    /// like counts, but sorts the values by their total weight, and applies --limit
+    /// to the total weights
+    fn weighted_counts(&self, ftab: &WeightedFrequencies) -> Vec<(ByteString, f64, f64)> {
+        let mut weights: Vec<(&Vec<u8>, f64)> = ftab.0.iter().map(|(v, w)| (v, *w)).collect();
+        let total_weight: f64 = weights.iter().map(|(_, w)| w).sum();
+        if self.flag_asc {
+            weights.sort_unstable_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+        } else {
+            weights.sort_unstable_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        }
+
+        // check if we need to apply limits
+        let unique_weights_len = weights.len();
+        if self.flag_lmt_threshold == 0 || self.flag_lmt_threshold >= unique_weights_len {
+            let abs_limit = self.flag_limit.unsigned_abs();
+            if self.flag_limit > 0 {
+                weights.truncate(abs_limit);
+            } else if self.flag_limit < 0 {
+                // only return values with a total weight >= absolute value of the negative limit
+                #[allow(clippy::cast_precision_loss)]
+                let weight_limit = abs_limit as f64;
+                weights.retain(|(_, weight)| *weight >= weight_limit);
+            }
+        }
+
+        let pct_factor = if total_weight > 0.0 {
+            100.0_f64 / total_weight
+        } else {
+            0.0_f64
+        };
+        let mut weight_sum = 0.0_f64;
+        let mut pct_sum = 0.0_f64;
+        let mut weights_final: Vec<(Vec<u8>, f64, f64)> = weights
+            .into_iter()
+            .map(|(value, weight)| {
+                let pct = weight * pct_factor;
+                weight_sum += weight;
+                pct_sum += pct;
+                if value.is_empty() {
+                    (NULL_VAL.to_vec(), weight, pct)
+                } else {
+                    (value.clone(), weight, pct)
+                }
+            })
+            .collect();
+
+        let other_unique_count = unique_weights_len - weights_final.len();
+        if other_unique_count > 0 && self.flag_other_text != "<NONE>" {
+            weights_final.push((
+                format!(
+                    "{} ({})",
+                    self.flag_other_text,
+                    HumanCount(other_unique_count as u64)
+                )
+                .as_bytes()
+                .to_vec(),
+                total_weight - weight_sum,
+                100.0_f64 - pct_sum,
+            ));
+            if self.flag_other_sorted {
+                if self.flag_asc {
+                    weights_final.sort_by(|a, b| a.1.total_cmp(&b.1));
+                } else {
+                    weights_final.sort_by(|a, b| b.1.total_cmp(&a.1));
+                }
+            }
+        }
+        weights_final
+    }
+
+    /// computes the --approx frequency tables, and writes the top values of each column
+    /// with the maximum overestimate of their counts
+    fn write_approx_frequencies(
+        &self,
+        wtr: &mut csv::Writer<Box<dyn io::Write + 'static>>,
+    ) -> CliResult<()> {
+        let (headers, tables) = match self.rconfig().indexed()? {
+            Some(ref mut idx) if util::njobs(self.flag_jobs) > 1 => {
+                self.parallel_approx_ftables(idx)
+            },
+            _ => self.sequential_approx_ftables(),
+        }?;
+
+        wtr.write_record(vec!["field", "value", "count", "percentage", "count_error"])?;
+        let mut itoa_buffer = itoa::Buffer::new();
+        let mut error_buffer = itoa::Buffer::new();
+        for (i, (header, summary)) in headers.iter().zip(tables).enumerate() {
+            let header_vec = if self.flag_no_headers {
+                (i + 1).to_string().into_bytes()
+            } else {
+                header.to_vec()
+            };
+            for (value, count, error, percentage) in self.approx_counts(summary) {
+                wtr.write_record([
+                    &*header_vec,
+                    &*value,
+                    itoa_buffer.format(count).as_bytes(),
+                    self.format_pct(percentage).as_bytes(),
+                    error_buffer.format(error).as_bytes(),
+                ])?;
+            }
+        }
+        Ok(wtr.flush()?)
+    }
+
+    /// the counters of a Space-Saving summary sorted by count, with --limit applied
+    fn approx_counts(&self, summary: SpaceSaving) -> Vec<(ByteString, u64, u64, f64)> {
+        let mut counters = summary.counters;
+        counters.sort_unstable_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+
+        // check if we need to apply limits
+        if self.flag_lmt_threshold == 0 || self.flag_lmt_threshold >= counters.len() {
+            let abs_limit = self.flag_limit.unsigned_abs();
+            if self.flag_limit > 0 {
+                counters.truncate(abs_limit);
+            } else if self.flag_limit < 0 {
+                // only return values with an estimated count >= absolute value of the
+                // negative limit
+                counters.retain(|counter| counter.count >= abs_limit as u64);
+            }
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let pct_factor = if summary.total > 0 {
+            100.0_f64 / summary.total as f64
+        } else {
+            0.0_f64
+        };
+        counters
+            .into_iter()
+            .map(|counter| {
+                #[allow(clippy::cast_precision_loss)]
+                let pct = counter.count as f64 * pct_factor;
+                let value = if counter.value.is_empty() {
+                    NULL_VAL.to_vec()
+                } else {
+                    counter.value
+                };
+                (value, counter.count, counter.error, pct)
+            })
+            .collect()
+    }
+
+    /// the counts of a frequency table, with the "Other" category moved to the end
+    /// unless --other-sorted is set
+    fn sorted_counts(&self, ftab: &FTable) -> Vec<(ByteString, u64, f64)> {
+        let mut sorted_counts = self.counts(ftab);
+
+        // if not --other_sorted and the first value is "Other (", rotate it to the end
+        if !self.flag_other_sorted
+            && sorted_counts.first().is_some_and(|(value, _, _)| {
+                value.starts_with(format!("{} (", self.flag_other_text).as_bytes())
+            })
+        {
+            sorted_counts.rotate_left(1);
+        }
+        sorted_counts
+    }
+
+    /// the --json object of a field, with its cardinality, null count & frequencies
+    fn field_json(
+        &self,
+        field: &[u8],
+        cardinality: u64,
+        nullcount: u64,
+        sorted_counts: Vec<(ByteString, u64, f64)>,
+    ) -> serde_json::Value {
+        let frequencies: Vec<serde_json::Value> = sorted_counts
+            .into_iter()
+            .map(|(value, count, percentage)| {
+                json!({
+                    "value": String::from_utf8_lossy(&value),
+                    "count": count,
+                    "percentage": self.format_pct(percentage).parse::<f64>().unwrap_or_default(),
+                })
+            })
+            .collect();
+        json!({
+            "field": String::from_utf8_lossy(field),
+            "cardinality": cardinality,
+            "nullcount": nullcount,
+            "frequencies": frequencies,
+        })
+    }
+
+    /// the --json objects of the fields of a set of frequency tables
+    fn fields_json(&self, headers: &Headers, tables: FTables) -> Vec<serde_json::Value> {
+        let null = Vec::new();
+        headers
+            .iter()
+            .zip(tables)
+            .enumerate()
+            .map(|(i, (header, ftab))| {
+                let header_vec = if self.flag_no_headers {
+                    (i + 1).to_string().into_bytes()
+                } else {
+                    header.to_vec()
+                };
+                self.field_json(
+                    &header_vec,
+                    ftab.cardinality(),
+                    ftab.count(&null),
+                    self.sorted_counts(&ftab),
+                )
+            })
+            .collect()
+    }
+
+    /// writes the frequency tables as a --json object
+    fn write_json_frequencies(
+        &self,
+        wconfig: &Config,
+        headers: &Headers,
+        tables: FTables,
+    ) -> CliResult<()> {
+        // safety: UNIQUE_COLUMNS is set by sel_headers when compiling the frequencies
+        let all_unique_headers = UNIQUE_COLUMNS.get().unwrap();
+        let row_count = *FREQ_ROW_COUNT.get().unwrap_or(&0);
+
+        let mut fields = self.fields_json(headers, tables);
+        for &i in all_unique_headers {
+            // columns with all unique values were short-circuited using the stats cache
+            if let Some(field) = fields.get_mut(i) {
+                field["cardinality"] = json!(row_count);
+                field["nullcount"] = json!(0);
+                field["frequencies"] = json!([{
+                    "value": self.flag_all_unique_text,
+                    "count": row_count,
+                    "percentage": 100.0,
+                }]);
+            }
+        }
+
+        let output = json!({
+            "fieldcount": fields.len(),
+            "fields": fields,
+        });
+        let mut wtr = wconfig.io_writer()?;
+        serde_json::to_writer_pretty(&mut wtr, &output)?;
+        writeln!(wtr)?;
+        Ok(wtr.flush()?)
+    }
+
+    /// computes the frequency tables of each --groupby group, and writes them as CSV with
+    /// the group columns prepended, or as a --json object
+    fn write_grouped_frequencies(&self, wconfig: &Config) -> CliResult<()> {
+        let (headers, grouped) = match self.rconfig().indexed()? {
+            Some(ref mut idx) if util::njobs(self.flag_jobs) > 1 => {
+                self.parallel_grouped_ftables(idx)
+            },
+            _ => self.sequential_grouped_ftables(),
+        }?;
+
+        // safety: GROUPBY_COLUMNS is set by sel_headers when --groupby is used
+        let group_sel = GROUPBY_COLUMNS.get().unwrap();
+        let all_headers = self.rconfig().reader()?.byte_headers()?.clone();
+        let group_headers: Vec<Vec<u8>> = group_sel
+            .iter()
+            .map(|&col| {
+                if self.flag_no_headers {
+                    (col + 1).to_string().into_bytes()
+                } else {
+                    all_headers[col].to_vec()
+                }
+            })
+            .collect();
+
+        let mut groups: Vec<(Vec<Vec<u8>>, (u64, FTables))> = grouped.0.into_iter().collect();
+        groups.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        if self.flag_json {
+            let groups_json: Vec<serde_json::Value> = groups
+                .into_iter()
+                .map(|(group, (rowcount, tables))| {
+                    let group_json: serde_json::Map<String, serde_json::Value> = group_headers
+                        .iter()
+                        .zip(group)
+                        .map(|(header, value)| {
+                            (
+                                String::from_utf8_lossy(header).into_owned(),
+                                json!(String::from_utf8_lossy(&value)),
+                            )
+                        })
+                        .collect();
+                    json!({
+                        "group": group_json,
+                        "rowcount": rowcount,
+                        "fields": self.fields_json(&headers, tables),
+                    })
+                })
+                .collect();
+            let group_names: Vec<std::borrow::Cow<str>> = group_headers
+                .iter()
+                .map(|header| String::from_utf8_lossy(header))
+                .collect();
+            let output = json!({
+                "groupby": group_names,
+                "groups": groups_json,
+            });
+            let mut wtr = wconfig.io_writer()?;
+            serde_json::to_writer_pretty(&mut wtr, &output)?;
+            writeln!(wtr)?;
+            return Ok(wtr.flush()?);
+        }
+
+        let mut wtr = wconfig.writer()?;
+        let mut header_row = group_headers;
+        header_row.extend(
+            ["field", "value", "count", "percentage"]
+                .iter()
+                .map(|h| h.as_bytes().to_vec()),
+        );
+        wtr.write_record(&header_row)?;
+
+        let mut itoa_buffer = itoa::Buffer::new();
+        for (group, (_, tables)) in groups {
+            for (i, (header, ftab)) in headers.iter().zip(tables).enumerate() {
+                let header_vec = if self.flag_no_headers {
+                    (i + 1).to_string().into_bytes()
+                } else {
+                    header.to_vec()
+                };
+                for (value, count, percentage) in self.sorted_counts(&ftab) {
+                    let pct_string = self.format_pct(percentage);
+                    let mut row: Vec<&[u8]> = group.iter().map(Vec::as_slice).collect();
+                    row.extend([
+                        &*header_vec,
+                        &*value,
+                        itoa_buffer.format(count).as_bytes(),
+                        pct_string.as_bytes(),
+                    ]);
+                    wtr.write_record(row)?;
+                }
+            }
+        }
+        Ok(wtr.flush()?)
+    }
+
     #[inline]
     fn counts(&self, ftab: &FTable) -> Vec<(ByteString, u64, f64)> {
         let (mut counts, total_count) = if self.flag_asc {
@@ -290,6 +965,12 @@ impl Args {
             ftab.par_frequent(false)
         };
 
+        // a table can be empty, e.g. for a --groupby group whose values are all NULLs
+        // with --no-nulls
+        if counts.is_empty() {
+            return Vec::new();
+        }
+
         // check if we need to apply limits
         let unique_counts_len = counts.len();
         if self.flag_lmt_threshold == 0 || self.flag_lmt_threshold >= unique_counts_len {
@@ -377,7 +1058,7 @@ impl Args {
 
     pub fn parallel_ftables(
         &self,
-        idx: &Indexed<fs::File, fs::File>,
+        idx: &Indexed<IndexedSource, fs::File>,
     ) -> CliResult<(Headers, FTables)> {
         let mut rdr = self.rconfig().reader()?;
         let (headers, sel) = self.sel_headers(&mut rdr)?;
@@ -407,6 +1088,239 @@ impl Args {
         Ok((headers, merge_all(recv.iter()).unwrap()))
     }
 
+    fn sequential_weighted_ftables(&self) -> CliResult<(Headers, WeightedFTables)> {
+        let mut rdr = self.rconfig().reader()?;
+        let (headers, sel) = self.sel_headers(&mut rdr)?;
+        Ok((headers, self.weighted_ftables(&sel, rdr.byte_records())))
+    }
+
+    fn parallel_weighted_ftables(
+        &self,
+        idx: &Indexed<IndexedSource, fs::File>,
+    ) -> CliResult<(Headers, WeightedFTables)> {
+        let mut rdr = self.rconfig().reader()?;
+        let (headers, sel) = self.sel_headers(&mut rdr)?;
+
+        let idx_count = idx.count() as usize;
+        if idx_count == 0 {
+            return Ok((headers, vec![]));
+        }
+
+        let njobs = util::njobs(self.flag_jobs);
+        let chunk_size = util::chunk_size(idx_count, njobs);
+        let nchunks = util::num_of_chunks(idx_count, chunk_size);
+
+        let pool = ThreadPool::new(njobs);
+        let (send, recv) = crossbeam_channel::bounded(0);
+        for i in 0..nchunks {
+            let (send, args, sel) = (send.clone(), self.clone(), sel.clone());
+            pool.execute(move || {
+                // safety: we know the file is indexed and seekable
+                let mut idx = args.rconfig().indexed().unwrap().unwrap();
+                idx.seek((i * chunk_size) as u64).unwrap();
+                let it = idx.byte_records().take(chunk_size);
+                send.send(args.weighted_ftables(&sel, it)).unwrap();
+            });
+        }
+        drop(send);
+        Ok((headers, merge_all(recv.iter()).unwrap()))
+    }
+
+    fn sequential_approx_ftables(&self) -> CliResult<(Headers, ApproxFTables)> {
+        let mut rdr = self.rconfig().reader()?;
+        let (headers, sel) = self.sel_headers(&mut rdr)?;
+        Ok((headers, self.approx_ftables(&sel, rdr.byte_records())))
+    }
+
+    fn parallel_approx_ftables(
+        &self,
+        idx: &Indexed<IndexedSource, fs::File>,
+    ) -> CliResult<(Headers, ApproxFTables)> {
+        let mut rdr = self.rconfig().reader()?;
+        let (headers, sel) = self.sel_headers(&mut rdr)?;
+
+        let idx_count = idx.count() as usize;
+        if idx_count == 0 {
+            return Ok((headers, vec![]));
+        }
+
+        let njobs = util::njobs(self.flag_jobs);
+        let chunk_size = util::chunk_size(idx_count, njobs);
+        let nchunks = util::num_of_chunks(idx_count, chunk_size);
+
+        let pool = ThreadPool::new(njobs);
+        let (send, recv) = crossbeam_channel::bounded(0);
+        for i in 0..nchunks {
+            let (send, args, sel) = (send.clone(), self.clone(), sel.clone());
+            pool.execute(move || {
+                // safety: we know the file is indexed and seekable
+                let mut idx = args.rconfig().indexed().unwrap().unwrap();
+                idx.seek((i * chunk_size) as u64).unwrap();
+                let it = idx.byte_records().take(chunk_size);
+                send.send(args.approx_ftables(&sel, it)).unwrap();
+            });
+        }
+        drop(send);
+        Ok((headers, merge_all(recv.iter()).unwrap()))
+    }
+
+    fn sequential_grouped_ftables(&self) -> CliResult<(Headers, GroupedFTables)> {
+        let mut rdr = self.rconfig().reader()?;
+        let (headers, sel) = self.sel_headers(&mut rdr)?;
+        Ok((headers, self.grouped_ftables(&sel, rdr.byte_records())))
+    }
+
+    fn parallel_grouped_ftables(
+        &self,
+        idx: &Indexed<IndexedSource, fs::File>,
+    ) -> CliResult<(Headers, GroupedFTables)> {
+        let mut rdr = self.rconfig().reader()?;
+        let (headers, sel) = self.sel_headers(&mut rdr)?;
+
+        let idx_count = idx.count() as usize;
+        if idx_count == 0 {
+            return Ok((headers, GroupedFTables::default()));
+        }
+
+        let njobs = util::njobs(self.flag_jobs);
+        let chunk_size = util::chunk_size(idx_count, njobs);
+        let nchunks = util::num_of_chunks(idx_count, chunk_size);
+
+        let pool = ThreadPool::new(njobs);
+        let (send, recv) = crossbeam_channel::bounded(0);
+        for i in 0..nchunks {
+            let (send, args, sel) = (send.clone(), self.clone(), sel.clone());
+            pool.execute(move || {
+                // safety: we know the file is indexed and seekable
+                let mut idx = args.rconfig().indexed().unwrap().unwrap();
+                idx.seek((i * chunk_size) as u64).unwrap();
+                let it = idx.byte_records().take(chunk_size);
+                send.send(args.grouped_ftables(&sel, it)).unwrap();
+            });
+        }
+        drop(send);
+        Ok((headers, merge_all(recv.iter()).unwrap()))
+    }
+
+    #[inline]
+    fn grouped_ftables<I>(&self, sel: &Selection, it: I) -> GroupedFTables
+    where
+        I: Iterator<Item = csv::Result<csv::ByteRecord>>,
+    {
+        let nsel = sel.normal();
+        let nsel_len = nsel.len();
+        // safety: GROUPBY_COLUMNS is set by sel_headers when --groupby is used
+        let group_sel = GROUPBY_COLUMNS.get().unwrap();
+        let mut grouped = GroupedFTables::default();
+
+        let mut group_key: Vec<Vec<u8>> = Vec::with_capacity(group_sel.len());
+        let mut buf = String::new();
+        for row in it {
+            // safety: we know the row is not empty
+            let row = row.unwrap();
+            group_key.clear();
+            group_key.extend(group_sel.select(&row).map(<[u8]>::to_vec));
+
+            if !grouped.0.contains_key(&group_key) {
+                grouped.0.insert(
+                    group_key.clone(),
+                    (0, (0..nsel_len).map(|_| Frequencies::new()).collect()),
+                );
+            }
+            // safety: we just inserted the group if it didn't exist
+            let (rowcount, freq_tables) = grouped.0.get_mut(&group_key).unwrap();
+            *rowcount += 1;
+            for (ftab, field) in freq_tables.iter_mut().zip(nsel.select(row.iter())) {
+                if let Some(value) = self.frequency_value(field, &mut buf) {
+                    ftab.add(value);
+                }
+            }
+        }
+        grouped
+    }
+
+    #[inline]
+    fn approx_ftables<I>(&self, sel: &Selection, it: I) -> ApproxFTables
+    where
+        I: Iterator<Item = csv::Result<csv::ByteRecord>>,
+    {
+        let nsel = sel.normal();
+        // track at least --limit values, so the top --limit values can be listed
+        let capacity = self
+            .flag_approx_capacity
+            .max(self.flag_limit.max(0) as usize);
+        let mut freq_tables: ApproxFTables = (0..nsel.len())
+            .map(|_| SpaceSaving::new(capacity))
+            .collect();
+
+        let mut buf = String::new();
+        for row in it {
+            // safety: we know the row is not empty
+            let row = row.unwrap();
+            for (summary, field) in freq_tables.iter_mut().zip(nsel.select(row.iter())) {
+                if let Some(value) = self.frequency_value(field, &mut buf) {
+                    summary.add(value);
+                }
+            }
+        }
+        freq_tables
+    }
+
+    /// returns the value a field is counted as - trimmed unless --no-trim is set, and
+    /// lowercased with --ignore-case. Returns None for NULLs with --no-nulls.
+    #[inline]
+    fn frequency_value(&self, field: &[u8], buf: &mut String) -> Option<Vec<u8>> {
+        if field.is_empty() {
+            return if self.flag_no_nulls {
+                None
+            } else {
+                Some(Vec::new())
+            };
+        }
+        Some(if let Ok(s) = simdutf8::basic::from_utf8(field) {
+            let s = if self.flag_no_trim { s } else { s.trim() };
+            if self.flag_ignore_case {
+                util::to_lowercase_into(s, buf);
+                buf.as_bytes().to_vec()
+            } else {
+                s.as_bytes().to_vec()
+            }
+        } else if self.flag_no_trim {
+            field.to_vec()
+        } else {
+            util::trim_bs_whitespace(field).to_vec()
+        })
+    }
+
+    #[inline]
+    fn weighted_ftables<I>(&self, sel: &Selection, it: I) -> WeightedFTables
+    where
+        I: Iterator<Item = csv::Result<csv::ByteRecord>>,
+    {
+        let nsel = sel.normal();
+        let mut freq_tables: WeightedFTables = (0..nsel.len())
+            .map(|_| WeightedFrequencies::default())
+            .collect();
+
+        // safety: WEIGHT_COLUMN is set by sel_headers when --weight is used
+        let weight_column = *WEIGHT_COLUMN.get().unwrap();
+        let mut buf = String::new();
+        for row in it {
+            // safety: we know the row is not empty
+            let row = row.unwrap();
+            let weight = row.get(weight_column).map_or(0.0, util::parse_weight);
+            if weight <= 0.0 {
+                continue;
+            }
+            for (ftab, field) in freq_tables.iter_mut().zip(nsel.select(row.iter())) {
+                if let Some(value) = self.frequency_value(field, &mut buf) {
+                    *ftab.0.entry(value).or_default() += weight;
+                }
+            }
+        }
+        freq_tables
+    }
+
     #[inline]
     fn ftables<I>(&self, sel: &Selection, it: I) -> FTables
     where
@@ -567,6 +1481,15 @@ impl Args {
     /// return the names of headers/columns that are unique identifiers
     /// (i.e. where cardinality == rowcount)
     fn get_unique_headers(&self, headers: &Headers) -> CliResult<Vec<usize>> {
+        // with --weight, the frequency of a unique value is its weight, not 1,
+        // so columns with all unique values can't be short-circuited.
+        // --approx doesn't need to, as its memory use is bounded
+        // with --groupby, a column may have all unique values within a group even
+        // if it doesn't across the whole CSV, so the stats cache can't be used either
+        if self.flag_weight.is_some() || self.flag_approx || self.flag_groupby.is_some() {
+            return Ok(Vec::new());
+        }
+
         // get the stats records for the entire CSV
         let schema_args = util::SchemaArgs {
             flag_enum_threshold:  0,
@@ -615,11 +1538,18 @@ impl Args {
                 // get the column name and stats record
                 // safety: we know that csv_fields and csv_stats have the same length
                 let col_name = csv_fields.get(i).unwrap();
+                // an estimated cardinality (from stats --approx-cardinality) can't tell
+                // if ALL the values are unique, so we only use exact cardinalities
+                let cardinality = if stats_record.cardinality_error.unwrap_or_default() > 0.0 {
+                    0
+                } else {
+                    stats_record.cardinality
+                };
                 (
                     simdutf8::basic::from_utf8(col_name)
                         .unwrap_or(NON_UTF8_ERR)
                         .to_string(),
-                    stats_record.cardinality,
+                    cardinality,
                 )
             })
             .collect();
@@ -651,7 +1581,32 @@ impl Args {
             .set(all_unique_headers_vec)
             .map_err(|_| "Cannot set UNIQUE_COLUMNS")?;
 
-        let sel = self.rconfig().selection(headers)?;
+        let mut sel = self.rconfig().selection(headers)?;
+        if let Some(ref weight) = self.flag_weight {
+            // the weight column is excluded from the frequency tables
+            let weight_sel = weight.selection(headers, !self.flag_no_headers)?;
+            if weight_sel.len() != 1 {
+                return fail_incorrectusage_clierror!("--weight must select exactly one column.");
+            }
+            WEIGHT_COLUMN
+                .set(weight_sel[0])
+                .map_err(|_| "Cannot set WEIGHT_COLUMN")?;
+            sel = sel.without(&weight_sel);
+        }
+        if let Some(ref groupby) = self.flag_groupby {
+            // the group columns are excluded from the frequency tables
+            let group_sel = groupby.selection(headers, !self.flag_no_headers)?;
+            sel = sel.without(&group_sel);
+            if sel.is_empty() {
+                return fail_incorrectusage_clierror!(
+                    "No columns left to compute frequencies for after excluding the --groupby \
+                     columns."
+                );
+            }
+            GROUPBY_COLUMNS
+                .set(group_sel)
+                .map_err(|_| "Cannot set GROUPBY_COLUMNS")?;
+        }
         Ok((sel.select(headers).map(<[u8]>::to_vec).collect(), sel))
     }
 }
@@ -0,0 +1,305 @@
+static USAGE: &str = r#"
+Joins two sets of CSV data on the specified columns by fuzzy matching, e.g. to link
+records of the same company or person across data sets that spell them differently.
+
+For every row of the first CSV, the rows of the second CSV whose join columns have a
+similarity >= --threshold are matched. The similarity of two rows is the mean of the
+similarities of their join columns (after trimming whitespace, and lowercasing with
+--ignore-case), between 0.0 & 1.0. The metrics are:
+
+  jarowinkler (jw):  Jaro-Winkler similarity
+  levenshtein (lev): Normalized Levenshtein similarity
+  damerau (dl):      Normalized Damerau-Levenshtein similarity
+  tokenset (ts):     Token set similarity - the normalized Levenshtein similarity of
+                     the sorted words the values have in common, with and without
+                     their other sorted words, so word order and extra words matter
+                     less (e.g. "Acme Corp" & "Corp Acme Inc")
+
+Only the --top-k best matches of each row are kept. Each joined row has two additional
+columns: "match_score", the similarity of the rows, and "match_rank", the rank of the
+match for the row of the first CSV, starting at 1 for the best match.
+Rows whose join columns are all empty are never matched.
+
+The second CSV is loaded into memory. Comparing every row of the first CSV with every
+row of the second CSV is quadratic, so use --blocking to only compare rows with the same
+values in the blocking columns (e.g. the same postal code or country).
+
+Examples:
+
+  # match vendors by name, keeping the best match with a similarity of at least 0.9
+  $ qsv fuzzyjoin name vendors.csv company_name registry.csv --threshold 0.9
+
+  # match by name & address within the same postal code, keeping the 3 best matches
+  $ qsv fuzzyjoin name,address vendors.csv name,street registry.csv \
+      --metric tokenset --blocking zip --top-k 3 --ignore-case
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_fuzzyjoin.rs.
+
+Usage:
+    qsv fuzzyjoin [options] <columns1> <input1> <columns2> <input2>
+    qsv fuzzyjoin --help
+
+input arguments:
+    <input1>                is the first CSV data set to join.
+    <input2>                is the second CSV data set to join.
+    <columns1> & <columns2> are the columns to join on for each input.
+
+    The columns arguments specify the columns to join for each input. Columns can
+    be referenced by name or index, starting at 1. Specify multiple columns by
+    separating them with a comma. Specify a range of columns with `-`. Both
+    columns1 and columns2 must specify exactly the same number of columns.
+    (See 'qsv select --help' for the full syntax.)
+
+fuzzyjoin options:
+    -m, --metric <arg>     The similarity metric: jarowinkler (jw), levenshtein (lev),
+                           damerau (dl) or tokenset (ts). [default: jw]
+    -t, --threshold <arg>  The minimum similarity of matching rows, between 0.0 & 1.0.
+                           [default: 0.85]
+    -b, --blocking <arg>   Only match rows that have exactly the same values in these
+                           columns, which must have the same names in both CSVs
+                           (or indices with --no-headers).
+                           Values are trimmed, and lowercased with --ignore-case.
+    -k, --top-k <arg>      The maximum number of matches to keep for each row of the
+                           first CSV. [default: 1]
+    --left                 Do a 'left outer' join. This also returns the rows of
+                           the first CSV that have no match, padded out with empty
+                           fields.
+    -i, --ignore-case      Compare values case insensitively.
+    -j, --jobs <arg>       The number of jobs to run in parallel.
+                           When not set, the number of jobs is set to the number
+                           of CPUs detected.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row will not be interpreted
+                           as headers. (i.e., They are not searched, analyzed,
+                           sliced, etc.)
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character. (default: ,)
+"#;
+
+use std::iter::repeat;
+
+use ahash::AHashMap;
+use csv::ByteRecord;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Deserialize;
+
+use crate::{
+    cmd::dedup::{fuzzy_values, row_similarity, FuzzyMetric},
+    config::{Config, Delimiter},
+    select::{SelectColumns, Selection},
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_columns1:     SelectColumns,
+    arg_input1:       String,
+    arg_columns2:     SelectColumns,
+    arg_input2:       String,
+    flag_metric:      String,
+    flag_threshold:   f64,
+    flag_blocking:    Option<SelectColumns>,
+    flag_top_k:       usize,
+    flag_left:        bool,
+    flag_ignore_case: bool,
+    flag_jobs:        Option<usize>,
+    flag_output:      Option<String>,
+    flag_no_headers:  bool,
+    flag_delimiter:   Option<Delimiter>,
+}
+
+// the number of rows of the first CSV that are matched in parallel at a time
+const BATCH_SIZE: usize = 10_000;
+
+/// the rows of the second CSV, with their join values, by blocking key
+struct MatchIndex {
+    rows:   Vec<ByteRecord>,
+    values: Vec<Vec<String>>,
+    blocks: AHashMap<Vec<String>, Vec<usize>>,
+}
+
+fn blocking_key(
+    record: &ByteRecord,
+    block_sel: Option<&Selection>,
+    ignore_case: bool,
+) -> Vec<String> {
+    match block_sel {
+        Some(block_sel) => fuzzy_values(record, block_sel, ignore_case).collect(),
+        None => Vec::new(),
+    }
+}
+
+impl MatchIndex {
+    /// the top-k rows matching the join values, with their similarity, best match first
+    fn matches(
+        &self,
+        metric: FuzzyMetric,
+        threshold: f64,
+        top_k: usize,
+        values: &[String],
+        block_key: &[String],
+    ) -> Vec<(usize, f64)> {
+        if values.iter().all(String::is_empty) {
+            return Vec::new();
+        }
+        let Some(candidates) = self.blocks.get(block_key) else {
+            return Vec::new();
+        };
+        let mut matches: Vec<(usize, f64)> = candidates
+            .iter()
+            .filter_map(|&i| {
+                let similarity = row_similarity(metric, values, &self.values[i]);
+                (similarity >= threshold).then_some((i, similarity))
+            })
+            .collect();
+        // best match first, in the order of the second CSV for ties
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        matches.truncate(top_k);
+        matches
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let Some(metric) = FuzzyMetric::from_name(&args.flag_metric) else {
+        return fail_incorrectusage_clierror!(
+            "Unknown --metric \"{}\". Valid metrics are jarowinkler (jw), levenshtein (lev), \
+             damerau (dl) and tokenset (ts).",
+            args.flag_metric
+        );
+    };
+    if !(0.0..=1.0).contains(&args.flag_threshold) {
+        return fail_incorrectusage_clierror!(
+            "--threshold must be a number between 0.0 and 1.0, got {}.",
+            args.flag_threshold
+        );
+    }
+    if args.flag_top_k == 0 {
+        return fail_incorrectusage_clierror!("--top-k must be at least 1.");
+    }
+
+    let rconf1 = Config::new(Some(&args.arg_input1))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+    let rconf2 = Config::new(Some(&args.arg_input2))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+    if rconf1.is_stdin() && rconf2.is_stdin() {
+        return fail_incorrectusage_clierror!("Only one of the inputs can be stdin.");
+    }
+
+    let mut rdr1 = rconf1.reader()?;
+    let mut rdr2 = rconf2.reader()?;
+    let headers1 = rdr1.byte_headers()?.clone();
+    let headers2 = rdr2.byte_headers()?.clone();
+
+    let sel1 = args
+        .arg_columns1
+        .selection(&headers1, !args.flag_no_headers)?;
+    let sel2 = args
+        .arg_columns2
+        .selection(&headers2, !args.flag_no_headers)?;
+    if sel1.len() != sel2.len() {
+        return fail_incorrectusage_clierror!(
+            "Column selections must have the same number of columns, but found column selections \
+             with {} and {} columns.",
+            sel1.len(),
+            sel2.len()
+        );
+    }
+    let (block_sel1, block_sel2) = match args.flag_blocking {
+        Some(ref blocking) => (
+            Some(blocking.selection(&headers1, !args.flag_no_headers)?),
+            Some(blocking.selection(&headers2, !args.flag_no_headers)?),
+        ),
+        None => (None, None),
+    };
+
+    // load the second CSV into memory
+    let mut index = MatchIndex {
+        rows:   Vec::new(),
+        values: Vec::new(),
+        blocks: AHashMap::new(),
+    };
+    for record in rdr2.byte_records() {
+        let record = record?;
+        index
+            .blocks
+            .entry(blocking_key(
+                &record,
+                block_sel2.as_ref(),
+                args.flag_ignore_case,
+            ))
+            .or_default()
+            .push(index.rows.len());
+        index
+            .values
+            .push(fuzzy_values(&record, &sel2, args.flag_ignore_case).collect());
+        index.rows.push(record);
+    }
+
+    let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+    if !args.flag_no_headers {
+        let mut headers = headers1.clone();
+        headers.extend(headers2.iter());
+        headers.push_field(b"match_score");
+        headers.push_field(b"match_rank");
+        wtr.write_byte_record(&headers)?;
+    }
+    let pad2: ByteRecord = repeat(b"").take(headers2.len() + 2).collect();
+
+    util::njobs(args.flag_jobs);
+
+    let mut batch: Vec<ByteRecord> = Vec::with_capacity(BATCH_SIZE);
+    let mut records = rdr1.byte_records();
+    let mut itoa_buffer = itoa::Buffer::new();
+    loop {
+        batch.clear();
+        for record in records.by_ref().take(BATCH_SIZE) {
+            batch.push(record?);
+        }
+        if batch.is_empty() {
+            break;
+        }
+
+        let batch_matches: Vec<Vec<(usize, f64)>> = batch
+            .par_iter()
+            .map(|record| {
+                let values: Vec<String> =
+                    fuzzy_values(record, &sel1, args.flag_ignore_case).collect();
+                let block_key = blocking_key(record, block_sel1.as_ref(), args.flag_ignore_case);
+                index.matches(
+                    metric,
+                    args.flag_threshold,
+                    args.flag_top_k,
+                    &values,
+                    &block_key,
+                )
+            })
+            .collect();
+
+        for (record, matches) in batch.iter().zip(batch_matches) {
+            if matches.is_empty() {
+                if args.flag_left {
+                    wtr.write_record(record.iter().chain(&pad2))?;
+                }
+                continue;
+            }
+            for (rank, (i, similarity)) in matches.into_iter().enumerate() {
+                let score = util::round_num(similarity, 4);
+                wtr.write_record(
+                    record
+                        .iter()
+                        .chain(&index.rows[i])
+                        .chain([score.as_bytes(), itoa_buffer.format(rank + 1).as_bytes()]),
+                )?;
+            }
+        }
+    }
+
+    Ok(wtr.flush()?)
+}
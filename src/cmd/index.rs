@@ -12,6 +12,14 @@ However, if the environment variable QSV_AUTOINDEX is set, qsv will automaticall
 create an index when none is detected, and stale indices will be automatically
 updated as well.
 
+Block-compressed files - BGZF (e.g. created with `bgzip`) or other multi-member
+gzip files (.gz), and multi-frame/seekable zstd files (.zst) - can also be indexed,
+as long as each block decompresses to less than 64 KiB. Their index stores the
+compressed offset of the block where each record starts and the record's offset
+within the decompressed block, so commands like `count`, `slice`, `sample` & `luau`'s
+random access mode only need to decompress the blocks they actually read.
+Other compressed files (.sz, .bz2, .xz) cannot be indexed.
+
 Usage:
     qsv index [options] <input>
     qsv index --help
@@ -35,7 +43,8 @@ use csv_index::RandomAccessSimple;
 use serde::Deserialize;
 
 use crate::{
-    config::{Config, DEFAULT_WTR_BUFFER_CAPACITY},
+    config::{Compression, Config, DEFAULT_WTR_BUFFER_CAPACITY},
+    index::create_block_index,
     util, CliResult,
 };
 
@@ -48,8 +57,14 @@ struct Args {
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
-    if args.arg_input.to_lowercase().ends_with(".sz") {
-        return fail_incorrectusage_clierror!("Cannot index a snappy file.");
+    let compression = Compression::from_path(Path::new(&args.arg_input));
+    if compression.is_compressed() && !compression.is_block_seekable() {
+        return fail_incorrectusage_clierror!(
+            "Cannot index a {}-compressed file. Decompress it first (e.g. `qsv input {} -o \
+             <file>.csv`), then index the decompressed file.",
+            compression.extension(),
+            args.arg_input
+        );
     }
 
     let pidx = match args.flag_output {
@@ -57,11 +72,22 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         Some(p) => PathBuf::from(&p),
     };
 
-    let rconfig = Config::new(Some(args.arg_input).as_ref());
-    let mut rdr = rconfig.reader_file()?;
+    let rconfig = Config::new(Some(&args.arg_input));
     let mut wtr =
-        io::BufWriter::with_capacity(DEFAULT_WTR_BUFFER_CAPACITY, fs::File::create(pidx)?);
-    RandomAccessSimple::create(&mut rdr, &mut wtr)?;
+        io::BufWriter::with_capacity(DEFAULT_WTR_BUFFER_CAPACITY, fs::File::create(&pidx)?);
+    if compression.is_compressed() {
+        if let Err(e) =
+            create_block_index(&rconfig, Path::new(&args.arg_input), compression, &mut wtr)
+        {
+            // don't leave a partial index behind
+            drop(wtr);
+            fs::remove_file(&pidx)?;
+            return fail_clierror!("Cannot index {}: {e}", args.arg_input);
+        }
+    } else {
+        let mut rdr = rconfig.reader_file()?;
+        RandomAccessSimple::create(&mut rdr, &mut wtr)?;
+    }
     io::Write::flush(&mut wtr)?;
 
     Ok(())
@@ -73,12 +73,58 @@ join options:
                            is found.
                            Cross joins do not write keys.
 
+                           DISK-BACKED JOIN OPTIONS:
+    --spill                Do a disk-backed (Grace) hash join, for inputs that are
+                           larger than memory. Both inputs are partitioned by the
+                           hash of their join keys into temporary files, and then
+                           joined partition by partition, so only one partition
+                           of the second CSV is loaded into memory at a time.
+                           Note that the joined rows are written in partition order,
+                           not in the order of the inputs.
+                           Cannot be used with --cross.
+    --spill-partitions <n> The number of partitions of each input with --spill.
+                           Increase it if a partition of the second CSV does not
+                           fit into memory. [default: 64]
+    --tmp-dir <arg>        The directory to use for the --spill partitions.
+                           [default: ./]
+
+                           RANGE JOIN OPTIONS:
+    --range                Do a range (interval) join. <columns1> is a single column
+                           of values, and <columns2> the start & end columns of the
+                           ranges in the second CSV, e.g.
+                             qsv join --range ip logs.csv start_ip,end_ip blocks.csv
+                           Each row of the first CSV is joined with the rows of the
+                           second CSV whose range contains its value, that is
+                           start <= value < end. Ranges may overlap.
+                           The ranges are loaded into a sorted interval index, while
+                           the first CSV is streamed.
+                           Only inner, --left, --left-anti, --left-semi, --right and
+                           --full joins are supported.
+                           Values of the first CSV that are empty or not a valid
+                           --range-type value never match. Rows of the second CSV
+                           with an empty start or end are never matched, and other
+                           invalid ranges are an error.
+    --range-type <arg>     The type of the --range values: number, date (with
+                           QSV_PREFER_DMY for day/month/year dates) or ip
+                           (IPv4 & IPv6 addresses). [default: number]
+    --range-closed         Also match values equal to the end of a range, that is
+                           start <= value <= end, e.g. for IP ranges that end
+                           with the last address of the block.
+
                            JOIN KEY TRANSFORMATION OPTIONS:
                            Note that transformations are applied to TEMPORARY
                            join key columns. The original columns are not modified
                            and the TEMPORARY columns are removed after the join.
 -i, --ignore-case           When set, joins are done case insensitively.
 -z, --ignore-leading-zeros  When set, leading zeros are ignored in join keys.
+    --key-transform <ops>  Normalize the join keys with a comma-delimited series of
+                           `qsv apply operations`, applied in order before matching
+                           (after trimming, and before --ignore-case &
+                           --ignore-leading-zeros). The original values are written
+                           to the output. Valid operations are trim, ltrim, rtrim,
+                           ltrim0, lower, upper, squeeze, squeeze0, strip_punct
+                           and unaccent. (e.g. "lower,strip_punct,squeeze,trim")
+                           Requires the apply feature.
 
 Common options:
     -h, --help             Display this message
@@ -90,10 +136,20 @@ Common options:
                            Must be a single character. (default: ,)
 "#;
 
-use std::{collections::hash_map::Entry, fmt, io, iter::repeat, mem::swap, str};
+use std::{
+    cmp::Ordering,
+    collections::hash_map::Entry,
+    fmt, fs,
+    io::{self, Seek},
+    iter::repeat,
+    mem::swap,
+    net::IpAddr,
+    path, str,
+};
 
 use ahash::AHashMap;
 use byteorder::{BigEndian, WriteBytesExt};
+use qsv_dateparser::parse_with_preference;
 use serde::Deserialize;
 
 use crate::{
@@ -101,7 +157,7 @@ use crate::{
     index::Indexed,
     select::{SelectColumns, Selection},
     util,
-    util::ByteString,
+    util::{ByteString, KeyTransform},
     CliResult,
 };
 
@@ -126,11 +182,105 @@ struct Args {
     flag_keys_output:          Option<String>,
     flag_ignore_case:          bool,
     flag_ignore_leading_zeros: bool,
+    flag_key_transform:        Option<String>,
+    flag_spill:                bool,
+    flag_spill_partitions:     usize,
+    flag_tmp_dir:              Option<String>,
+    flag_range:                bool,
+    flag_range_type:           String,
+    flag_range_closed:         bool,
+}
+
+/// The joins --spill & --range can do. For --spill, right anti & semi joins are
+/// left anti & semi joins with the inputs swapped.
+#[derive(Clone, Copy)]
+enum JoinKind {
+    Inner,
+    Left,
+    Right,
+    Full,
+    LeftAnti,
+    LeftSemi,
+}
+
+struct SpillConfig {
+    partitions: usize,
+    tmp_dir:    String,
+}
+
+#[derive(Clone, Copy)]
+enum RangeType {
+    Number,
+    Date,
+    Ip,
+}
+
+/// A --range join value. All the values of a join have the same --range-type.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum RangeValue {
+    Number(f64),
+    // milliseconds since the Unix epoch
+    Date(i64),
+    // IPv4 addresses are IPv4-mapped IPv6 addresses, so both can be compared
+    Ip(u128),
+}
+
+struct RangeConfig {
+    join:       JoinKind,
+    range_type: RangeType,
+    closed:     bool,
+    prefer_dmy: bool,
+}
+
+impl RangeConfig {
+    /// Parses a join value, returning None if it is empty or not a valid --range-type value.
+    fn parse(&self, value: &[u8]) -> Option<RangeValue> {
+        let value = util::trim_bs_whitespace(value);
+        if value.is_empty() {
+            return None;
+        }
+        match self.range_type {
+            RangeType::Number => fast_float2::parse::<f64, &[u8]>(value)
+                .ok()
+                .filter(|n| !n.is_nan())
+                .map(RangeValue::Number),
+            RangeType::Date => {
+                let value = simdutf8::basic::from_utf8(value).ok()?;
+                parse_with_preference(value, self.prefer_dmy)
+                    .ok()
+                    .map(|datetime| RangeValue::Date(datetime.timestamp_millis()))
+            },
+            RangeType::Ip => {
+                let value = simdutf8::basic::from_utf8(value).ok()?;
+                let ip = match value.parse::<IpAddr>().ok()? {
+                    IpAddr::V4(ipv4) => ipv4.to_ipv6_mapped(),
+                    IpAddr::V6(ipv6) => ipv6,
+                };
+                Some(RangeValue::Ip(u128::from(ip)))
+            },
+        }
+    }
+
+    /// Whether a range with this end contains a value at or after its start.
+    fn ends_after(&self, end: RangeValue, value: RangeValue) -> bool {
+        end > value || (self.closed && end == value)
+    }
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
+    let range = args.range_config()?;
+    let spill = args.spill_config()?;
     let mut state = args.new_io_state()?;
+
+    if let Some(range) = range {
+        if matches!(range.join, JoinKind::LeftAnti | JoinKind::LeftSemi) {
+            state.write_headers1()?;
+        } else {
+            state.write_headers()?;
+        }
+        return state.range_join(&range);
+    }
     match (
         args.flag_left,
         args.flag_left_anti,
@@ -144,27 +294,42 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         // default inner join
         (false, false, false, false, false, false, false, false) => {
             state.write_headers()?;
-            state.inner_join()
+            match spill {
+                Some(spill) => state.spill_join(JoinKind::Inner, &spill),
+                None => state.inner_join(),
+            }
         },
         // left join
         (true, false, false, false, false, false, false, false) => {
             state.write_headers()?;
-            state.outer_join(false)
+            match spill {
+                Some(spill) => state.spill_join(JoinKind::Left, &spill),
+                None => state.outer_join(false),
+            }
         },
         // left anti join
         (false, true, false, false, false, false, false, false) => {
             state.write_headers1()?;
-            state.left_join(true)
+            match spill {
+                Some(spill) => state.spill_join(JoinKind::LeftAnti, &spill),
+                None => state.left_join(true),
+            }
         },
         // left semi join
         (false, false, true, false, false, false, false, false) => {
             state.write_headers1()?;
-            state.left_join(false)
+            match spill {
+                Some(spill) => state.spill_join(JoinKind::LeftSemi, &spill),
+                None => state.left_join(false),
+            }
         },
         // right join
         (false, false, false, true, false, false, false, false) => {
             state.write_headers()?;
-            state.outer_join(true)
+            match spill {
+                Some(spill) => state.spill_join(JoinKind::Right, &spill),
+                None => state.outer_join(true),
+            }
         },
         // right anti join
         // swap left and right data sets and run left anti join
@@ -173,7 +338,10 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             swap(&mut swapped_join.rdr1, &mut swapped_join.rdr2);
             swap(&mut swapped_join.sel1, &mut swapped_join.sel2);
             swapped_join.write_headers1()?;
-            swapped_join.left_join(true)
+            match spill {
+                Some(spill) => swapped_join.spill_join(JoinKind::LeftAnti, &spill),
+                None => swapped_join.left_join(true),
+            }
         },
         // right semi join
         // swap left and right data sets and run left semi join
@@ -182,12 +350,18 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             swap(&mut swapped_join.rdr1, &mut swapped_join.rdr2);
             swap(&mut swapped_join.sel1, &mut swapped_join.sel2);
             swapped_join.write_headers1()?;
-            swapped_join.left_join(false)
+            match spill {
+                Some(spill) => swapped_join.spill_join(JoinKind::LeftSemi, &spill),
+                None => swapped_join.left_join(false),
+            }
         },
         // full outer join
         (false, false, false, false, false, false, true, false) => {
             state.write_headers()?;
-            state.full_outer_join()
+            match spill {
+                Some(spill) => state.spill_join(JoinKind::Full, &spill),
+                None => state.full_outer_join(),
+            }
         },
         // cross join
         (false, false, false, false, false, false, false, true) => {
@@ -199,16 +373,17 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 }
 
 struct IoState<R, W: io::Write> {
-    wtr:        csv::Writer<W>,
-    rdr1:       csv::Reader<R>,
-    sel1:       Selection,
-    rdr2:       csv::Reader<R>,
-    sel2:       Selection,
-    no_headers: bool,
-    casei:      bool,
-    zerosi:     bool,
-    nulls:      bool,
-    keys_wtr:   KeysWriter,
+    wtr:           csv::Writer<W>,
+    rdr1:          csv::Reader<R>,
+    sel1:          Selection,
+    rdr2:          csv::Reader<R>,
+    sel2:          Selection,
+    no_headers:    bool,
+    casei:         bool,
+    zerosi:        bool,
+    key_transform: Option<KeyTransform>,
+    nulls:         bool,
+    keys_wtr:      KeysWriter,
 }
 
 impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
@@ -231,13 +406,25 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
 
     fn inner_join(mut self) -> CliResult<()> {
         let mut scratch = csv::ByteRecord::new();
-        let mut validx =
-            ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.zerosi, self.nulls)?;
+        let mut validx = ValueIndex::new(
+            self.rdr2,
+            &self.sel2,
+            self.casei,
+            self.zerosi,
+            self.key_transform.as_ref(),
+            self.nulls,
+        )?;
         let mut row = csv::ByteRecord::new();
         let mut key;
 
         while self.rdr1.read_byte_record(&mut row)? {
-            key = get_row_key(&self.sel1, &row, self.casei, self.zerosi);
+            key = get_row_key(
+                &self.sel1,
+                &row,
+                self.casei,
+                self.zerosi,
+                self.key_transform.as_ref(),
+            );
             if let Some(rows) = validx.values.get(&key) {
                 self.keys_wtr.write_key(&key)?;
 
@@ -264,13 +451,25 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
 
         let mut scratch = csv::ByteRecord::new();
         let (_, pad2) = self.get_padding()?;
-        let mut validx =
-            ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.zerosi, self.nulls)?;
+        let mut validx = ValueIndex::new(
+            self.rdr2,
+            &self.sel2,
+            self.casei,
+            self.zerosi,
+            self.key_transform.as_ref(),
+            self.nulls,
+        )?;
         let mut row = csv::ByteRecord::new();
         let mut key;
 
         while self.rdr1.read_byte_record(&mut row)? {
-            key = get_row_key(&self.sel1, &row, self.casei, self.zerosi);
+            key = get_row_key(
+                &self.sel1,
+                &row,
+                self.casei,
+                self.zerosi,
+                self.key_transform.as_ref(),
+            );
             if let Some(rows) = validx.values.get(&key) {
                 self.keys_wtr.write_key(&key)?;
 
@@ -296,12 +495,25 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
     }
 
     fn left_join(mut self, anti: bool) -> CliResult<()> {
-        let validx = ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.zerosi, self.nulls)?;
+        let validx = ValueIndex::new(
+            self.rdr2,
+            &self.sel2,
+            self.casei,
+            self.zerosi,
+            self.key_transform.as_ref(),
+            self.nulls,
+        )?;
         let mut row = csv::ByteRecord::new();
         let mut key;
 
         while self.rdr1.read_byte_record(&mut row)? {
-            key = get_row_key(&self.sel1, &row, self.casei, self.zerosi);
+            key = get_row_key(
+                &self.sel1,
+                &row,
+                self.casei,
+                self.zerosi,
+                self.key_transform.as_ref(),
+            );
             if validx.values.get(&key).is_none() {
                 if anti {
                     self.keys_wtr.write_key(&key)?;
@@ -320,8 +532,14 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
     fn full_outer_join(mut self) -> CliResult<()> {
         let mut scratch = csv::ByteRecord::new();
         let (pad1, pad2) = self.get_padding()?;
-        let mut validx =
-            ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.zerosi, self.nulls)?;
+        let mut validx = ValueIndex::new(
+            self.rdr2,
+            &self.sel2,
+            self.casei,
+            self.zerosi,
+            self.key_transform.as_ref(),
+            self.nulls,
+        )?;
 
         // Keep track of which rows we've written from rdr2.
         let mut rdr2_written: Vec<_> = repeat(false).take(validx.num_rows).collect();
@@ -329,7 +547,13 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
         let mut key;
 
         while self.rdr1.read_byte_record(&mut row1)? {
-            key = get_row_key(&self.sel1, &row1, self.casei, self.zerosi);
+            key = get_row_key(
+                &self.sel1,
+                &row1,
+                self.casei,
+                self.zerosi,
+                self.key_transform.as_ref(),
+            );
             if let Some(rows) = validx.values.get(&key) {
                 self.keys_wtr.write_key(&key)?;
 
@@ -379,6 +603,146 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
         Ok(self.wtr.flush()?)
     }
 
+    /// Joins the inputs partition by partition (a Grace hash join). Both inputs are
+    /// partitioned by the hash of their join keys, so matching rows are always in
+    /// partitions with the same number, and only one partition of the second input
+    /// has to be loaded into memory at a time.
+    fn spill_join(mut self, join: JoinKind, spill: &SpillConfig) -> CliResult<()> {
+        let (pad1, pad2) = self.get_padding()?;
+        let hasher = ahash::RandomState::new();
+        let partitions1 = partition_rows(
+            &mut self.rdr1,
+            &self.sel1,
+            self.casei,
+            self.zerosi,
+            self.key_transform.as_ref(),
+            &hasher,
+            spill,
+        )?;
+        let partitions2 = partition_rows(
+            &mut self.rdr2,
+            &self.sel2,
+            self.casei,
+            self.zerosi,
+            self.key_transform.as_ref(),
+            &hasher,
+            spill,
+        )?;
+
+        let mut row1 = csv::ByteRecord::new();
+        let mut key;
+        for (partition1, partition2) in partitions1.into_iter().zip(partitions2) {
+            let rows2 = partition_reader(partition2)
+                .byte_records()
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut values: AHashMap<Vec<ByteString>, Vec<usize>> = AHashMap::new();
+            for (i, row2) in rows2.iter().enumerate() {
+                key = get_row_key(
+                    &self.sel2,
+                    row2,
+                    self.casei,
+                    self.zerosi,
+                    self.key_transform.as_ref(),
+                );
+                if self.nulls || !key.iter().any(std::vec::Vec::is_empty) {
+                    values.entry(key).or_default().push(i);
+                }
+            }
+            let mut rows2_written = vec![false; rows2.len()];
+
+            let mut rdr1 = partition_reader(partition1);
+            while rdr1.read_byte_record(&mut row1)? {
+                key = get_row_key(
+                    &self.sel1,
+                    &row1,
+                    self.casei,
+                    self.zerosi,
+                    self.key_transform.as_ref(),
+                );
+                match (values.get(&key), join) {
+                    (Some(_), JoinKind::LeftAnti)
+                    | (None, JoinKind::Inner | JoinKind::Right | JoinKind::LeftSemi) => {},
+                    (None, JoinKind::LeftAnti) | (Some(_), JoinKind::LeftSemi) => {
+                        self.keys_wtr.write_key(&key)?;
+                        self.wtr.write_record(&row1)?;
+                    },
+                    (None, JoinKind::Left | JoinKind::Full) => {
+                        self.wtr.write_record(row1.iter().chain(&pad2))?;
+                    },
+                    (Some(rows), _) => {
+                        self.keys_wtr.write_key(&key)?;
+                        for &rowi in rows {
+                            rows2_written[rowi] = true;
+                            self.wtr.write_record(row1.iter().chain(&rows2[rowi]))?;
+                        }
+                    },
+                }
+            }
+
+            if matches!(join, JoinKind::Right | JoinKind::Full) {
+                for (row2, written) in rows2.iter().zip(rows2_written) {
+                    if !written {
+                        self.wtr.write_record(pad1.iter().chain(row2))?;
+                    }
+                }
+            }
+        }
+        self.wtr.flush()?;
+        self.keys_wtr.flush()?;
+        Ok(())
+    }
+
+    /// Joins each row of the first CSV with the rows of the second CSV whose range
+    /// contains its join value.
+    fn range_join(mut self, range: &RangeConfig) -> CliResult<()> {
+        let (pad1, pad2) = self.get_padding()?;
+        let mut rngidx = RangeIndex::new(self.rdr2, &self.sel2, range)?;
+
+        let mut rows2_written = vec![false; rngidx.num_rows];
+        let mut scratch = csv::ByteRecord::new();
+        let mut row = csv::ByteRecord::new();
+        while self.rdr1.read_byte_record(&mut row)? {
+            let value = self.sel1.select(&row).next().unwrap_or_default();
+            let matches = range
+                .parse(value)
+                .map(|value| rngidx.matches(value, range))
+                .unwrap_or_default();
+            match (matches.is_empty(), range.join) {
+                (false, JoinKind::LeftAnti)
+                | (true, JoinKind::Inner | JoinKind::Right | JoinKind::LeftSemi) => {},
+                (true, JoinKind::LeftAnti) | (false, JoinKind::LeftSemi) => {
+                    self.keys_wtr.write_key(&[value.to_vec()])?;
+                    self.wtr.write_record(&row)?;
+                },
+                (true, JoinKind::Left | JoinKind::Full) => {
+                    self.wtr.write_record(row.iter().chain(&pad2))?;
+                },
+                (false, _) => {
+                    self.keys_wtr.write_key(&[value.to_vec()])?;
+                    for rowi in matches {
+                        rows2_written[rowi] = true;
+                        rngidx.idx.seek(rowi as u64)?;
+                        rngidx.idx.read_byte_record(&mut scratch)?;
+                        self.wtr.write_record(row.iter().chain(&scratch))?;
+                    }
+                },
+            }
+        }
+
+        if matches!(range.join, JoinKind::Right | JoinKind::Full) {
+            for (i, &written) in rows2_written.iter().enumerate() {
+                if !written {
+                    rngidx.idx.seek(i as u64)?;
+                    rngidx.idx.read_byte_record(&mut scratch)?;
+                    self.wtr.write_record(pad1.iter().chain(&scratch))?;
+                }
+            }
+        }
+        self.wtr.flush()?;
+        self.keys_wtr.flush()?;
+        Ok(())
+    }
+
     fn get_padding(&mut self) -> CliResult<(csv::ByteRecord, csv::ByteRecord)> {
         let len1 = self.rdr1.byte_headers()?.len();
         let len2 = self.rdr2.byte_headers()?.len();
@@ -389,7 +753,136 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
     }
 }
 
+/// Partitions the rows of a CSV by the hash of their join keys into temporary files,
+/// returning the files rewound to their start.
+fn partition_rows<R: io::Read>(
+    rdr: &mut csv::Reader<R>,
+    sel: &Selection,
+    casei: bool,
+    zerosi: bool,
+    key_transform: Option<&KeyTransform>,
+    hasher: &ahash::RandomState,
+    spill: &SpillConfig,
+) -> CliResult<Vec<fs::File>> {
+    let mut wtrs = (0..spill.partitions)
+        .map(|_| {
+            Ok(csv::WriterBuilder::new()
+                .flexible(true)
+                .from_writer(io::BufWriter::new(tempfile::tempfile_in(&spill.tmp_dir)?)))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let mut row = csv::ByteRecord::new();
+    while rdr.read_byte_record(&mut row)? {
+        let key = get_row_key(sel, &row, casei, zerosi, key_transform);
+        let partition = (hasher.hash_one(&key) % spill.partitions as u64) as usize;
+        wtrs[partition].write_byte_record(&row)?;
+    }
+
+    let partitions = wtrs
+        .into_iter()
+        .map(|wtr| {
+            let mut file = wtr
+                .into_inner()
+                .map_err(csv::IntoInnerError::into_error)?
+                .into_inner()
+                .map_err(io::IntoInnerError::into_error)?;
+            file.rewind()?;
+            Ok(file)
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok(partitions)
+}
+
+fn partition_reader(partition: fs::File) -> csv::Reader<io::BufReader<fs::File>> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(io::BufReader::new(partition))
+}
+
 impl Args {
+    fn range_config(&self) -> CliResult<Option<RangeConfig>> {
+        if !self.flag_range {
+            return Ok(None);
+        }
+        if self.flag_spill {
+            return fail_incorrectusage_clierror!("--range cannot be used with --spill.");
+        }
+        if self.flag_ignore_case
+            || self.flag_ignore_leading_zeros
+            || self.flag_key_transform.is_some()
+        {
+            return fail_incorrectusage_clierror!(
+                "--range cannot be used with --ignore-case, --ignore-leading-zeros or \
+                 --key-transform."
+            );
+        }
+        let join = match (
+            self.flag_left,
+            self.flag_left_anti,
+            self.flag_left_semi,
+            self.flag_right,
+            self.flag_right_anti,
+            self.flag_right_semi,
+            self.flag_full,
+            self.flag_cross,
+        ) {
+            (false, false, false, false, false, false, false, false) => JoinKind::Inner,
+            (true, false, false, false, false, false, false, false) => JoinKind::Left,
+            (false, true, false, false, false, false, false, false) => JoinKind::LeftAnti,
+            (false, false, true, false, false, false, false, false) => JoinKind::LeftSemi,
+            (false, false, false, true, false, false, false, false) => JoinKind::Right,
+            (false, false, false, false, false, false, true, false) => JoinKind::Full,
+            _ => {
+                return fail_incorrectusage_clierror!(
+                    "--range supports inner, --left, --left-anti, --left-semi, --right and --full \
+                     joins. Please pick at most one of them."
+                )
+            },
+        };
+        let range_type = match self.flag_range_type.to_lowercase().as_str() {
+            "number" => RangeType::Number,
+            "date" => RangeType::Date,
+            "ip" => RangeType::Ip,
+            _ => {
+                return fail_incorrectusage_clierror!(
+                    "Invalid --range-type \"{}\". Valid types are number, date and ip.",
+                    self.flag_range_type
+                )
+            },
+        };
+        Ok(Some(RangeConfig {
+            join,
+            range_type,
+            closed: self.flag_range_closed,
+            prefer_dmy: util::get_envvar_flag("QSV_PREFER_DMY"),
+        }))
+    }
+
+    fn spill_config(&self) -> CliResult<Option<SpillConfig>> {
+        if !self.flag_spill {
+            return Ok(None);
+        }
+        if self.flag_cross {
+            return fail_incorrectusage_clierror!("--spill cannot be used with --cross.");
+        }
+        if self.flag_spill_partitions == 0 {
+            return fail_incorrectusage_clierror!("--spill-partitions must be at least 1.");
+        }
+        let tmp_dir = self
+            .flag_tmp_dir
+            .clone()
+            .unwrap_or_else(|| "./".to_string());
+        if !path::Path::new(&tmp_dir).exists() {
+            return fail_clierror!("tmp-dir '{tmp_dir}' does not exist");
+        }
+        Ok(Some(SpillConfig {
+            partitions: self.flag_spill_partitions,
+            tmp_dir,
+        }))
+    }
+
     fn new_io_state(
         &self,
     ) -> CliResult<IoState<Box<dyn SeekRead + 'static>, Box<dyn io::Write + 'static>>> {
@@ -427,12 +920,16 @@ impl Args {
             no_headers: rconf1.no_headers,
             casei: self.flag_ignore_case,
             zerosi: self.flag_ignore_leading_zeros,
+            key_transform: self
+                .flag_key_transform
+                .as_deref()
+                .map(KeyTransform::new)
+                .transpose()?,
             nulls: self.flag_nulls,
             keys_wtr,
         })
     }
 
-    #[allow(clippy::unused_self)]
     fn get_selections<R: io::Read>(
         &self,
         rconf1: &Config,
@@ -444,6 +941,18 @@ impl Args {
         let headers2 = rdr2.byte_headers()?;
         let select1 = rconf1.selection(headers1)?;
         let select2 = rconf2.selection(headers2)?;
+        if self.flag_range {
+            if select1.len() != 1 || select2.len() != 2 {
+                return fail_incorrectusage_clierror!(
+                    "With --range, columns1 must be a single column and columns2 the start and \
+                     end columns of the ranges, but found column selections with {} and {} \
+                     columns.",
+                    select1.len(),
+                    select2.len()
+                );
+            }
+            return Ok((select1, select2));
+        }
         if select1.len() != select2.len() {
             return fail_incorrectusage_clierror!(
                 "Column selections must have the same number of columns, but found column \
@@ -477,6 +986,7 @@ impl<R: io::Read + io::Seek> ValueIndex<R> {
     /// * `sel` - A Selection that specifies which columns to index
     /// * `casei` - If true, indexed values are compared case-insensitively
     /// * `zerosi` - If true, indexed values are compared without leading zeros
+    /// * `key_transform` - If set, indexed values are normalized with these apply operations
     /// * `nulls` - If true, indexed rows with empty values are included
     ///
     /// # Returns
@@ -489,13 +999,15 @@ impl<R: io::Read + io::Seek> ValueIndex<R> {
     /// # Notes
     ///
     /// - Header rows are included in the byte offset index but not the value index
-    /// - Values are trimmed and optionally converted to lowercase before indexing
+    /// - Values are trimmed, optionally transformed with `key_transform` and converted to lowercase
+    ///   before indexing
     /// - Rows with empty indexed values are skipped unless nulls=true
     fn new(
         mut rdr: csv::Reader<R>,
         sel: &Selection,
         casei: bool,
         zerosi: bool,
+        key_transform: Option<&KeyTransform>,
         nulls: bool,
     ) -> CliResult<ValueIndex<R>> {
         let mut val_idx = AHashMap::with_capacity(20_000);
@@ -526,35 +1038,7 @@ impl<R: io::Read + io::Seek> ValueIndex<R> {
             // indexes in one pass.
             row_idx.write_u64::<BigEndian>(row.position().unwrap().byte())?;
 
-            let fields: Vec<_> = sel
-                .select(&row)
-                .map(|v| {
-                    if let Ok(s) = simdutf8::basic::from_utf8(v) {
-                        let cased_bytes_vec = if casei {
-                            s.trim().to_lowercase().into_bytes()
-                        } else {
-                            s.trim().as_bytes().to_vec()
-                        };
-                        if zerosi {
-                            if cased_bytes_vec.iter().all(|&b| b == b'0')
-                                && !cased_bytes_vec.is_empty()
-                            {
-                                vec![b'0']
-                            } else {
-                                cased_bytes_vec
-                                    .iter()
-                                    .skip_while(|&b| *b == b'0')
-                                    .copied()
-                                    .collect()
-                            }
-                        } else {
-                            cased_bytes_vec
-                        }
-                    } else {
-                        v.to_vec()
-                    }
-                })
-                .collect();
+            let fields = get_row_key(sel, &row, casei, zerosi, key_transform);
             if nulls || !fields.iter().any(std::vec::Vec::is_empty) {
                 match val_idx.entry(fields) {
                     Entry::Vacant(v) => {
@@ -598,6 +1082,115 @@ impl<R> fmt::Debug for ValueIndex<R> {
     }
 }
 
+/// A sorted interval index of the ranges of the second CSV, for --range joins.
+struct RangeIndex<R> {
+    // (start, end, row number) of the ranges, sorted by start
+    ranges:   Vec<(RangeValue, RangeValue, usize)>,
+    // the maximum end of ranges[..=i], so looking for ranges containing a value
+    // can stop as soon as no earlier range ends after it
+    max_ends: Vec<RangeValue>,
+    idx:      Indexed<R, io::Cursor<Vec<u8>>>,
+    num_rows: usize,
+}
+
+impl<R: io::Read + io::Seek> RangeIndex<R> {
+    /// Creates a new RangeIndex by reading the start & end columns of a CSV,
+    /// building a byte offset index for random access to its rows like ValueIndex.
+    /// Rows with an empty start or end are not indexed.
+    fn new(
+        mut rdr: csv::Reader<R>,
+        sel: &Selection,
+        range: &RangeConfig,
+    ) -> CliResult<RangeIndex<R>> {
+        let mut ranges = Vec::with_capacity(20_000);
+        let mut row_idx = io::Cursor::new(Vec::with_capacity(8 * 20_000));
+        let (mut rowi, mut count) = (0_usize, 0_usize);
+
+        // see ValueIndex::new
+        if rdr.has_headers() {
+            rdr.byte_headers()?;
+            row_idx.write_u64::<BigEndian>(0)?;
+            count += 1;
+        } else {
+            let mut pos = csv::Position::new();
+            pos.set_byte(0);
+            rdr.seek(pos)?;
+        }
+
+        let mut row = csv::ByteRecord::new();
+        while rdr.read_byte_record(&mut row)? {
+            row_idx.write_u64::<BigEndian>(row.position().unwrap().byte())?;
+
+            let mut bounds = sel.select(&row);
+            let (start, end) = (
+                bounds.next().unwrap_or_default(),
+                bounds.next().unwrap_or_default(),
+            );
+            if !util::trim_bs_whitespace(start).is_empty()
+                && !util::trim_bs_whitespace(end).is_empty()
+            {
+                let (Some(start_value), Some(end_value)) = (range.parse(start), range.parse(end))
+                else {
+                    return fail_clierror!(
+                        "Invalid range \"{}\" - \"{}\" in row {} of input2.",
+                        String::from_utf8_lossy(start),
+                        String::from_utf8_lossy(end),
+                        rowi + 1
+                    );
+                };
+                if start_value > end_value {
+                    return fail_clierror!(
+                        "Invalid range in row {} of input2: the start \"{}\" is after the end \
+                         \"{}\".",
+                        rowi + 1,
+                        String::from_utf8_lossy(start),
+                        String::from_utf8_lossy(end)
+                    );
+                }
+                ranges.push((start_value, end_value, rowi));
+            }
+            rowi += 1;
+            count += 1;
+        }
+
+        // the values are never NaN, so they are totally ordered
+        ranges.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        let mut max_ends: Vec<RangeValue> = Vec::with_capacity(ranges.len());
+        for &(_, end, _) in &ranges {
+            max_ends.push(match max_ends.last() {
+                Some(&max_end) if max_end > end => max_end,
+                _ => end,
+            });
+        }
+
+        row_idx.write_u64::<BigEndian>(count as u64)?;
+        let idx = Indexed::open(rdr, io::Cursor::new(row_idx.into_inner()))?;
+        Ok(RangeIndex {
+            ranges,
+            max_ends,
+            idx,
+            num_rows: rowi,
+        })
+    }
+
+    /// The row numbers of the ranges that contain the value, in the order of the CSV.
+    fn matches(&self, value: RangeValue, range: &RangeConfig) -> Vec<usize> {
+        let starts_before = self.ranges.partition_point(|&(start, ..)| start <= value);
+        let mut rows = Vec::new();
+        for i in (0..starts_before).rev() {
+            if !range.ends_after(self.max_ends[i], value) {
+                break;
+            }
+            let (_, end, rowi) = self.ranges[i];
+            if range.ends_after(end, value) {
+                rows.push(rowi);
+            }
+        }
+        rows.sort_unstable();
+        rows
+    }
+}
+
 #[inline]
 /// Extracts key values from a CSV row based on the given selection and options.
 ///
@@ -618,6 +1211,7 @@ impl<R> fmt::Debug for ValueIndex<R> {
 /// 1. Attempts to convert the bytes to a UTF-8 string
 /// 2. If successful:
 ///    - Trims leading/trailing whitespace
+///    - Optionally normalizes it with the `key_transform` apply operations
 ///    - Optionally converts to lowercase if `casei` is true
 ///    - If `zerosi` is true:
 ///      * For all-zero values, returns a single "0" byte
@@ -629,11 +1223,20 @@ fn get_row_key(
     row: &csv::ByteRecord,
     casei: bool,
     zerosi: bool,
+    key_transform: Option<&KeyTransform>,
 ) -> Vec<ByteString> {
     let key: Vec<_> = sel
         .select(row)
         .map(|v| {
             if let Ok(s) = simdutf8::basic::from_utf8(v) {
+                let transformed;
+                let s = match key_transform {
+                    Some(key_transform) => {
+                        transformed = key_transform.apply(s.trim());
+                        transformed.as_str()
+                    },
+                    None => s,
+                };
                 let cased_bytes_vec = if casei {
                     s.trim().to_lowercase().into_bytes()
                 } else {
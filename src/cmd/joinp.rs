@@ -236,6 +236,13 @@ joinp options:
                                nfkd - Normalization Form KD
                                none - No normalization is performed.
                              [default: none]
+   --key-transform <ops>     Normalize the join keys with a comma-delimited series of
+                             `qsv apply operations`, applied in order before the
+                             other join key transformations. Valid operations are
+                             trim, ltrim, rtrim, ltrim0, lower, upper, squeeze,
+                             squeeze0, strip_punct and unaccent.
+                             (e.g. "trim,lower,strip_punct,squeeze,unaccent")
+                             Requires the apply feature.
 
 Common options:
     -h, --help             Display this message
@@ -246,12 +253,14 @@ Common options:
 "#;
 
 use std::{
+    borrow::Cow,
     env,
     fs::File,
     io::{self, BufReader, BufWriter, Read, Write},
     mem::swap,
     path::{Path, PathBuf},
     str,
+    sync::Arc,
 };
 
 use polars::prelude::*;
@@ -259,7 +268,10 @@ use serde::Deserialize;
 use tempfile::tempdir;
 
 use crate::{
-    cmd::sqlp::compress_output_if_needed, config::Delimiter, util, util::get_stats_records,
+    cmd::sqlp::compress_output_if_needed,
+    config::Delimiter,
+    util,
+    util::{get_stats_records, KeyTransform},
     CliResult,
 };
 
@@ -309,6 +321,7 @@ struct Args {
     flag_ignore_case:          bool,
     flag_ignore_leading_zeros: bool,
     flag_norm_unicode:         Option<String>,
+    flag_key_transform:        Option<String>,
 }
 
 #[derive(PartialEq, Eq)]
@@ -565,6 +578,7 @@ struct JoinStruct {
     null_value:           String,
     ignore_case:          bool,
     ignore_leading_zeros: bool,
+    key_transform:        Option<Arc<KeyTransform>>,
 }
 
 impl JoinStruct {
@@ -588,69 +602,76 @@ impl JoinStruct {
             .map(polars::lazy::dsl::col)
             .collect();
 
-        // Handle ignore_case, ignore_leading_zeros, and unicode normalization transformations
-        let keys_transformed =
-            if self.ignore_case || self.ignore_leading_zeros || normalization_form.is_some() {
-                // Create transformation function that applies all enabled transformations
-                let transform_col = |col: Expr| {
-                    let mut transformed = col.cast(DataType::String);
-                    if self.ignore_leading_zeros {
-                        transformed = transformed.str().replace_all(lit(r"^0+"), lit(""), false);
-                    }
-                    if self.ignore_case {
-                        transformed = transformed.str().to_lowercase();
-                    }
-                    if let Some(ref form) = normalization_form {
-                        transformed = transformed.str().normalize(form.clone());
-                    }
-                    transformed
-                };
-
-                // Helper to get clean column name without col("") wrapper
-                let clean_col_name = |col: &Expr| {
-                    col.to_string()
-                        .trim_start_matches(r#"col(""#)
-                        .trim_end_matches(r#"")"#)
-                        .to_string()
-                };
-
-                // Transform left dataframe columns
-                for col in &left_selcols {
-                    let col_name = clean_col_name(col);
-                    let temp_col_name = format!("_qsv-{col_name}-transformed");
-                    self.left_lf = self
-                        .left_lf
-                        .with_column(transform_col(col.clone()).alias(&temp_col_name));
+        // Handle key_transform, ignore_case, ignore_leading_zeros, and unicode normalization
+        // transformations
+        let keys_transformed = if self.key_transform.is_some()
+            || self.ignore_case
+            || self.ignore_leading_zeros
+            || normalization_form.is_some()
+        {
+            // Create transformation function that applies all enabled transformations
+            let transform_col = |col: Expr| {
+                let mut transformed = col.cast(DataType::String);
+                if let Some(ref key_transform) = self.key_transform {
+                    transformed = key_transform_expr(transformed, Arc::clone(key_transform));
                 }
-
-                // Transform right dataframe columns
-                for col in &right_selcols {
-                    let col_name = clean_col_name(col);
-                    let temp_col_name = format!("_qsv-{col_name}-transformed");
-                    self.right_lf = self
-                        .right_lf
-                        .with_column(transform_col(col.clone()).alias(&temp_col_name));
+                if self.ignore_leading_zeros {
+                    transformed = transformed.str().replace_all(lit(r"^0+"), lit(""), false);
                 }
+                if self.ignore_case {
+                    transformed = transformed.str().to_lowercase();
+                }
+                if let Some(ref form) = normalization_form {
+                    transformed = transformed.str().normalize(form.clone());
+                }
+                transformed
+            };
 
-                // Update selcols to use transformed column names
-                left_selcols = left_selcols
-                    .iter()
-                    .map(|col| {
-                        polars::lazy::dsl::col(format!("_qsv-{}-transformed", clean_col_name(col)))
-                    })
-                    .collect();
+            // Helper to get clean column name without col("") wrapper
+            let clean_col_name = |col: &Expr| {
+                col.to_string()
+                    .trim_start_matches(r#"col(""#)
+                    .trim_end_matches(r#"")"#)
+                    .to_string()
+            };
 
-                right_selcols = right_selcols
-                    .iter()
-                    .map(|col| {
-                        polars::lazy::dsl::col(format!("_qsv-{}-transformed", clean_col_name(col)))
-                    })
-                    .collect();
+            // Transform left dataframe columns
+            for col in &left_selcols {
+                let col_name = clean_col_name(col);
+                let temp_col_name = format!("_qsv-{col_name}-transformed");
+                self.left_lf = self
+                    .left_lf
+                    .with_column(transform_col(col.clone()).alias(&temp_col_name));
+            }
 
-                true
-            } else {
-                false
-            };
+            // Transform right dataframe columns
+            for col in &right_selcols {
+                let col_name = clean_col_name(col);
+                let temp_col_name = format!("_qsv-{col_name}-transformed");
+                self.right_lf = self
+                    .right_lf
+                    .with_column(transform_col(col.clone()).alias(&temp_col_name));
+            }
+
+            // Update selcols to use transformed column names
+            left_selcols = left_selcols
+                .iter()
+                .map(|col| {
+                    polars::lazy::dsl::col(format!("_qsv-{}-transformed", clean_col_name(col)))
+                })
+                .collect();
+
+            right_selcols = right_selcols
+                .iter()
+                .map(|col| {
+                    polars::lazy::dsl::col(format!("_qsv-{}-transformed", clean_col_name(col)))
+                })
+                .collect();
+
+            true
+        } else {
+            false
+        };
 
         let left_selcols_len = left_selcols.len();
         let right_selcols_len = right_selcols.len();
@@ -1152,10 +1173,30 @@ impl Args {
             },
             ignore_case: self.flag_ignore_case,
             ignore_leading_zeros: self.flag_ignore_leading_zeros,
+            key_transform: self
+                .flag_key_transform
+                .as_deref()
+                .map(KeyTransform::new)
+                .transpose()?
+                .map(Arc::new),
         })
     }
 }
 
+/// Normalizes the join keys with the --key-transform apply operations in a polars UDF,
+/// so joinp matches the same keys as the join & exclude commands.
+fn key_transform_expr(expr: Expr, key_transform: Arc<KeyTransform>) -> Expr {
+    expr.map(
+        move |column: Column| {
+            let transformed: StringChunked = column
+                .str()?
+                .apply_values(|value| Cow::Owned(key_transform.apply(value)));
+            Ok(Some(transformed.into_column()))
+        },
+        GetOutput::from_type(DataType::String),
+    )
+}
+
 /// if the file has a TSV/TAB or SSV extension, we automatically use
 /// tab or semicolon as the delimiter
 /// otherwise, we use the delimiter specified by the user
@@ -44,6 +44,7 @@ use serde_json::Value;
 
 use crate::{
     config::{Config, Delimiter, DEFAULT_RDR_BUFFER_CAPACITY},
+    formats::{infer_headers, json_line_to_csv_record},
     util, CliResult,
 };
 
@@ -57,96 +58,6 @@ struct Args {
     flag_batch:         usize,
 }
 
-fn recurse_to_infer_headers(value: &Value, headers: &mut Vec<Vec<String>>, path: &[String]) {
-    match value {
-        Value::Object(map) => {
-            for (key, value) in map {
-                match value {
-                    Value::Null
-                    | Value::Bool(_)
-                    | Value::Number(_)
-                    | Value::String(_)
-                    | Value::Array(_) => {
-                        let mut full_path = path.to_owned();
-                        full_path.push(key.to_string());
-
-                        headers.push(full_path);
-                    },
-                    Value::Object(_) => {
-                        let mut new_path = path.to_owned();
-                        new_path.push(key.to_string());
-
-                        recurse_to_infer_headers(value, headers, &new_path);
-                    },
-                    #[allow(unreachable_patterns)]
-                    _ => {},
-                }
-            }
-        },
-        _ => {
-            headers.push(vec![String::from("value")]);
-        },
-    }
-}
-
-fn infer_headers(value: &Value) -> Vec<Vec<String>> {
-    let mut headers: Vec<Vec<String>> = Vec::new();
-
-    recurse_to_infer_headers(value, &mut headers, &Vec::new());
-
-    headers
-}
-
-fn get_value_at_path(value: &Value, path: &[String]) -> Option<Value> {
-    let mut current = value;
-
-    for key in path {
-        match current.get(key) {
-            Some(new_value) => {
-                current = new_value;
-            },
-            None => {
-                return None;
-            },
-        }
-    }
-
-    Some(current.clone())
-}
-
-#[inline]
-fn json_line_to_csv_record(value: &Value, headers: &[Vec<String>]) -> csv::StringRecord {
-    let mut record = csv::StringRecord::new();
-
-    for path in headers {
-        let value = get_value_at_path(value, path);
-
-        if let Some(value) = value {
-            record.push_field(&match value {
-                Value::Bool(v) => {
-                    if v {
-                        String::from("true")
-                    } else {
-                        String::from("false")
-                    }
-                },
-                Value::Number(v) => v.to_string(),
-                Value::String(v) => v,
-                Value::Array(v) => v
-                    .iter()
-                    .map(std::string::ToString::to_string)
-                    .collect::<Vec<_>>()
-                    .join(","),
-                _ => String::new(),
-            });
-        } else {
-            record.push_field("");
-        }
-    }
-
-    record
-}
-
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
     let mut wtr = Config::new(args.flag_output.as_ref())
@@ -252,7 +252,8 @@ use mlua::{Lua, LuaSerdeExt, Value};
 use serde::Deserialize;
 
 use crate::{
-    config::{Config, Delimiter, DEFAULT_WTR_BUFFER_CAPACITY},
+    config::{Compression, Config, Delimiter, DEFAULT_WTR_BUFFER_CAPACITY},
+    index::create_block_index,
     lookup, util, CliError, CliResult,
 };
 
@@ -1399,8 +1400,12 @@ fn create_index(arg_input: Option<&String>) -> Result<bool, CliError> {
         return Ok(false);
     };
 
-    if input.to_ascii_lowercase().ends_with(".sz") {
-        log::warn!("qsv_autoindex() does not work with snappy files.");
+    let compression = Compression::from_path(Path::new(&input));
+    if compression.is_compressed() && !compression.is_block_seekable() {
+        log::warn!(
+            "qsv_autoindex() does not work with {}-compressed files.",
+            compression.extension()
+        );
         return Ok(false);
     }
 
@@ -1408,12 +1413,18 @@ fn create_index(arg_input: Option<&String>) -> Result<bool, CliError> {
     debug!("Creating index file {pidx:?} for {input:?}.");
 
     let rconfig = Config::new(Some((*input).to_string()).as_ref());
-    let mut rdr = rconfig.reader_file()?;
     let mut wtr =
         io::BufWriter::with_capacity(DEFAULT_WTR_BUFFER_CAPACITY, fs::File::create(pidx)?);
-    if RandomAccessSimple::create(&mut rdr, &mut wtr).is_err() {
-        return Ok(false);
-    };
+    if compression.is_compressed() {
+        if create_block_index(&rconfig, Path::new(&input), compression, &mut wtr).is_err() {
+            return Ok(false);
+        }
+    } else {
+        let mut rdr = rconfig.reader_file()?;
+        if RandomAccessSimple::create(&mut rdr, &mut wtr).is_err() {
+            return Ok(false);
+        };
+    }
     if wtr.flush().is_err() {
         return Ok(false);
     }
@@ -5,10 +5,16 @@ pub mod applydp;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod behead;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod bins;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod cat;
 #[cfg(feature = "clipboard")]
 pub mod clipboard;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod corr;
 pub mod count;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod crosstab;
 pub mod datefmt;
 pub mod dedup;
 pub mod describegpt;
@@ -39,6 +45,8 @@ pub mod fmt;
 #[cfg(all(feature = "foreach", not(feature = "lite")))]
 pub mod foreach;
 pub mod frequency;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod fuzzyjoin;
 #[cfg(all(feature = "geocode", feature = "feature_capable"))]
 pub mod geocode;
 pub mod headers;
@@ -61,6 +69,8 @@ pub mod lens;
 pub mod luau;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod partition;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod patch;
 #[cfg(all(
     feature = "polars",
     any(feature = "feature_capable", feature = "datapusher_plus")
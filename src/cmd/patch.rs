@@ -0,0 +1,233 @@
+static USAGE: &str = r#"
+Apply a diff result to a CSV, so the result of `qsv diff left.csv right.csv` can be
+applied to the left (base) CSV to reproduce the right CSV. This way, only the (usually
+much smaller) diff result has to be shipped instead of a full snapshot of the data.
+
+The diff result must be in the csv format of `qsv diff`, with the "diffresult" column
+marking deleted ("-") and added ("+") rows. A deleted and an added row with the same key
+are a modified row, which is replaced in place. Added rows are appended at the end, in
+the order of the diff result.
+
+Before any change is applied, the deleted and modified rows of the diff result are
+checked against the base CSV in a first pass, so applying a diff result to the wrong
+base CSV fails without writing any output. Because of this, the diff result must not be
+created with `--drop-equal-fields`, and the base CSV cannot be read from stdin.
+
+Note that rows the diff ignored (e.g. with --ignore-case or --ignore-columns) are
+not modified.
+
+Examples:
+
+Create a diff result of two snapshots, and apply it to the older snapshot:
+    qsv diff -o delta.csv snapshot-jan.csv snapshot-feb.csv
+    qsv patch -o snapshot-feb-copy.csv snapshot-jan.csv delta.csv
+
+Apply a diff result that was created using the first two columns as the key:
+    qsv diff --key 0,1 -o delta.csv left.csv right.csv
+    qsv patch --key 0,1 left.csv delta.csv
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_patch.rs
+
+Usage:
+    qsv patch [options] <base> <diff>
+    qsv patch --help
+
+patch options:
+    -k, --key <arg>        The column indices that uniquely identify a record
+                           as a comma separated list of indices, e.g. 0,1,2
+                           or column names, e.g. name,age.
+                           Must be the same key used to create the diff result.
+                           (default: 0)
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row of the base CSV and of the diff
+                           result will not be interpreted as headers.
+                           Use this when the diff result was created with
+                           --no-headers-left, --no-headers-right & --no-headers-output.
+    -d, --delimiter <arg>  The field delimiter for reading the base CSV and the
+                           diff result. Must be a single character. (default: ,)
+"#;
+
+use ahash::{AHashMap, AHashSet};
+use csv::ByteRecord;
+use serde::Deserialize;
+
+use crate::{
+    config::{Config, Delimiter},
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_base:        String,
+    arg_diff:        String,
+    flag_key:        Option<String>,
+    flag_output:     Option<String>,
+    flag_no_headers: bool,
+    flag_delimiter:  Option<Delimiter>,
+}
+
+type Key = Vec<Vec<u8>>;
+
+fn row_key(record: &ByteRecord, key_fields: &[usize]) -> Key {
+    key_fields
+        .iter()
+        .map(|&i| record.get(i).unwrap_or_default().to_vec())
+        .collect()
+}
+
+fn display_key(key: &Key) -> String {
+    key.iter()
+        .map(|k| String::from_utf8_lossy(k))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let base_config = Config::new(Some(&args.arg_base))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+    let diff_config = Config::new(Some(&args.arg_diff))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+
+    if base_config.is_stdin() {
+        return fail_incorrectusage_clierror!(
+            "<base> cannot be stdin, as it is read twice - to check the diff result against it, \
+             and to patch it."
+        );
+    }
+
+    let mut base_rdr = base_config.reader()?;
+    let mut diff_rdr = diff_config.reader()?;
+    let base_headers = base_rdr.byte_headers()?.clone();
+
+    if !args.flag_no_headers && diff_rdr.byte_headers()?.get(0) != Some(&b"diffresult"[..]) {
+        return fail_incorrectusage_clierror!(
+            "{} is not a diff result. Its first column must be \"diffresult\".",
+            args.arg_diff
+        );
+    }
+
+    let key_fields: Vec<usize> = match args.flag_key {
+        None => vec![0],
+        Some(ref s) if s.chars().all(|c: char| c.is_numeric() || c == ',') => s
+            .split(',')
+            .map(str::parse::<usize>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?,
+        Some(ref s) => s
+            .split(',')
+            .map(|col_name| {
+                base_headers
+                    .iter()
+                    .position(|h| h == col_name.as_bytes())
+                    .ok_or_else(|| format!("Column name '{col_name}' not found on base CSV"))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+
+    // read the diff result - deleted rows by key, to check them against the base CSV,
+    // and the added rows, which replace the deleted rows with the same key
+    let mut deleted: AHashMap<Key, ByteRecord> = AHashMap::new();
+    let mut added: Vec<(Key, ByteRecord)> = Vec::new();
+    for (i, diff_record) in diff_rdr.byte_records().enumerate() {
+        let diff_record = diff_record?;
+        let row: ByteRecord = diff_record.iter().skip(1).collect();
+        let key = row_key(&row, &key_fields);
+        match diff_record.get(0) {
+            Some(b"-") => {
+                if deleted.insert(key.clone(), row).is_some() {
+                    return fail_clierror!(
+                        "Duplicate deleted row with key \"{}\" in diff result.",
+                        display_key(&key)
+                    );
+                }
+            },
+            Some(b"+") => added.push((key, row)),
+            _ => {
+                return fail_clierror!(
+                    "Invalid diffresult in row {} of diff result. Must be \"+\" or \"-\".",
+                    i + 1
+                )
+            },
+        }
+    }
+    let mut modified: AHashMap<Key, ByteRecord> = AHashMap::new();
+    let mut appended: Vec<ByteRecord> = Vec::new();
+    let mut appended_keys: AHashSet<Key> = AHashSet::new();
+    for (key, row) in added {
+        if deleted.contains_key(&key) {
+            if modified.insert(key.clone(), row).is_some() {
+                return fail_clierror!(
+                    "Duplicate added row with key \"{}\" in diff result.",
+                    display_key(&key)
+                );
+            }
+        } else if !appended_keys.insert(key.clone()) {
+            return fail_clierror!(
+                "Duplicate added row with key \"{}\" in diff result.",
+                display_key(&key)
+            );
+        } else {
+            appended.push(row);
+        }
+    }
+
+    // first pass - check the diff result against the whole base CSV before writing anything,
+    // so a diff result that does not apply doesn't leave a partially patched output behind
+    let mut record = ByteRecord::new();
+    let mut checked: AHashSet<Key> = AHashSet::with_capacity(deleted.len());
+    while base_rdr.read_byte_record(&mut record)? {
+        let key = row_key(&record, &key_fields);
+        if let Some(expected) = deleted.get(&key) {
+            // only the first row with the key is the deleted row, as when patching
+            if checked.insert(key.clone()) && *expected != record {
+                return fail_clierror!(
+                    "Diff result does not apply: the row with key \"{}\" does not match the base \
+                     CSV.",
+                    display_key(&key)
+                );
+            }
+        } else if appended_keys.contains(&key) {
+            return fail_clierror!(
+                "Diff result does not apply: the added row with key \"{}\" is already in the base \
+                 CSV.",
+                display_key(&key)
+            );
+        }
+    }
+    if let Some(key) = deleted.keys().find(|key| !checked.contains(*key)) {
+        return fail_clierror!(
+            "Diff result does not apply: the row with key \"{}\" is not in the base CSV.",
+            display_key(key)
+        );
+    }
+    drop(checked);
+
+    // second pass - patch the base CSV
+    let mut base_rdr = base_config.reader()?;
+    let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+    base_config.write_headers(&mut base_rdr, &mut wtr)?;
+
+    while base_rdr.read_byte_record(&mut record)? {
+        let key = row_key(&record, &key_fields);
+        if deleted.remove(&key).is_some() {
+            if let Some(new_row) = modified.get(&key) {
+                wtr.write_byte_record(new_row)?;
+            }
+        } else {
+            wtr.write_byte_record(&record)?;
+        }
+    }
+
+    for row in appended {
+        wtr.write_byte_record(&row)?;
+    }
+
+    Ok(wtr.flush()?)
+}
@@ -47,6 +47,8 @@ pivotp options:
     --col-separator <arg>   The separator in generated column names in case of multiple --values columns.
                             [default: _]
     --validate              Validate a pivot by checking the pivot column(s)' cardinality.
+                            If the stats cache doesn't exist yet, the cardinalities are
+                            estimated with `qsv stats --approx-cardinality`.
     --try-parsedates        When set, will attempt to parse columns as dates.
     --infer-len <arg>       Number of rows to scan when inferring schema.
                             Set to 0 to scan entire file. [default: 10000]
@@ -129,7 +131,7 @@ fn calculate_pivot_metadata(
     };
 
     let (csv_fields, csv_stats) = STATS_RECORDS.get_or_init(|| {
-        get_stats_records(&schema_args, StatsMode::FrequencyForceStats)
+        get_stats_records(&schema_args, StatsMode::ApproxCardinality)
             .unwrap_or_else(|_| (ByteRecord::new(), Vec::new()))
     });
 
@@ -232,7 +234,7 @@ fn suggest_agg_function(
     };
 
     let (csv_fields, csv_stats) = STATS_RECORDS.get_or_init(|| {
-        get_stats_records(&schema_args, StatsMode::FrequencyForceStats)
+        get_stats_records(&schema_args, StatsMode::ApproxCardinality)
             .unwrap_or_else(|_| (ByteRecord::new(), Vec::new()))
     });
 
@@ -23,6 +23,8 @@ Note that `stdin.csv` will be overwritten if it already exists.
 Schema generation can be a compute-intensive process, especially for large CSV files.
 To speed up generation, the `schema` command will reuse a `stats.csv.data.jsonl` file if it
 exists and is current (i.e. stats generated with --cardinality and --infer-dates options).
+Stats generated with --approx-cardinality can also be used, as cardinalities below 4,096 -
+well above the default --enum-threshold - are counted exactly.
 Otherwise, it will run the `stats` command to generate the `stats.csv.data.jsonl` file first,
 and then use that to generate the schema file.
 
@@ -480,6 +482,11 @@ fn get_unique_values(
         flag_no_nulls:        true,
         flag_no_trim:         false,
         flag_ignore_case:     args.flag_ignore_case,
+        flag_weight:          None,
+        flag_approx:          false,
+        flag_approx_capacity: 0,
+        flag_groupby:         None,
+        flag_json:            false,
         // internal mode for getting frequency tables
         flag_stats_mode:      "_schema".to_string(),
         flag_all_unique_text: "<ALL UNIQUE>".to_string(),
@@ -83,7 +83,7 @@ use serde::Deserialize;
 
 use crate::{
     config::{Config, Delimiter},
-    index::Indexed,
+    index::{Indexed, IndexedSource},
     util, CliResult,
 };
 
@@ -169,7 +169,7 @@ impl Args {
         }
     }
 
-    fn with_index(&self, mut indexed_file: Indexed<fs::File, fs::File>) -> CliResult<()> {
+    fn with_index(&self, mut indexed_file: Indexed<IndexedSource, fs::File>) -> CliResult<()> {
         let (start, end) = self.range()?;
         if end - start == 0 && !self.flag_invert {
             return Ok(());
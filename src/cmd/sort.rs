@@ -5,6 +5,28 @@ reverse, unique or random (with optional seed) order.
 The sort is done in lexicographical order.
 https://en.wikipedia.org/wiki/Lexicographic_order
 
+With --by, each sort key has its own direction and type. A sort key is a column (by
+name or index, see 'qsv select --help'), followed by optional ':'-separated settings:
+
+  direction: asc (default) or desc
+  type:      str      compare as strings (default)
+             istr     compare as strings, disregarding case
+             num      compare as numbers
+             natural  compare as strings, with runs of digits compared as numbers
+                      (e.g. "file2" before "file10")
+             version  compare as version numbers, with a leading "v" ignored and
+                      pre-releases before releases (e.g. "v1.9" before "1.10.0",
+                      and "2.0.0-rc1" before "2.0.0")
+             date     compare as dates, parsed like `datefmt`. Set QSV_PREFER_DMY
+                      to parse ambiguous dates in day/month/year format.
+  nulls:     nulls_first or nulls_last. Nulls are empty values, and values that are not
+             valid numbers or dates for num & date keys. By default, nulls are smaller
+             than any other value (i.e. first in ascending & last in descending order).
+
+For example, `--by "date:desc:date,amount:num,name:natural:nulls_last"` sorts by the
+date column, newest first, then by amount, smallest first, and then by name in natural
+order, with empty names last.
+
 Note that this requires reading all of the CSV data into memory. If
 you need to sort a large file that may not fit into memory, use the
 extsort command instead.
@@ -21,8 +43,21 @@ sort options:
     -N, --numeric           Compare according to string numerical value
     -R, --reverse           Reverse order
     -i, --ignore-case       Compare strings disregarding case
+    --collation <locale>    Compare strings using the collation rules of a locale,
+                            e.g. "de", "fr-CA" or "sv", instead of byte-wise
+                            (so "Äpfel" sorts before "Zebra"). Append ":primary" to
+                            ignore accents and case, ":secondary" to only ignore
+                            case, or ":tertiary" (the default), e.g. "de:primary".
+                            Cannot be used with --numeric, --ignore-case,
+                            --random or --by.
     -u, --unique            When set, identical consecutive lines will be dropped
                             to keep only one line per sorted value.
+    --by <spec>             Sort by a typed, multi-key sort specification, a comma
+                            separated list of sort keys of the form
+                            column[:direction][:type][:nulls] (see above), e.g.
+                            "date:desc:date,amount:asc:num,name:asc:natural".
+                            Cannot be used with --select, --numeric, --reverse,
+                            --ignore-case or --random.
 
     --random                Random order
     --seed <number>         Random Number Generator (RNG) seed to use if --random is set
@@ -63,13 +98,20 @@ Common options:
                             CSV into memory using CONSERVATIVE heuristics.
 "#;
 
-use std::{cmp, str::FromStr};
+use std::{cmp, fmt, mem, str::FromStr};
 
+use icu_collator::{Collator, CollatorOptions, Strength};
+use icu_locid::Locale;
+use icu_provider::DataLocale;
+use qsv_dateparser::parse_with_preference;
 // use fastrand; //DevSkim: ignore DS148264
 use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 use rand_hc::Hc128Rng;
 use rand_xoshiro::Xoshiro256Plus;
-use rayon::slice::ParallelSliceMut;
+use rayon::{
+    iter::{IntoParallelIterator, ParallelIterator},
+    slice::ParallelSliceMut,
+};
 use serde::Deserialize;
 use simdutf8::basic::from_utf8;
 use strum_macros::EnumString;
@@ -89,7 +131,9 @@ struct Args {
     flag_numeric:     bool,
     flag_reverse:     bool,
     flag_ignore_case: bool,
+    flag_collation:   Option<String>,
     flag_unique:      bool,
+    flag_by:          Option<String>,
     flag_random:      bool,
     flag_seed:        Option<u64>,
     flag_rng:         String,
@@ -115,6 +159,24 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let reverse = args.flag_reverse;
     let random = args.flag_random;
     let faster = args.flag_faster;
+    if args.flag_by.is_some()
+        && (numeric || reverse || random || args.flag_ignore_case || !args.flag_select.is_empty())
+    {
+        return fail_incorrectusage_clierror!(
+            "--by cannot be used with --select, --numeric, --reverse, --ignore-case or --random."
+        );
+    }
+    let collation = match args.flag_collation {
+        Some(ref locale) => {
+            if numeric || random || args.flag_ignore_case || args.flag_by.is_some() {
+                return fail_incorrectusage_clierror!(
+                    "--collation cannot be used with --numeric, --ignore-case, --random or --by."
+                );
+            }
+            Some(Collation::parse(locale)?)
+        },
+        None => None,
+    };
     let rconfig = Config::new(args.arg_input.as_ref())
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
@@ -140,6 +202,15 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let headers = rdr.byte_headers()?.clone();
     let sel = rconfig.selection(&headers)?;
+    let sort_spec = match args.flag_by {
+        Some(ref by) => Some(SortSpec::parse(
+            by,
+            &headers,
+            args.flag_no_headers,
+            rconfig.get_dmy_preference(),
+        )?),
+        None => None,
+    };
 
     util::njobs(args.flag_jobs);
 
@@ -149,9 +220,12 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let ignore_case = args.flag_ignore_case;
 
     let mut all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
-    match (numeric, reverse, random, faster) {
+    match (numeric, reverse, random, faster, &sort_spec) {
+        // --by typed, multi-key sort
+        (_, _, _, _, Some(spec)) => sort_by_spec(&mut all, spec, faster),
+
         // --random sort
-        (_, _, true, _) => {
+        (_, _, true, _, None) => {
             match rng_kind {
                 RngKind::Standard => {
                     if let Some(val) = seed {
@@ -189,20 +263,24 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         },
 
         // default stable parallel sort
-        (false, false, false, false) => all.par_sort_by(|r1, r2| {
+        (false, false, false, false, None) => all.par_sort_by(|r1, r2| {
             let a = sel.select(r1);
             let b = sel.select(r2);
-            if ignore_case {
+            if let Some(ref collation) = collation {
+                collation.iter_cmp(a, b)
+            } else if ignore_case {
                 iter_cmp_ignore_case(a, b)
             } else {
                 iter_cmp(a, b)
             }
         }),
         // default --faster unstable, non-allocating parallel sort
-        (false, false, false, true) => all.par_sort_unstable_by(|r1, r2| {
+        (false, false, false, true, None) => all.par_sort_unstable_by(|r1, r2| {
             let a = sel.select(r1);
             let b = sel.select(r2);
-            if ignore_case {
+            if let Some(ref collation) = collation {
+                collation.iter_cmp(a, b)
+            } else if ignore_case {
                 iter_cmp_ignore_case(a, b)
             } else {
                 iter_cmp(a, b)
@@ -210,33 +288,37 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }),
 
         // --numeric stable parallel numeric sort
-        (true, false, false, false) => all.par_sort_by(|r1, r2| {
+        (true, false, false, false, None) => all.par_sort_by(|r1, r2| {
             let a = sel.select(r1);
             let b = sel.select(r2);
             iter_cmp_num(a, b)
         }),
         // --numeric --faster unstable, non-allocating, parallel numeric sort
-        (true, false, false, true) => all.par_sort_unstable_by(|r1, r2| {
+        (true, false, false, true, None) => all.par_sort_unstable_by(|r1, r2| {
             let a = sel.select(r1);
             let b = sel.select(r2);
             iter_cmp_num(a, b)
         }),
 
         // --reverse stable parallel sort
-        (false, true, false, false) => all.par_sort_by(|r1, r2| {
+        (false, true, false, false, None) => all.par_sort_by(|r1, r2| {
             let a = sel.select(r1);
             let b = sel.select(r2);
-            if ignore_case {
+            if let Some(ref collation) = collation {
+                collation.iter_cmp(b, a)
+            } else if ignore_case {
                 iter_cmp_ignore_case(b, a)
             } else {
                 iter_cmp(b, a)
             }
         }),
         // --reverse --faster unstable parallel sort
-        (false, true, false, true) => all.par_sort_unstable_by(|r1, r2| {
+        (false, true, false, true, None) => all.par_sort_unstable_by(|r1, r2| {
             let a = sel.select(r1);
             let b = sel.select(r2);
-            if ignore_case {
+            if let Some(ref collation) = collation {
+                collation.iter_cmp(b, a)
+            } else if ignore_case {
                 iter_cmp_ignore_case(b, a)
             } else {
                 iter_cmp(b, a)
@@ -244,13 +326,13 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }),
 
         // --numeric --reverse stable sort
-        (true, true, false, false) => all.par_sort_by(|r1, r2| {
+        (true, true, false, false, None) => all.par_sort_by(|r1, r2| {
             let a = sel.select(r1);
             let b = sel.select(r2);
             iter_cmp_num(b, a)
         }),
         // --numeric --reverse --faster unstable sort
-        (true, true, false, true) => all.par_sort_unstable_by(|r1, r2| {
+        (true, true, false, true, None) => all.par_sort_unstable_by(|r1, r2| {
             let a = sel.select(r1);
             let b = sel.select(r2);
             iter_cmp_num(b, a)
@@ -259,11 +341,20 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
     let mut prev: Option<csv::ByteRecord> = None;
+    let unique_cmp = |r1: &csv::ByteRecord, r2: &csv::ByteRecord| {
+        if let Some(ref spec) = sort_spec {
+            spec.compare(&spec.values(r1), &spec.values(r2))
+        } else if let Some(ref collation) = collation {
+            collation.iter_cmp(sel.select(r1), sel.select(r2))
+        } else {
+            iter_cmp(sel.select(r1), sel.select(r2))
+        }
+    };
     rconfig.write_headers(&mut rdr, &mut wtr)?;
     for r in all {
         if args.flag_unique {
             match prev {
-                Some(other_r) => match iter_cmp(sel.select(&r), sel.select(&other_r)) {
+                Some(other_r) => match unique_cmp(&r, &other_r) {
                     cmp::Ordering::Equal => (),
                     _ => {
                         wtr.write_byte_record(&r)?;
@@ -367,3 +458,446 @@ where
         None => None,
     }
 }
+
+/// Sort `all` by the `--by` sort specification, precomputing the sort values of each record
+fn sort_by_spec(all: &mut Vec<csv::ByteRecord>, spec: &SortSpec, faster: bool) {
+    let mut keyed: Vec<(Vec<SortValue>, csv::ByteRecord)> = mem::take(all)
+        .into_par_iter()
+        .map(|r| (spec.values(&r), r))
+        .collect();
+    if faster {
+        keyed.par_sort_unstable_by(|a, b| spec.compare(&a.0, &b.0));
+    } else {
+        keyed.par_sort_by(|a, b| spec.compare(&a.0, &b.0));
+    }
+    all.extend(keyed.into_iter().map(|(_, r)| r));
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SortType {
+    Str,
+    IStr,
+    Num,
+    Natural,
+    Version,
+    Date,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum NullsOrder {
+    Smallest,
+    First,
+    Last,
+}
+
+struct SortKey {
+    column:     usize,
+    descending: bool,
+    sort_type:  SortType,
+    nulls:      NullsOrder,
+}
+
+/// The value of a `--by` sort key of a record
+#[derive(Clone)]
+pub enum SortValue {
+    /// an empty value, or a value that is not a valid number or date for num & date keys
+    Null(Vec<u8>),
+    /// the value of a str, istr (lowercased), natural or version key
+    Text(Vec<u8>),
+    Num(f64),
+    /// a date, in milliseconds since the epoch
+    Date(i64),
+}
+
+/// A borrowed `SortValue`, so the sort values encoded by extsort can be compared
+/// without allocating
+#[derive(Clone, Copy)]
+enum SortValueRef<'a> {
+    Null(&'a [u8]),
+    Text(&'a [u8]),
+    Num(f64),
+    Date(i64),
+}
+
+impl SortValue {
+    fn borrowed(&self) -> SortValueRef<'_> {
+        match self {
+            SortValue::Null(bytes) => SortValueRef::Null(bytes),
+            SortValue::Text(bytes) => SortValueRef::Text(bytes),
+            SortValue::Num(n) => SortValueRef::Num(*n),
+            SortValue::Date(d) => SortValueRef::Date(*d),
+        }
+    }
+}
+
+/// A typed, multi-key sort specification (`--by`) of sort, extsort & sortcheck,
+/// e.g. "date:desc:date,amount:asc:num,name:asc:natural"
+pub struct SortSpec {
+    keys:       Vec<SortKey>,
+    prefer_dmy: bool,
+}
+
+impl SortSpec {
+    pub fn parse(
+        spec: &str,
+        headers: &csv::ByteRecord,
+        no_headers: bool,
+        prefer_dmy: bool,
+    ) -> CliResult<SortSpec> {
+        let mut keys = Vec::new();
+        for key_spec in spec.split(',') {
+            // the settings are the trailing ':'-separated parts, the rest is the column
+            let mut parts: Vec<&str> = key_spec.split(':').collect();
+            let (mut descending, mut sort_type, mut nulls) = (None, None, None);
+            while parts.len() > 1 {
+                let setting = parts[parts.len() - 1].trim().to_ascii_lowercase();
+                match setting.as_str() {
+                    "asc" | "desc" if descending.is_none() => {
+                        descending = Some(setting == "desc");
+                    },
+                    "nulls_first" if nulls.is_none() => nulls = Some(NullsOrder::First),
+                    "nulls_last" if nulls.is_none() => nulls = Some(NullsOrder::Last),
+                    _ if sort_type.is_none() => {
+                        sort_type = Some(match setting.as_str() {
+                            "str" => SortType::Str,
+                            "istr" => SortType::IStr,
+                            "num" => SortType::Num,
+                            "natural" => SortType::Natural,
+                            "version" => SortType::Version,
+                            "date" => SortType::Date,
+                            _ => break,
+                        });
+                    },
+                    _ => break,
+                }
+                parts.pop();
+            }
+            let column = parts.join(":");
+            let column = column.trim();
+            if column.is_empty() {
+                return fail_incorrectusage_clierror!(
+                    "Invalid --by sort key \"{key_spec}\": missing column."
+                );
+            }
+            let sel = SelectColumns::parse(column)
+                .and_then(|sel| sel.selection(headers, !no_headers))
+                .map_err(|e| {
+                    format!(
+                        "Invalid --by sort key \"{key_spec}\": {e}. Valid settings are asc, desc, \
+                         str, istr, num, natural, version, date, nulls_first & nulls_last."
+                    )
+                })?;
+            if sel.len() != 1 {
+                return fail_incorrectusage_clierror!(
+                    "Invalid --by sort key \"{key_spec}\": it must select exactly one column."
+                );
+            }
+            keys.push(SortKey {
+                column:     sel[0],
+                descending: descending.unwrap_or(false),
+                sort_type:  sort_type.unwrap_or(SortType::Str),
+                nulls:      nulls.unwrap_or(NullsOrder::Smallest),
+            });
+        }
+        Ok(SortSpec { keys, prefer_dmy })
+    }
+
+    /// The sort values of a record, one per sort key
+    pub fn values(&self, record: &csv::ByteRecord) -> Vec<SortValue> {
+        self.keys
+            .iter()
+            .map(|key| {
+                let field = record.get(key.column).unwrap_or_default();
+                let trimmed = util::trim_bs_whitespace(field);
+                if trimmed.is_empty() {
+                    return SortValue::Null(field.to_vec());
+                }
+                match key.sort_type {
+                    SortType::Str | SortType::Natural | SortType::Version => {
+                        SortValue::Text(field.to_vec())
+                    },
+                    SortType::IStr => {
+                        SortValue::Text(String::from_utf8_lossy(field).to_lowercase().into_bytes())
+                    },
+                    SortType::Num => fast_float2::parse::<f64, &[u8]>(trimmed)
+                        .ok()
+                        .filter(|n| !n.is_nan())
+                        .map_or_else(|| SortValue::Null(field.to_vec()), SortValue::Num),
+                    SortType::Date => from_utf8(trimmed)
+                        .ok()
+                        .and_then(|s| parse_with_preference(s, self.prefer_dmy).ok())
+                        .map_or_else(
+                            || SortValue::Null(field.to_vec()),
+                            |dt| SortValue::Date(dt.timestamp_millis()),
+                        ),
+                }
+            })
+            .collect()
+    }
+
+    /// Order the sort values of two records
+    pub fn compare(&self, a: &[SortValue], b: &[SortValue]) -> cmp::Ordering {
+        for (key, (x, y)) in self.keys.iter().zip(a.iter().zip(b)) {
+            match key.compare(x.borrowed(), y.borrowed()) {
+                cmp::Ordering::Equal => (),
+                non_eq => return non_eq,
+            }
+        }
+        cmp::Ordering::Equal
+    }
+
+    /// Encode sort values as a line of text, for extsort
+    pub fn encode_values(values: &[SortValue], line: &mut String) {
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                line.push('\x1f');
+            }
+            match value {
+                SortValue::Null(bytes) => {
+                    line.push('n');
+                    escape_sort_text(bytes, line);
+                },
+                SortValue::Text(bytes) => {
+                    line.push('t');
+                    escape_sort_text(bytes, line);
+                },
+                SortValue::Num(n) => line.push_str(&format!("f{:016x}", n.to_bits())),
+                SortValue::Date(d) => line.push_str(&format!("d{d}")),
+            }
+        }
+    }
+
+    /// Order two lines of sort values encoded by `encode_values`, comparing the
+    /// escaped text in place as the escaping preserves the byte order
+    pub fn compare_encoded(&self, a: &str, b: &str) -> cmp::Ordering {
+        for (key, (x, y)) in self.keys.iter().zip(a.split('\x1f').zip(b.split('\x1f'))) {
+            match key.compare(decode_sort_value(x), decode_sort_value(y)) {
+                cmp::Ordering::Equal => (),
+                non_eq => return non_eq,
+            }
+        }
+        cmp::Ordering::Equal
+    }
+}
+
+impl SortKey {
+    fn compare(&self, x: SortValueRef, y: SortValueRef) -> cmp::Ordering {
+        let (x_null, y_null) = (
+            matches!(x, SortValueRef::Null(_)),
+            matches!(y, SortValueRef::Null(_)),
+        );
+        // explicit nulls placement does not depend on the direction
+        if x_null != y_null && self.nulls != NullsOrder::Smallest {
+            let nulls_first = if x_null {
+                cmp::Ordering::Less
+            } else {
+                cmp::Ordering::Greater
+            };
+            return if self.nulls == NullsOrder::First {
+                nulls_first
+            } else {
+                nulls_first.reverse()
+            };
+        }
+        let ordering = match (x, y) {
+            (SortValueRef::Null(a), SortValueRef::Null(b)) => a.cmp(b),
+            (SortValueRef::Null(_), _) => cmp::Ordering::Less,
+            (_, SortValueRef::Null(_)) => cmp::Ordering::Greater,
+            (SortValueRef::Num(a), SortValueRef::Num(b)) => compare_float(a, b),
+            (SortValueRef::Date(a), SortValueRef::Date(b)) => a.cmp(&b),
+            (SortValueRef::Text(a), SortValueRef::Text(b)) => match self.sort_type {
+                SortType::Natural => natural_cmp(a, b),
+                SortType::Version => version_cmp(a, b),
+                _ => a.cmp(b),
+            },
+            // the values of a sort key always have the same type
+            _ => cmp::Ordering::Equal,
+        };
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// Escape the control characters of a sort value, so the encoded sort values have no
+/// separators or line breaks, but keep the byte order of the values: a control character
+/// becomes '\x01' followed by the character + 0x40, which sorts below all the other
+/// characters, and in the same order as the control characters
+fn escape_sort_text(bytes: &[u8], line: &mut String) {
+    for c in String::from_utf8_lossy(bytes).chars() {
+        if c < ' ' {
+            line.push('\x01');
+            line.push(char::from_u32(u32::from(c) + 0x40).unwrap_or(c));
+        } else {
+            line.push(c);
+        }
+    }
+}
+
+fn decode_sort_value(token: &str) -> SortValueRef<'_> {
+    let (tag, value) = token.split_at(1.min(token.len()));
+    match tag {
+        "t" => SortValueRef::Text(value.as_bytes()),
+        "f" => SortValueRef::Num(f64::from_bits(
+            u64::from_str_radix(value, 16).unwrap_or_default(),
+        )),
+        "d" => SortValueRef::Date(value.parse().unwrap_or_default()),
+        _ => SortValueRef::Null(value.as_bytes()),
+    }
+}
+
+/// Order `a` and `b` in natural order, comparing runs of ASCII digits as numbers
+fn natural_cmp(a: &[u8], b: &[u8]) -> cmp::Ordering {
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].is_ascii_digit() && b[j].is_ascii_digit() {
+            let (start_i, start_j) = (i, j);
+            while i < a.len() && a[i].is_ascii_digit() {
+                i += 1;
+            }
+            while j < b.len() && b[j].is_ascii_digit() {
+                j += 1;
+            }
+            let (run_a, run_b) = (&a[start_i..i], &b[start_j..j]);
+            let num_a = &run_a[run_a.iter().take_while(|&&c| c == b'0').count()..];
+            let num_b = &run_b[run_b.iter().take_while(|&&c| c == b'0').count()..];
+            // a longer number (without leading zeroes) is larger, and for equal
+            // numbers, the one with fewer leading zeroes comes first
+            match num_a
+                .len()
+                .cmp(&num_b.len())
+                .then_with(|| num_a.cmp(num_b))
+                .then_with(|| run_a.len().cmp(&run_b.len()))
+            {
+                cmp::Ordering::Equal => (),
+                non_eq => return non_eq,
+            }
+        } else {
+            match a[i].cmp(&b[j]) {
+                cmp::Ordering::Equal => (),
+                non_eq => return non_eq,
+            }
+            i += 1;
+            j += 1;
+        }
+    }
+    (a.len() - i).cmp(&(b.len() - j))
+}
+
+/// Order `a` and `b` as version numbers, e.g. "v1.9" < "1.10.0" and "2.0.0-rc1" < "2.0.0"
+fn version_cmp(a: &[u8], b: &[u8]) -> cmp::Ordering {
+    let (release_a, pre_a) = split_version(a);
+    let (release_b, pre_b) = split_version(b);
+    let mut parts_a = release_a.split(|&c| c == b'.');
+    let mut parts_b = release_b.split(|&c| c == b'.');
+    let zero: &[u8] = b"0";
+    loop {
+        match (parts_a.next(), parts_b.next()) {
+            (None, None) => break,
+            // missing components are zero, so "1.2" == "1.2.0"
+            (x, y) => match natural_cmp(x.unwrap_or(zero), y.unwrap_or(zero)) {
+                cmp::Ordering::Equal => (),
+                non_eq => return non_eq,
+            },
+        }
+    }
+    match (pre_a, pre_b) {
+        (None, None) => cmp::Ordering::Equal,
+        (Some(_), None) => cmp::Ordering::Less,
+        (None, Some(_)) => cmp::Ordering::Greater,
+        (Some(x), Some(y)) => natural_cmp(x, y),
+    }
+}
+
+/// Split a version number into its release & pre-release parts,
+/// ignoring a leading "v" & the build metadata
+fn split_version(version: &[u8]) -> (&[u8], Option<&[u8]>) {
+    let version = version
+        .strip_prefix(b"v")
+        .or_else(|| version.strip_prefix(b"V"))
+        .unwrap_or(version);
+    let version = version.split(|&c| c == b'+').next().unwrap_or_default();
+    match version.iter().position(|&c| c == b'-') {
+        Some(pos) => (&version[..pos], Some(&version[pos + 1..])),
+        None => (version, None),
+    }
+}
+
+/// A locale-aware collation (`--collation`) of sort, extsort, dedup & sortcheck, using the
+/// Unicode Collation Algorithm with the collation data bundled with qsv
+pub struct Collation {
+    collator: Collator,
+    spec:     String,
+}
+
+impl Collation {
+    /// parses a `--collation` locale, e.g. "de" or "fr-CA", optionally followed by
+    /// ":primary", ":secondary" or ":tertiary" (the default) to set the strength
+    pub fn parse(spec: &str) -> CliResult<Self> {
+        let (locale, strength) = match spec.split_once(':') {
+            Some((locale, strength)) => (locale, Some(strength)),
+            None => (spec, None),
+        };
+        let Ok(locale) = locale.trim().parse::<Locale>() else {
+            return fail_incorrectusage_clierror!(
+                "Invalid --collation locale \"{locale}\". Use a locale like \"de\" or \"fr-CA\"."
+            );
+        };
+        let mut options = CollatorOptions::new();
+        options.strength = Some(
+            match strength.map(|s| s.trim().to_ascii_lowercase()).as_deref() {
+                Some("primary") => Strength::Primary,
+                Some("secondary") => Strength::Secondary,
+                None | Some("tertiary") => Strength::Tertiary,
+                Some(other) => {
+                    return fail_incorrectusage_clierror!(
+                        "Invalid --collation strength \"{other}\". Valid strengths are primary, \
+                         secondary and tertiary."
+                    );
+                },
+            },
+        );
+        let collator = match Collator::try_new(&DataLocale::from(&locale), options) {
+            Ok(collator) => collator,
+            Err(e) => return fail_clierror!("Cannot create collator for \"{spec}\": {e}"),
+        };
+        Ok(Collation {
+            collator,
+            spec: spec.to_string(),
+        })
+    }
+
+    /// Order `a` and `b` per the collation. Invalid UTF-8 is compared as U+FFFD.
+    #[inline]
+    pub fn compare(&self, a: &[u8], b: &[u8]) -> cmp::Ordering {
+        self.collator.compare_utf8(a, b)
+    }
+
+    /// Order `a` and `b` lexicographically per the collation
+    #[inline]
+    pub fn iter_cmp<'a, L, R>(&self, mut a: L, mut b: R) -> cmp::Ordering
+    where
+        L: Iterator<Item = &'a [u8]>,
+        R: Iterator<Item = &'a [u8]>,
+    {
+        loop {
+            match (a.next(), b.next()) {
+                (None, None) => return cmp::Ordering::Equal,
+                (None, _) => return cmp::Ordering::Less,
+                (_, None) => return cmp::Ordering::Greater,
+                (Some(x), Some(y)) => match self.compare(x, y) {
+                    cmp::Ordering::Equal => (),
+                    non_eq => return non_eq,
+                },
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Collation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Collation({})", self.spec)
+    }
+}
@@ -30,6 +30,14 @@ sort options:
     -s, --select <arg>      Select a subset of columns to check for sort.
                             See 'qsv select --help' for the format details.
     -i, --ignore-case       Compare strings disregarding case
+    --collation <locale>    Compare strings using the collation rules of a locale,
+                            e.g. "de" or "de:primary".
+                            See 'qsv sort --help' for the format details.
+                            Cannot be used with --ignore-case or --by.
+    --by <spec>             Check the sort by a typed, multi-key sort specification,
+                            e.g. "date:desc:date,amount:asc:num,name:asc:natural".
+                            See 'qsv sort --help' for the format details.
+                            Cannot be used with --select or --ignore-case.
     --all                   Check all records. Do not stop/short-circuit the check 
                             on the first unsorted record.
     --json                  Return results in JSON format, scanning --all records. 
@@ -62,7 +70,10 @@ use indicatif::{HumanCount, ProgressBar, ProgressDrawTarget};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    cmd::{dedup, sort::iter_cmp},
+    cmd::{
+        dedup,
+        sort::{iter_cmp, Collation, SortSpec},
+    },
     config::{Config, Delimiter},
     select::SelectColumns,
     util, CliResult,
@@ -74,6 +85,8 @@ struct Args {
     arg_input:        Option<String>,
     flag_select:      SelectColumns,
     flag_ignore_case: bool,
+    flag_collation:   Option<String>,
+    flag_by:          Option<String>,
     flag_all:         bool,
     flag_no_headers:  bool,
     flag_delimiter:   Option<Delimiter>,
@@ -93,6 +106,22 @@ struct SortCheckStruct {
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
     let ignore_case = args.flag_ignore_case;
+    if args.flag_by.is_some() && (ignore_case || !args.flag_select.is_empty()) {
+        return fail_incorrectusage_clierror!(
+            "--by cannot be used with --select or --ignore-case."
+        );
+    }
+    let collation = match args.flag_collation {
+        Some(ref locale) => {
+            if ignore_case || args.flag_by.is_some() {
+                return fail_incorrectusage_clierror!(
+                    "--collation cannot be used with --ignore-case or --by."
+                );
+            }
+            Some(Collation::parse(locale)?)
+        },
+        None => None,
+    };
     let rconfig = Config::new(args.arg_input.as_ref())
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
@@ -102,6 +131,15 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let headers = rdr.byte_headers()?.clone();
     let sel = rconfig.selection(&headers)?;
+    let sort_spec = match args.flag_by {
+        Some(ref by) => Some(SortSpec::parse(
+            by,
+            &headers,
+            args.flag_no_headers,
+            rconfig.get_dmy_preference(),
+        )?),
+        None => None,
+    };
     let record_count;
 
     // prep progress bar
@@ -148,7 +186,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         };
         let a = sel.select(&record);
         let b = sel.select(&next_record);
-        let comparison = if ignore_case {
+        let comparison = if let Some(ref spec) = sort_spec {
+            spec.compare(&spec.values(&record), &spec.values(&next_record))
+        } else if let Some(ref collation) = collation {
+            collation.iter_cmp(a, b)
+        } else if ignore_case {
             dedup::iter_cmp_ignore_case(a, b)
         } else {
             iter_cmp(a, b)
@@ -107,7 +107,7 @@ use serde::Deserialize;
 
 use crate::{
     config::{Config, Delimiter},
-    index::Indexed,
+    index::{Indexed, IndexedSource},
     util::{self, FilenameTemplate},
     CliResult,
 };
@@ -266,7 +266,7 @@ impl Args {
         Ok(())
     }
 
-    fn parallel_split(&self, idx: &Indexed<fs::File, fs::File>) -> CliResult<()> {
+    fn parallel_split(&self, idx: &Indexed<IndexedSource, fs::File>) -> CliResult<()> {
         let chunk_size;
         let idx_count = idx.count();
 
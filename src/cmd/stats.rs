@@ -22,6 +22,10 @@ The following additional "non-streaming" statistics require loading the entire f
 cardinality, modes/antimodes, median, MAD, quartiles and its related measures (q1, q2, q3, IQR,
 lower/upper fences & skewness).
 
+Alternatively, the median, MAD, quartiles and percentiles can be estimated in bounded memory with
+the --approx-quantiles option, which keeps a mergeable t-digest sketch per column instead of
+loading the column into memory. This allows computing them for arbitrarily large CSVs.
+
 When computing "non-streaming" statistics, an Out-Of-Memory (OOM) heuristic check is done.
 If the file is larger than the available memory minus a headroom buffer of 20% (which can be
 adjusted using the QSV_FREEMEMORY_HEADROOM_PCT environment variable), processing will be
@@ -88,6 +92,15 @@ Compute all statistics for the "nyc311.csv", inferring dates only for columns wi
 In addition, also infer boolean data types for the "nyc311.csv" file:
     $ qsv stats -E --infer-dates --dates-whitelist _date --infer-boolean nyc311.csv
 
+Compute the approximate median, quartiles and 5th, 95th & 99th percentiles in bounded memory:
+    $ qsv stats --quartiles --percentiles 5,95,99 --approx-quantiles nyc311.csv
+
+Compute the weighted mean, stddev, median & modes of a survey, weighted by its "wgt" column:
+    $ qsv stats --median --mode --weight wgt survey.csv
+
+Compute all statistics for each borough and complaint type of the "nyc311.csv" file:
+    $ qsv stats -E --groupby Borough,"Complaint Type" nyc311.csv
+
 In addition to basis "streaming" stats, also compute the cardinality for the "nyc311.csv" file:
     $ qsv stats --cardinality nyc311.csv
 
@@ -131,6 +144,26 @@ stats options:
                               See 'qsv select --help' for the format details.
                               This is provided here because piping 'qsv select'
                               into 'qsv stats' will prevent the use of indexing.
+    -g, --groupby <cols>      Compute the stats separately for each group of rows with the
+                              same values in the given columns, emitting a stats block per
+                              group, sorted by the group values. The group columns are
+                              prepended to the stats output, and are excluded from the
+                              columns stats are computed for.
+                              The dataset-level qsv__ rows are not emitted, and grouped
+                              stats are never cached.
+                              See 'qsv select --help' for the format details.
+    --weight <col>            Compute weighted statistics, using the numeric values of the
+                              given column as the weight of each row (e.g. survey sampling
+                              weights). The sum, mean, SEM, geometric/harmonic means, stddev,
+                              variance, CV, median, MAD, quartiles, percentiles & modes are
+                              weighted, with mode_occurrences being the sum of the weights.
+                              The SEM uses the effective sample size -
+                              (sum of weights)^2 / sum of squared weights.
+                              The other stats (e.g. min/max, lengths, nullcount & cardinality)
+                              are computed from the unweighted rows.
+                              Empty, non-numeric & negative weights are treated as zero.
+                              The weight column is excluded from the stats output,
+                              and weighted stats are never cached.
     -E, --everything          Compute all statistics available.
     --typesonly               Infer data types only and do not compute statistics.
                               Note that if you want to infer dates, you'll still need to use
@@ -145,6 +178,13 @@ stats options:
     --cardinality             Compute the cardinality.
                               This requires loading CSV data in memory proportionate to the
                               number of unique values in each column.
+    --approx-cardinality      Estimate the cardinality with a HyperLogLog sketch per column,
+                              using at most ~100kb of memory per column. This enables the
+                              --cardinality option, and adds a "cardinality_error" column
+                              with the relative standard error of the estimate (~0.8%).
+                              Cardinalities below 4,096 are counted exactly, with an error of 0.
+                              When --mode or --everything is also used, the column values are
+                              already in memory, so the exact cardinality is returned instead.
     --median                  Compute the median.
                               This requires loading all CSV data in memory.
     --mad                     Compute the median absolute deviation (MAD).
@@ -152,6 +192,17 @@ stats options:
     --quartiles               Compute the quartiles, the IQR, the lower/upper inner/outer
                               fences and skewness.
                               This requires loading all CSV data in memory.
+    --percentiles <list>      Compute the given comma-separated list of percentiles
+                              (0-100, e.g. 5,95,99) using the nearest-rank method.
+                              The values are returned in the "percentiles" column,
+                              separated by QSV_MODES_SEPARATOR (default: |).
+                              This requires loading all CSV data in memory.
+    --approx-quantiles        Estimate the median, MAD, quartiles and percentiles with a
+                              t-digest sketch per column instead of loading all the CSV
+                              data in memory. The estimates are typically within 1% of
+                              the exact values, and are more accurate at the tails.
+                              The sketches are merged across parallel jobs when the CSV
+                              is indexed.
     --round <decimal_places>  Round statistics to <decimal_places>. Rounding is done following
                               Midpoint Nearest Even (aka "Bankers Rounding") rule.
                               https://docs.rs/rust_decimal/latest/rust_decimal/enum.RoundingStrategy.html
@@ -246,6 +297,7 @@ with ~500 tests.
 */
 
 use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
     default::Default,
     fmt, fs, io,
     io::Write,
@@ -255,6 +307,7 @@ use std::{
     sync::OnceLock,
 };
 
+use ahash::AHashMap;
 use crossbeam_channel;
 use itertools::Itertools;
 use qsv_dateparser::parse_with_preference;
@@ -264,6 +317,7 @@ use smallvec::SmallVec;
 use stats::{merge_all, Commute, MinMax, OnlineStats, Unsorted};
 use tempfile::NamedTempFile;
 use threadpool::ThreadPool;
+use xxhash_rust::xxh3::xxh3_64;
 
 use self::FieldType::{TDate, TDateTime, TFloat, TInteger, TNull, TString};
 use crate::{
@@ -275,29 +329,34 @@ use crate::{
 #[allow(clippy::unsafe_derive_deserialize)]
 #[derive(Clone, Deserialize)]
 pub struct Args {
-    pub arg_input:            Option<String>,
-    pub flag_select:          SelectColumns,
-    pub flag_everything:      bool,
-    pub flag_typesonly:       bool,
-    pub flag_infer_boolean:   bool,
-    pub flag_mode:            bool,
-    pub flag_cardinality:     bool,
-    pub flag_median:          bool,
-    pub flag_mad:             bool,
-    pub flag_quartiles:       bool,
-    pub flag_round:           u32,
-    pub flag_nulls:           bool,
-    pub flag_infer_dates:     bool,
-    pub flag_dates_whitelist: String,
-    pub flag_prefer_dmy:      bool,
-    pub flag_force:           bool,
-    pub flag_jobs:            Option<usize>,
-    pub flag_stats_jsonl:     bool,
-    pub flag_cache_threshold: isize,
-    pub flag_output:          Option<String>,
-    pub flag_no_headers:      bool,
-    pub flag_delimiter:       Option<Delimiter>,
-    pub flag_memcheck:        bool,
+    pub arg_input:               Option<String>,
+    pub flag_select:             SelectColumns,
+    pub flag_groupby:            Option<SelectColumns>,
+    pub flag_weight:             Option<SelectColumns>,
+    pub flag_everything:         bool,
+    pub flag_typesonly:          bool,
+    pub flag_infer_boolean:      bool,
+    pub flag_mode:               bool,
+    pub flag_cardinality:        bool,
+    pub flag_approx_cardinality: bool,
+    pub flag_median:             bool,
+    pub flag_mad:                bool,
+    pub flag_quartiles:          bool,
+    pub flag_percentiles:        Option<String>,
+    pub flag_approx_quantiles:   bool,
+    pub flag_round:              u32,
+    pub flag_nulls:              bool,
+    pub flag_infer_dates:        bool,
+    pub flag_dates_whitelist:    String,
+    pub flag_prefer_dmy:         bool,
+    pub flag_force:              bool,
+    pub flag_jobs:               Option<usize>,
+    pub flag_stats_jsonl:        bool,
+    pub flag_cache_threshold:    isize,
+    pub flag_output:             Option<String>,
+    pub flag_no_headers:         bool,
+    pub flag_delimiter:          Option<Delimiter>,
+    pub flag_memcheck:           bool,
 }
 
 // this struct is used to serialize/deserialize the stats to
@@ -305,78 +364,94 @@ pub struct Args {
 // if we can skip recomputing stats.
 #[derive(Clone, Serialize, Deserialize, PartialEq, Default)]
 struct StatsArgs {
-    arg_input:            String,
-    flag_select:          String,
-    flag_everything:      bool,
-    flag_typesonly:       bool,
-    flag_infer_boolean:   bool,
-    flag_mode:            bool,
-    flag_cardinality:     bool,
-    flag_median:          bool,
-    flag_mad:             bool,
-    flag_quartiles:       bool,
-    flag_round:           u32,
-    flag_nulls:           bool,
-    flag_infer_dates:     bool,
-    flag_dates_whitelist: String,
-    flag_prefer_dmy:      bool,
-    flag_no_headers:      bool,
-    flag_delimiter:       String,
-    flag_output_snappy:   bool,
-    canonical_input_path: String,
-    canonical_stats_path: String,
-    record_count:         u64,
-    date_generated:       String,
-    compute_duration_ms:  u64,
-    qsv_version:          String,
+    arg_input:               String,
+    flag_select:             String,
+    flag_everything:         bool,
+    flag_typesonly:          bool,
+    flag_infer_boolean:      bool,
+    flag_mode:               bool,
+    flag_cardinality:        bool,
+    flag_approx_cardinality: bool,
+    flag_median:             bool,
+    flag_mad:                bool,
+    flag_quartiles:          bool,
+    flag_percentiles:        String,
+    flag_approx_quantiles:   bool,
+    flag_weight:             String,
+    flag_round:              u32,
+    flag_nulls:              bool,
+    flag_infer_dates:        bool,
+    flag_dates_whitelist:    String,
+    flag_prefer_dmy:         bool,
+    flag_no_headers:         bool,
+    flag_delimiter:          String,
+    flag_output_snappy:      bool,
+    canonical_input_path:    String,
+    canonical_stats_path:    String,
+    record_count:            u64,
+    date_generated:          String,
+    compute_duration_ms:     u64,
+    qsv_version:             String,
 }
 
 impl StatsArgs {
     // this is for deserializing the stats.csv.jsonl file
     fn from_owned_value(value: &OwnedValue) -> Result<Self, Box<dyn std::error::Error>> {
         Ok(Self {
-            arg_input:            value["arg_input"].as_str().unwrap_or_default().to_string(),
-            flag_select:          value["flag_select"]
+            arg_input:               value["arg_input"].as_str().unwrap_or_default().to_string(),
+            flag_select:             value["flag_select"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            flag_everything:         value["flag_everything"].as_bool().unwrap_or_default(),
+            flag_typesonly:          value["flag_typesonly"].as_bool().unwrap_or_default(),
+            flag_infer_boolean:      value["flag_infer_boolean"].as_bool().unwrap_or_default(),
+            flag_mode:               value["flag_mode"].as_bool().unwrap_or_default(),
+            flag_cardinality:        value["flag_cardinality"].as_bool().unwrap_or_default(),
+            flag_approx_cardinality: value["flag_approx_cardinality"]
+                .as_bool()
+                .unwrap_or_default(),
+            flag_median:             value["flag_median"].as_bool().unwrap_or_default(),
+            flag_mad:                value["flag_mad"].as_bool().unwrap_or_default(),
+            flag_quartiles:          value["flag_quartiles"].as_bool().unwrap_or_default(),
+            flag_percentiles:        value["flag_percentiles"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            flag_approx_quantiles:   value["flag_approx_quantiles"].as_bool().unwrap_or_default(),
+            flag_weight:             value["flag_weight"]
                 .as_str()
                 .unwrap_or_default()
                 .to_string(),
-            flag_everything:      value["flag_everything"].as_bool().unwrap_or_default(),
-            flag_typesonly:       value["flag_typesonly"].as_bool().unwrap_or_default(),
-            flag_infer_boolean:   value["flag_infer_boolean"].as_bool().unwrap_or_default(),
-            flag_mode:            value["flag_mode"].as_bool().unwrap_or_default(),
-            flag_cardinality:     value["flag_cardinality"].as_bool().unwrap_or_default(),
-            flag_median:          value["flag_median"].as_bool().unwrap_or_default(),
-            flag_mad:             value["flag_mad"].as_bool().unwrap_or_default(),
-            flag_quartiles:       value["flag_quartiles"].as_bool().unwrap_or_default(),
-            flag_round:           value["flag_round"].as_u64().unwrap_or_default() as u32,
-            flag_nulls:           value["flag_nulls"].as_bool().unwrap_or_default(),
-            flag_infer_dates:     value["flag_infer_dates"].as_bool().unwrap_or_default(),
-            flag_dates_whitelist: value["flag_dates_whitelist"]
+            flag_round:              value["flag_round"].as_u64().unwrap_or_default() as u32,
+            flag_nulls:              value["flag_nulls"].as_bool().unwrap_or_default(),
+            flag_infer_dates:        value["flag_infer_dates"].as_bool().unwrap_or_default(),
+            flag_dates_whitelist:    value["flag_dates_whitelist"]
                 .as_str()
                 .unwrap_or_default()
                 .to_string(),
-            flag_prefer_dmy:      value["flag_prefer_dmy"].as_bool().unwrap_or_default(),
-            flag_no_headers:      value["flag_no_headers"].as_bool().unwrap_or_default(),
-            flag_delimiter:       value["flag_delimiter"]
+            flag_prefer_dmy:         value["flag_prefer_dmy"].as_bool().unwrap_or_default(),
+            flag_no_headers:         value["flag_no_headers"].as_bool().unwrap_or_default(),
+            flag_delimiter:          value["flag_delimiter"]
                 .as_str()
                 .unwrap_or_default()
                 .to_string(),
-            flag_output_snappy:   value["flag_output_snappy"].as_bool().unwrap_or_default(),
-            canonical_input_path: value["canonical_input_path"]
+            flag_output_snappy:      value["flag_output_snappy"].as_bool().unwrap_or_default(),
+            canonical_input_path:    value["canonical_input_path"]
                 .as_str()
                 .unwrap_or_default()
                 .to_string(),
-            canonical_stats_path: value["canonical_stats_path"]
+            canonical_stats_path:    value["canonical_stats_path"]
                 .as_str()
                 .unwrap_or_default()
                 .to_string(),
-            record_count:         value["record_count"].as_u64().unwrap_or_default(),
-            date_generated:       value["date_generated"]
+            record_count:            value["record_count"].as_u64().unwrap_or_default(),
+            date_generated:          value["date_generated"]
                 .as_str()
                 .unwrap_or_default()
                 .to_string(),
-            compute_duration_ms:  value["compute_duration_ms"].as_u64().unwrap_or_default(),
-            qsv_version:          value["qsv_version"]
+            compute_duration_ms:     value["compute_duration_ms"].as_u64().unwrap_or_default(),
+            qsv_version:             value["qsv_version"]
                 .as_str()
                 .unwrap_or_default()
                 .to_string(),
@@ -423,12 +498,14 @@ pub struct StatsData {
     pub upper_outer_fence:    Option<f64>,
     pub skewness:             Option<f64>,
     pub cardinality:          u64,
+    pub cardinality_error:    Option<f64>,
     pub mode:                 Option<String>,
     pub mode_count:           Option<u64>,
     pub mode_occurrences:     Option<u64>,
     pub antimode:             Option<String>,
     pub antimode_count:       Option<u64>,
     pub antimode_occurrences: Option<u64>,
+    pub percentiles:          Option<String>,
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -440,56 +517,63 @@ pub enum JsonTypes {
 }
 
 // we use this to serialize the StatsData data structure
-// to a JSONL file using serde_json
-const STATSDATA_TYPES_ARRAY: [JsonTypes; MAX_STAT_COLUMNS] = [
-    JsonTypes::String, //field
-    JsonTypes::String, //type
-    JsonTypes::Bool,   //is_ascii
-    JsonTypes::Float,  //sum
-    JsonTypes::String, //min
-    JsonTypes::String, //max
-    JsonTypes::Float,  //range
-    JsonTypes::String, //sort_order
-    JsonTypes::Int,    //min_length
-    JsonTypes::Int,    //max_length
-    JsonTypes::Int,    //sum_length
-    JsonTypes::Float,  //avg_length
-    JsonTypes::Float,  //stddev_length
-    JsonTypes::Float,  //variance_length
-    JsonTypes::Float,  //cv_length
-    JsonTypes::Float,  //mean
-    JsonTypes::Float,  //sem
-    JsonTypes::Float,  //geometric_mean
-    JsonTypes::Float,  //harmonic_mean
-    JsonTypes::Float,  //stddev
-    JsonTypes::Float,  //variance
-    JsonTypes::Float,  //cv
-    JsonTypes::Int,    //nullcount
-    JsonTypes::Int,    //max_precision
-    JsonTypes::Float,  //sparsity
-    JsonTypes::Float,  //mad
-    JsonTypes::Float,  //lower_outer_fence
-    JsonTypes::Float,  //lower_inner_fence
-    JsonTypes::Float,  //q1
-    JsonTypes::Float,  //q2_median
-    JsonTypes::Float,  //q3
-    JsonTypes::Float,  //iqr
-    JsonTypes::Float,  //upper_inner_fence
-    JsonTypes::Float,  //upper_outer_fence
-    JsonTypes::Float,  //skewness
-    JsonTypes::Int,    //cardinality
-    JsonTypes::String, //mode
-    JsonTypes::Int,    //mode_count
-    JsonTypes::Int,    //mode_occurrences
-    JsonTypes::String, //antimode
-    JsonTypes::Int,    //antimode_count
-    JsonTypes::Int,    //antimode_occurrences
+// to a JSONL file using serde_json, looking up the JSON type of each stats column by name
+const STATSDATA_TYPES_ARRAY: [(&str, JsonTypes); MAX_STAT_COLUMNS] = [
+    ("field", JsonTypes::String),
+    ("type", JsonTypes::String),
+    ("is_ascii", JsonTypes::Bool),
+    ("sum", JsonTypes::Float),
+    ("min", JsonTypes::String),
+    ("max", JsonTypes::String),
+    ("range", JsonTypes::Float),
+    ("sort_order", JsonTypes::String),
+    ("min_length", JsonTypes::Int),
+    ("max_length", JsonTypes::Int),
+    ("sum_length", JsonTypes::Int),
+    ("avg_length", JsonTypes::Float),
+    ("stddev_length", JsonTypes::Float),
+    ("variance_length", JsonTypes::Float),
+    ("cv_length", JsonTypes::Float),
+    ("mean", JsonTypes::Float),
+    ("sem", JsonTypes::Float),
+    ("geometric_mean", JsonTypes::Float),
+    ("harmonic_mean", JsonTypes::Float),
+    ("stddev", JsonTypes::Float),
+    ("variance", JsonTypes::Float),
+    ("cv", JsonTypes::Float),
+    ("nullcount", JsonTypes::Int),
+    ("max_precision", JsonTypes::Int),
+    ("sparsity", JsonTypes::Float),
+    ("mad", JsonTypes::Float),
+    ("lower_outer_fence", JsonTypes::Float),
+    ("lower_inner_fence", JsonTypes::Float),
+    ("q1", JsonTypes::Float),
+    ("q2_median", JsonTypes::Float),
+    ("q3", JsonTypes::Float),
+    ("iqr", JsonTypes::Float),
+    ("upper_inner_fence", JsonTypes::Float),
+    ("upper_outer_fence", JsonTypes::Float),
+    ("skewness", JsonTypes::Float),
+    ("cardinality", JsonTypes::Int),
+    ("cardinality_error", JsonTypes::Float),
+    ("mode", JsonTypes::String),
+    ("mode_count", JsonTypes::Int),
+    ("mode_occurrences", JsonTypes::Int),
+    ("antimode", JsonTypes::String),
+    ("antimode_count", JsonTypes::Int),
+    ("antimode_occurrences", JsonTypes::Int),
+    ("percentiles", JsonTypes::String),
 ];
 
+// the row count & stats of each --groupby group, keyed by the group's values
+type GroupedStats = AHashMap<Vec<Vec<u8>>, (u64, Vec<Stats>)>;
+
 static INFER_DATE_FLAGS: OnceLock<SmallVec<[bool; 50]>> = OnceLock::new();
 static RECORD_COUNT: OnceLock<u64> = OnceLock::new();
+static WEIGHT_COLUMN: OnceLock<usize> = OnceLock::new();
 static ANTIMODES_LEN: OnceLock<usize> = OnceLock::new();
 static ANTIMODES_SEPARATOR: OnceLock<String> = OnceLock::new();
+static PERCENTILE_LIST: OnceLock<Vec<f64>> = OnceLock::new();
 
 // standard overflow and underflow strings
 // for sum, sum_length and avg_length
@@ -504,7 +588,7 @@ const MS_IN_DAY_INT: i64 = 86_400_000;
 const DAY_DECIMAL_PLACES: u32 = 5;
 
 // maximum number of output columns
-const MAX_STAT_COLUMNS: usize = 42;
+const MAX_STAT_COLUMNS: usize = 44;
 
 // the first N columns are fingerprint hash columns
 const FINGERPRINT_HASH_COLUMNS: usize = 25;
@@ -515,8 +599,25 @@ const MAX_ANTIMODES: usize = 10;
 const DEFAULT_ANTIMODES_LEN: usize = 100;
 pub const DEFAULT_MODES_SEPARATOR: &str = "|";
 
+// the t-digest compression parameter used by --approx-quantiles.
+// A digest holds about half this number of centroids, regardless of the number of samples.
+const TDIGEST_COMPRESSION: f64 = 200.0;
+// the number of samples buffered before merging them into the t-digest centroids
+const TDIGEST_BUFFER_SIZE: usize = 500;
+
+// the HyperLogLog precision used by --approx-cardinality, i.e. it uses 2^14 registers
+// for a relative standard error of 1.04/sqrt(2^14) = ~0.8%
+const HLL_PRECISION: u32 = 14;
+// the number of distinct values counted exactly, before switching to HyperLogLog registers
+const HLL_SPARSE_MAX: usize = 4096;
+
+// the relative tolerance used to compare the total weights of --weight modes/antimodes,
+// as weights summed in a different order (e.g. across parallel chunks) can differ in
+// their last bits
+const WEIGHT_EPSILON: f64 = 1e-9;
+
 // we do this so this is evaluated at compile-time
-pub const fn get_stats_data_types() -> [JsonTypes; MAX_STAT_COLUMNS] {
+pub const fn get_stats_data_types() -> [(&'static str, JsonTypes); MAX_STAT_COLUMNS] {
     STATSDATA_TYPES_ARRAY
 }
 
@@ -529,6 +630,23 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         args.flag_median = false;
         args.flag_quartiles = false;
         args.flag_mad = false;
+        args.flag_percentiles = None;
+    }
+
+    if let Some(ref percentiles) = args.flag_percentiles {
+        let mut percentile_list = Vec::new();
+        for p in percentiles.split(',') {
+            match p.trim().parse::<f64>() {
+                Ok(p) if (0.0..=100.0).contains(&p) => percentile_list.push(p),
+                _ => {
+                    return fail_incorrectusage_clierror!(
+                        "Invalid percentile \"{p}\". Percentiles must be between 0 and 100."
+                    );
+                },
+            }
+        }
+        // safety: PERCENTILE_LIST is only set here
+        PERCENTILE_LIST.set(percentile_list).unwrap();
     }
 
     // inferring boolean requires inferring cardinality
@@ -536,6 +654,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         args.flag_cardinality = true;
     }
 
+    // estimating the cardinality is still computing the cardinality
+    if args.flag_approx_cardinality {
+        args.flag_cardinality = true;
+    }
+
     // check prefer_dmy env var
     args.flag_prefer_dmy = args.flag_prefer_dmy || util::get_envvar_flag("QSV_PREFER_DMY");
 
@@ -545,41 +668,49 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     // save the current args, we'll use it to generate
     // the stats.csv.json file
     let mut current_stats_args = StatsArgs {
-        arg_input:            format!("{:?}", args.arg_input),
-        flag_select:          format!("{:?}", args.flag_select),
-        flag_everything:      args.flag_everything,
-        flag_typesonly:       args.flag_typesonly,
-        flag_infer_boolean:   args.flag_infer_boolean,
-        flag_mode:            args.flag_mode,
-        flag_cardinality:     args.flag_cardinality,
-        flag_median:          args.flag_median,
-        flag_mad:             args.flag_mad,
-        flag_quartiles:       args.flag_quartiles,
-        flag_round:           args.flag_round,
-        flag_nulls:           args.flag_nulls,
-        flag_infer_dates:     args.flag_infer_dates,
-        flag_dates_whitelist: args.flag_dates_whitelist.clone(),
-        flag_prefer_dmy:      args.flag_prefer_dmy,
-        flag_no_headers:      args.flag_no_headers,
-        flag_delimiter:       format!("{:?}", args.flag_delimiter.clone()),
+        arg_input:               format!("{:?}", args.arg_input),
+        flag_select:             format!("{:?}", args.flag_select),
+        flag_everything:         args.flag_everything,
+        flag_typesonly:          args.flag_typesonly,
+        flag_infer_boolean:      args.flag_infer_boolean,
+        flag_mode:               args.flag_mode,
+        flag_cardinality:        args.flag_cardinality,
+        flag_approx_cardinality: args.flag_approx_cardinality,
+        flag_median:             args.flag_median,
+        flag_mad:                args.flag_mad,
+        flag_quartiles:          args.flag_quartiles,
+        flag_percentiles:        args.flag_percentiles.clone().unwrap_or_default(),
+        flag_approx_quantiles:   args.flag_approx_quantiles,
+        flag_weight:             args
+            .flag_weight
+            .as_ref()
+            .map(|weight| format!("{weight:?}"))
+            .unwrap_or_default(),
+        flag_round:              args.flag_round,
+        flag_nulls:              args.flag_nulls,
+        flag_infer_dates:        args.flag_infer_dates,
+        flag_dates_whitelist:    args.flag_dates_whitelist.clone(),
+        flag_prefer_dmy:         args.flag_prefer_dmy,
+        flag_no_headers:         args.flag_no_headers,
+        flag_delimiter:          format!("{:?}", args.flag_delimiter.clone()),
         // when we write to stdout, we don't use snappy compression
         // when we write to a file with the --output option, we use
         // snappy compression if the file ends with ".sz"
-        flag_output_snappy:   if stdout_output_flag {
+        flag_output_snappy:      if stdout_output_flag {
             false
         } else {
             let p = args.flag_output.clone().unwrap();
             p.to_ascii_lowercase().ends_with(".sz")
         },
-        canonical_input_path: String::new(),
-        canonical_stats_path: String::new(),
-        record_count:         0,
-        date_generated:       String::new(),
-        compute_duration_ms:  0,
+        canonical_input_path:    String::new(),
+        canonical_stats_path:    String::new(),
+        record_count:            0,
+        date_generated:          String::new(),
+        compute_duration_ms:     0,
         // save the qsv version in the stats.csv.json file
         // so cached stats are automatically invalidated
         // when the qsv version changes
-        qsv_version:          env!("CARGO_PKG_VERSION").to_string(),
+        qsv_version:             env!("CARGO_PKG_VERSION").to_string(),
     };
 
     // create a temporary file to store the <FILESTEM>.stats.csv file
@@ -638,6 +769,17 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     }
 
+    // grouped stats are written directly to the output, bypassing the stats cache,
+    // as the cache holds the stats of the whole file
+    if let Some(ref groupby) = args.flag_groupby {
+        let result = args.grouped_stats(&rconfig, groupby);
+        if let Some(pb) = stdin_tempfile_path {
+            // remove the temp file we created to store stdin
+            std::fs::remove_file(pb)?;
+        }
+        return result;
+    }
+
     let mut compute_stats = true;
     let mut create_cache = args.flag_cache_threshold == 1
         || args.flag_stats_jsonl
@@ -737,6 +879,13 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                         && existing_stats_args_json.flag_delimiter
                             == current_stats_args.flag_delimiter
                         && existing_stats_args_json.flag_nulls == current_stats_args.flag_nulls
+                        && existing_stats_args_json.flag_percentiles
+                            == current_stats_args.flag_percentiles
+                        && existing_stats_args_json.flag_approx_quantiles
+                            == current_stats_args.flag_approx_quantiles
+                        && existing_stats_args_json.flag_approx_cardinality
+                            == current_stats_args.flag_approx_cardinality
+                        && existing_stats_args_json.flag_weight == current_stats_args.flag_weight
                         && existing_stats_args_json.qsv_version == current_stats_args.qsv_version)
             {
                 log::info!(
@@ -756,13 +905,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             let start_time = std::time::Instant::now();
 
             // we're loading the entire file into memory, we need to check avail mem
-            if args.flag_everything
-                || args.flag_mode
-                || args.flag_cardinality
-                || args.flag_median
-                || args.flag_quartiles
-                || args.flag_mad
-            {
+            if args.loads_data_in_memory() {
                 util::mem_file_check(&path, false, args.flag_memcheck)?;
             }
 
@@ -794,7 +937,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 },
             }?;
 
-            let stats_sr_vec = args.stats_to_records(stats);
+            let stats_sr_vec = args.stats_to_records(stats, *record_count);
             let mut work_br;
 
             // vec we use to compute dataset-level fingerprint hash
@@ -904,8 +1047,10 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     }
 
-    // ensure create_cache is false if the user specified --cache-threshold 0
-    if args.flag_cache_threshold == 0 {
+    // ensure create_cache is false if the user specified --cache-threshold 0.
+    // Weighted stats are not cached either, as the commands that use the stats cache
+    // expect the unweighted stats of every column
+    if args.flag_cache_threshold == 0 || args.flag_weight.is_some() {
         create_cache = false;
     };
 
@@ -1018,6 +1163,20 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 }
 
 impl Args {
+    /// returns true if the requested stats require loading the column values in memory.
+    /// With --approx-quantiles, median/MAD/quartiles/percentiles use bounded memory,
+    /// and with --approx-cardinality, so does cardinality.
+    fn loads_data_in_memory(&self) -> bool {
+        self.flag_everything
+            || self.flag_mode
+            || (self.flag_cardinality && !self.flag_approx_cardinality)
+            || (!self.flag_approx_quantiles
+                && (self.flag_median
+                    || self.flag_quartiles
+                    || self.flag_mad
+                    || self.flag_percentiles.is_some()))
+    }
+
     fn sequential_stats(&self, whitelist: &str) -> CliResult<(csv::ByteRecord, Vec<Stats>)> {
         let mut rdr = self.rconfig().reader()?;
         let (headers, sel) = self.sel_headers(&mut rdr)?;
@@ -1076,7 +1235,137 @@ impl Args {
         Ok((headers, merge_all(recv.iter()).unwrap_or_default()))
     }
 
-    fn stats_to_records(&self, stats: Vec<Stats>) -> Vec<csv::StringRecord> {
+    /// computes the stats of each group of rows with the same --groupby values, and writes
+    /// them to the output, with the group values prepended to each stats record
+    fn grouped_stats(&self, rconfig: &Config, groupby: &SelectColumns) -> CliResult<()> {
+        if let Some(path) = rconfig.path.as_ref() {
+            if self.loads_data_in_memory() {
+                util::mem_file_check(path, false, self.flag_memcheck)?;
+            }
+        }
+
+        let mut rdr = rconfig.reader()?;
+        let all_headers = rdr.byte_headers()?.clone();
+        let groupby_sel = groupby.selection(&all_headers, !self.flag_no_headers)?;
+        let sel = self.exclude_weight_column(
+            &all_headers,
+            rconfig.selection(&all_headers)?.without(&groupby_sel),
+        )?;
+        if sel.is_empty() {
+            return fail_incorrectusage_clierror!(
+                "There are no columns to compute stats for after excluding the --groupby and \
+                 --weight columns."
+            );
+        }
+        let headers: csv::ByteRecord = sel.select(&all_headers).collect();
+
+        init_date_inference(self.flag_infer_dates, &headers, &self.flag_dates_whitelist)?;
+
+        let groups = match rconfig.indexed()? {
+            Some(idx) if idx.count() > 0 && self.flag_jobs != Some(1) => {
+                self.parallel_grouped_stats(&sel, &groupby_sel, idx.count())
+            },
+            _ => self.compute_grouped(&sel, &groupby_sel, rdr.byte_records()),
+        };
+        let mut groups: Vec<_> = groups.into_iter().collect();
+        groups.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        let stats_headers = self.stats_headers();
+        // there are no dataset-level stats rows, so we skip the qsv__value column
+        let num_stats_fields = stats_headers.len() - usize::from(!self.flag_typesonly);
+
+        let mut wtr = Config::new(self.flag_output.as_ref()).writer()?;
+        let mut record = csv::ByteRecord::with_capacity(1024, groupby_sel.len() + num_stats_fields);
+        for (i, name) in groupby_sel.iter().zip(groupby_sel.select(&all_headers)) {
+            if self.flag_no_headers {
+                record.push_field(i.to_string().as_bytes());
+            } else {
+                record.push_field(name);
+            }
+        }
+        for field in stats_headers.iter().take(num_stats_fields) {
+            record.push_field(field.as_bytes());
+        }
+        wtr.write_byte_record(&record)?;
+
+        let round_places = self.flag_round;
+        let infer_boolean = self.flag_infer_boolean;
+        for (key, (record_count, stats)) in groups {
+            for ((i, header), mut stat) in sel.iter().zip(headers.iter()).zip(stats) {
+                record.clear();
+                for group_value in &key {
+                    record.push_field(group_value);
+                }
+                if self.flag_no_headers {
+                    record.push_field(i.to_string().as_bytes());
+                } else {
+                    record.push_field(header);
+                }
+                let stat_record = stat.to_record(round_places, infer_boolean, record_count);
+                for piece in stat_record.iter().take(num_stats_fields - 1) {
+                    record.push_field(piece.as_bytes());
+                }
+                wtr.write_byte_record(&record)?;
+            }
+        }
+        Ok(wtr.flush()?)
+    }
+
+    fn parallel_grouped_stats(
+        &self,
+        sel: &Selection,
+        groupby_sel: &Selection,
+        idx_count: u64,
+    ) -> GroupedStats {
+        let njobs = util::njobs(self.flag_jobs);
+        let chunk_size = util::chunk_size(idx_count as usize, njobs);
+        let nchunks = util::num_of_chunks(idx_count as usize, chunk_size);
+
+        let pool = ThreadPool::new(njobs);
+        let (send, recv) = crossbeam_channel::bounded(0);
+        for i in 0..nchunks {
+            let (send, args, sel, groupby_sel) =
+                (send.clone(), self.clone(), sel.clone(), groupby_sel.clone());
+            pool.execute(move || {
+                // safety: see parallel_stats
+                let mut idx = unsafe {
+                    args.rconfig()
+                        .indexed()
+                        .unwrap_unchecked()
+                        .unwrap_unchecked()
+                };
+                idx.seek((i * chunk_size) as u64)
+                    .expect("File seek failed.");
+                let it = idx.byte_records().take(chunk_size);
+                // safety: this will only return an Error if the channel has been disconnected
+                unsafe {
+                    send.send(args.compute_grouped(&sel, &groupby_sel, it))
+                        .unwrap_unchecked();
+                }
+            });
+        }
+        drop(send);
+
+        // merge the stats of the groups found in each chunk
+        let mut groups = GroupedStats::new();
+        for chunk_groups in &recv {
+            for (key, (record_count, stats)) in chunk_groups {
+                match groups.entry(key) {
+                    Entry::Occupied(mut e) => {
+                        let (total_count, merged_stats) = e.get_mut();
+                        *total_count += record_count;
+                        merged_stats.merge(stats);
+                    },
+                    Entry::Vacant(e) => {
+                        e.insert((record_count, stats));
+                    },
+                }
+            }
+        }
+        groups
+    }
+
+    fn stats_to_records(&self, stats: Vec<Stats>, record_count: u64) -> Vec<csv::StringRecord> {
         let round_places = self.flag_round;
         let infer_boolean = self.flag_infer_boolean;
         let mut records = Vec::with_capacity(stats.len());
@@ -1089,7 +1378,7 @@ impl Args {
             pool.execute(move || {
                 // safety: this will only return an Error if the channel has been disconnected
                 // which will not happen in this case
-                send.send(stat.to_record(round_places, infer_boolean))
+                send.send(stat.to_record(round_places, infer_boolean, record_count))
                     .unwrap();
             });
         }
@@ -1119,8 +1408,10 @@ impl Args {
         // and hopefully the compiler will optimize this and use registers in the hot loop
         let infer_boolean = self.flag_infer_boolean;
         let prefer_dmy = self.flag_prefer_dmy;
+        let weight_column = WEIGHT_COLUMN.get().copied();
 
         let mut i;
+        let mut weight;
         #[allow(unused_assignments)]
         let mut current_row = csv::ByteRecord::with_capacity(1024, sel_len);
         for row in it {
@@ -1129,9 +1420,11 @@ impl Args {
             // we know we don't need to bounds check
             unsafe {
                 current_row = row.unwrap_unchecked();
+                weight = weight_column.map_or(1.0, |w| util::parse_weight(&current_row[w]));
                 for field in sel.select(&current_row) {
                     stats.get_unchecked_mut(i).add(
                         field,
+                        weight,
                         *infer_date_flags.get_unchecked(i),
                         infer_boolean,
                         prefer_dmy,
@@ -1143,16 +1436,71 @@ impl Args {
         stats
     }
 
+    #[inline]
+    fn compute_grouped<I>(&self, sel: &Selection, groupby_sel: &Selection, it: I) -> GroupedStats
+    where
+        I: Iterator<Item = csv::Result<csv::ByteRecord>>,
+    {
+        let group_stats = self.new_stats(sel.len());
+
+        // safety: we know INFER_DATE_FLAGS is Some because we called init_date_inference
+        let infer_date_flags = INFER_DATE_FLAGS.get().unwrap();
+        let infer_boolean = self.flag_infer_boolean;
+        let prefer_dmy = self.flag_prefer_dmy;
+        let weight_column = WEIGHT_COLUMN.get().copied();
+
+        let mut groups = GroupedStats::new();
+        let mut key: Vec<Vec<u8>> = Vec::with_capacity(groupby_sel.len());
+        for row in it {
+            // safety: see compute
+            let row = unsafe { row.unwrap_unchecked() };
+            key.clear();
+            key.extend(groupby_sel.select(&row).map(<[u8]>::to_vec));
+            if !groups.contains_key(&key) {
+                groups.insert(key.clone(), (0, group_stats.clone()));
+            }
+            // safety: we inserted the group above if it didn't exist
+            let (record_count, stats) = unsafe { groups.get_mut(&key).unwrap_unchecked() };
+            *record_count += 1;
+            let weight = weight_column.map_or(1.0, |w| util::parse_weight(&row[w]));
+            for ((stat, field), infer_dates) in
+                stats.iter_mut().zip(sel.select(&row)).zip(infer_date_flags)
+            {
+                stat.add(field, weight, *infer_dates, infer_boolean, prefer_dmy);
+            }
+        }
+        groups
+    }
+
     #[inline]
     fn sel_headers<R: io::Read>(
         &self,
         rdr: &mut csv::Reader<R>,
     ) -> CliResult<(csv::ByteRecord, Selection)> {
         let headers = rdr.byte_headers()?.clone();
-        let sel = self.rconfig().selection(&headers)?;
+        let sel = self.exclude_weight_column(&headers, self.rconfig().selection(&headers)?)?;
         Ok((sel.select(&headers).collect(), sel))
     }
 
+    /// excludes the --weight column from the stats selection,
+    /// and saves its index in WEIGHT_COLUMN to compute the weighted stats
+    fn exclude_weight_column(
+        &self,
+        headers: &csv::ByteRecord,
+        sel: Selection,
+    ) -> CliResult<Selection> {
+        let Some(ref weight) = self.flag_weight else {
+            return Ok(sel);
+        };
+        let weight_sel = weight.selection(headers, !self.flag_no_headers)?;
+        if weight_sel.len() != 1 {
+            return fail_incorrectusage_clierror!("--weight must select exactly one column.");
+        }
+        // the weight column is the same for all the jobs, so it's OK if it's already set
+        let _ = WEIGHT_COLUMN.set(weight_sel[0]);
+        Ok(sel.without(&weight_sel))
+    }
+
     #[inline]
     fn rconfig(&self) -> Config {
         Config::new(self.arg_input.as_ref())
@@ -1166,16 +1514,22 @@ impl Args {
         let mut stats: Vec<Stats> = Vec::with_capacity(record_len);
         stats.extend(
             repeat(Stats::new(WhichStats {
-                include_nulls: self.flag_nulls,
-                sum:           !self.flag_typesonly,
-                range:         !self.flag_typesonly || self.flag_infer_boolean,
-                dist:          !self.flag_typesonly,
-                cardinality:   self.flag_everything || self.flag_cardinality,
-                median:        !self.flag_everything && self.flag_median && !self.flag_quartiles,
-                mad:           self.flag_everything || self.flag_mad,
-                quartiles:     self.flag_everything || self.flag_quartiles,
-                mode:          self.flag_everything || self.flag_mode,
-                typesonly:     self.flag_typesonly,
+                include_nulls:      self.flag_nulls,
+                sum:                !self.flag_typesonly,
+                range:              !self.flag_typesonly || self.flag_infer_boolean,
+                dist:               !self.flag_typesonly,
+                cardinality:        self.flag_everything || self.flag_cardinality,
+                approx_cardinality: self.flag_approx_cardinality,
+                median:             !self.flag_everything
+                    && self.flag_median
+                    && !self.flag_quartiles,
+                mad:                self.flag_everything || self.flag_mad,
+                quartiles:          self.flag_everything || self.flag_quartiles,
+                percentiles:        self.flag_percentiles.is_some(),
+                approx:             self.flag_approx_quantiles,
+                weighted:           self.flag_weight.is_some(),
+                mode:               self.flag_everything || self.flag_mode,
+                typesonly:          self.flag_typesonly,
             }))
             .take(record_len),
         );
@@ -1245,6 +1599,9 @@ impl Args {
         if self.flag_cardinality || everything {
             fields.push("cardinality");
         }
+        if self.flag_approx_cardinality {
+            fields.push("cardinality_error");
+        }
         if self.flag_mode || everything {
             fields.extend_from_slice(&[
                 "mode",
@@ -1255,6 +1612,9 @@ impl Args {
                 "antimode_occurrences",
             ]);
         }
+        if self.flag_percentiles.is_some() {
+            fields.push("percentiles");
+        }
 
         // we add the qsv__value field at the end for dataset-level stats
         fields.push("qsv__value");
@@ -1325,16 +1685,20 @@ fn init_date_inference(
 
 #[derive(Clone, Debug, Eq, PartialEq, Default, Serialize, Deserialize)]
 struct WhichStats {
-    include_nulls: bool,
-    sum:           bool,
-    range:         bool,
-    dist:          bool,
-    cardinality:   bool,
-    median:        bool,
-    mad:           bool,
-    quartiles:     bool,
-    mode:          bool,
-    typesonly:     bool,
+    include_nulls:      bool,
+    sum:                bool,
+    range:              bool,
+    dist:               bool,
+    cardinality:        bool,
+    approx_cardinality: bool,
+    median:             bool,
+    mad:                bool,
+    quartiles:          bool,
+    percentiles:        bool,
+    approx:             bool,
+    weighted:           bool,
+    mode:               bool,
+    typesonly:          bool,
 }
 
 impl Commute for WhichStats {
@@ -1347,20 +1711,27 @@ impl Commute for WhichStats {
 #[allow(clippy::unsafe_derive_deserialize)]
 #[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub struct Stats {
-    typ:           FieldType,
-    is_ascii:      bool,
-    sum:           Option<TypedSum>,
-    sum_stotlen:   u64,
-    minmax:        Option<TypedMinMax>,
-    online:        Option<OnlineStats>,
-    online_len:    Option<OnlineStats>,
-    nullcount:     u64,
-    max_precision: u16,
-    modes:         Option<Unsorted<Vec<u8>>>,
-    median:        Option<Unsorted<f64>>,
-    mad:           Option<Unsorted<f64>>,
-    quartiles:     Option<Unsorted<f64>>,
-    which:         WhichStats,
+    typ:              FieldType,
+    is_ascii:         bool,
+    sum:              Option<TypedSum>,
+    sum_stotlen:      u64,
+    minmax:           Option<TypedMinMax>,
+    online:           Option<OnlineStats>,
+    online_len:       Option<OnlineStats>,
+    nullcount:        u64,
+    max_precision:    u16,
+    modes:            Option<Unsorted<Vec<u8>>>,
+    hll:              Option<HyperLogLog>,
+    median:           Option<Unsorted<f64>>,
+    mad:              Option<Unsorted<f64>>,
+    quartiles:        Option<Unsorted<f64>>,
+    percentiles:      Option<Samples>,
+    tdigest:          Option<TDigest>,
+    // the weighted stats, computed instead of their unweighted counterparts with --weight
+    weighted_online:  Option<WeightedOnlineStats>,
+    weighted_samples: Option<WeightedSamples>,
+    weighted_modes:   Option<WeightedModes>,
+    which:            WhichStats,
 }
 
 #[inline]
@@ -1385,10 +1756,18 @@ impl Stats {
             mut online,
             mut online_len,
             mut modes,
+            mut hll,
             mut median,
             mut quartiles,
             mut mad,
-        ) = (None, None, None, None, None, None, None, None);
+            mut percentiles,
+            mut tdigest,
+            mut weighted_online,
+            mut weighted_samples,
+            mut weighted_modes,
+        ) = (
+            None, None, None, None, None, None, None, None, None, None, None, None, None, None,
+        );
         if which.sum {
             sum = Some(TypedSum::default());
         }
@@ -1396,19 +1775,44 @@ impl Stats {
             minmax = Some(TypedMinMax::default());
         }
         if which.dist {
-            online = Some(stats::OnlineStats::default());
+            if which.weighted {
+                weighted_online = Some(WeightedOnlineStats::default());
+            } else {
+                online = Some(stats::OnlineStats::default());
+            }
             online_len = Some(stats::OnlineStats::default());
         }
-        if which.mode || which.cardinality {
+        if which.mode || (which.cardinality && !which.approx_cardinality) {
             modes = Some(stats::Unsorted::default());
+        } else if which.approx_cardinality {
+            hll = Some(HyperLogLog::default());
         }
-        if which.quartiles {
-            quartiles = Some(stats::Unsorted::default());
-        } else if which.median {
-            median = Some(stats::Unsorted::default());
+        if which.mode && which.weighted {
+            weighted_modes = Some(WeightedModes::default());
         }
-        if which.mad {
-            mad = Some(stats::Unsorted::default());
+        if which.approx {
+            // a single t-digest sketch estimates all the quantile-based stats
+            if which.median || which.mad || which.quartiles || which.percentiles {
+                tdigest = Some(TDigest::default());
+            }
+        } else if which.weighted {
+            // the weighted samples compute all the quantile-based stats
+            if which.median || which.mad || which.quartiles || which.percentiles {
+                weighted_samples = Some(WeightedSamples::default());
+            }
+        } else {
+            if which.percentiles {
+                // the samples of --percentiles also compute the median & quartiles
+                // (see to_record), so the values of a column are only kept once
+                percentiles = Some(Samples::default());
+            } else if which.quartiles {
+                quartiles = Some(stats::Unsorted::default());
+            } else if which.median {
+                median = Some(stats::Unsorted::default());
+            }
+            if which.mad {
+                mad = Some(stats::Unsorted::default());
+            }
         }
         Stats {
             typ: FieldType::default(),
@@ -1421,15 +1825,28 @@ impl Stats {
             nullcount: 0,
             max_precision: 0,
             modes,
+            hll,
             median,
             mad,
             quartiles,
+            percentiles,
+            tdigest,
+            weighted_online,
+            weighted_samples,
+            weighted_modes,
             which,
         }
     }
 
     #[inline]
-    fn add(&mut self, sample: &[u8], infer_dates: bool, infer_boolean: bool, prefer_dmy: bool) {
+    fn add(
+        &mut self,
+        sample: &[u8],
+        weight: f64,
+        infer_dates: bool,
+        infer_boolean: bool,
+        prefer_dmy: bool,
+    ) {
         let (sample_type, timestamp_val) =
             FieldType::from_sample(infer_dates, prefer_dmy, sample, self.typ);
         self.typ.merge(sample_type);
@@ -1455,6 +1872,12 @@ impl Stats {
         if let Some(v) = self.modes.as_mut() {
             v.add(sample.to_vec());
         };
+        if let Some(v) = self.hll.as_mut() {
+            v.add(sample);
+        };
+        if let Some(v) = self.weighted_modes.as_mut() {
+            v.add(sample, weight);
+        };
         if sample_type == TNull {
             self.nullcount += 1;
         }
@@ -1471,6 +1894,9 @@ impl Stats {
                         if let Some(v) = self.online.as_mut() {
                             v.add_null();
                         };
+                        if let Some(v) = self.weighted_online.as_mut() {
+                            v.add_null(weight);
+                        };
                     }
                 } else {
                     // safety: we know the sample is a valid f64, so we can use unwrap
@@ -1484,9 +1910,21 @@ impl Stats {
                     if let Some(v) = self.quartiles.as_mut() {
                         v.add(n);
                     }
+                    if let Some(v) = self.percentiles.as_mut() {
+                        v.add(n);
+                    }
+                    if let Some(v) = self.tdigest.as_mut() {
+                        v.add(n, weight);
+                    }
                     if let Some(v) = self.online.as_mut() {
                         v.add(&n);
                     }
+                    if let Some(v) = self.weighted_online.as_mut() {
+                        v.add(n, weight);
+                    }
+                    if let Some(v) = self.weighted_samples.as_mut() {
+                        v.add(n, weight);
+                    }
                     if t == TFloat {
                         let mut ryu_buffer = ryu::Buffer::new();
                         // safety: we know that n is a valid f64
@@ -1514,6 +1952,9 @@ impl Stats {
                     if let Some(v) = self.online.as_mut() {
                         v.add_null();
                     };
+                    if let Some(v) = self.weighted_online.as_mut() {
+                        v.add_null(weight);
+                    };
                 }
             },
             TDateTime | TDate => {
@@ -1522,6 +1963,9 @@ impl Stats {
                         if let Some(v) = self.online.as_mut() {
                             v.add_null();
                         };
+                        if let Some(v) = self.weighted_online.as_mut() {
+                            v.add_null(weight);
+                        };
                     }
                 // if ts_val.is_some() then we successfully inferred a date from the sample
                 // and the timestamp value is not None
@@ -1539,16 +1983,54 @@ impl Stats {
                     if let Some(v) = self.quartiles.as_mut() {
                         v.add(n);
                     }
+                    if let Some(v) = self.percentiles.as_mut() {
+                        v.add(n);
+                    }
+                    if let Some(v) = self.tdigest.as_mut() {
+                        v.add(n, weight);
+                    }
                     if let Some(v) = self.online.as_mut() {
                         v.add(&n);
                     }
+                    if let Some(v) = self.weighted_online.as_mut() {
+                        v.add(n, weight);
+                    }
+                    if let Some(v) = self.weighted_samples.as_mut() {
+                        v.add(n, weight);
+                    }
                 }
             },
         }
     }
 
+    /// returns the (mean, sem, geometric_mean, harmonic_mean, stddev, variance)
+    /// of the samples, which are weighted with --weight
+    fn moments(&self) -> Option<(f64, f64, f64, f64, f64, f64)> {
+        if let Some(v) = self.weighted_online.as_ref() {
+            return v.moments();
+        }
+        self.online.as_ref().map(|v| {
+            let std_dev = v.stddev();
+            #[allow(clippy::cast_precision_loss)]
+            let sem = std_dev / (v.len() as f64).sqrt();
+            (
+                v.mean(),
+                sem,
+                v.geometric_mean(),
+                v.harmonic_mean(),
+                std_dev,
+                v.variance(),
+            )
+        })
+    }
+
     #[allow(clippy::wrong_self_convention)]
-    pub fn to_record(&mut self, round_places: u32, infer_boolean: bool) -> csv::StringRecord {
+    pub fn to_record(
+        &mut self,
+        round_places: u32,
+        infer_boolean: bool,
+        record_count: u64,
+    ) -> csv::StringRecord {
         // we're doing typesonly and not inferring boolean, just return the type
         if self.which.typesonly && !infer_boolean {
             return csv::StringRecord::from(vec![self.typ.to_string()]);
@@ -1586,19 +2068,24 @@ impl Stats {
             minmax_range_sortorder_pieces.extend_from_slice(&[empty(), empty(), empty(), empty()]);
         }
 
-        let record_count = *RECORD_COUNT.get().unwrap_or(&1);
-
         // modes/antimodes & cardinality
         // we do this second because we can use the sort order with cardinality, to skip sorting
         // if its not required. This makes not only cardinality computation faster, it also makes
         // modes/antimodes computation faster.
         // We also need to know the cardinality to --infer-boolean should that be enabled
         let mut cardinality = 0;
-        let mut mc_pieces = Vec::with_capacity(7);
+        let mut mc_pieces = Vec::with_capacity(8);
         match self.modes.as_mut() {
             None => {
-                if self.which.cardinality {
+                if let Some(v) = self.hll.as_ref() {
+                    cardinality = v.estimate();
+                    mc_pieces.push(itoa::Buffer::new().format(cardinality).to_owned());
+                    mc_pieces.push(util::round_num(v.relative_error(), round_places));
+                } else if self.which.cardinality {
                     mc_pieces.push(empty());
+                    if self.which.approx_cardinality {
+                        mc_pieces.push(empty());
+                    }
                 }
                 if self.which.mode {
                     mc_pieces.extend_from_slice(&[empty(), empty(), empty(), empty()]);
@@ -1608,6 +2095,10 @@ impl Stats {
                 if self.which.cardinality {
                     cardinality = v.cardinality(column_sorted, 1);
                     mc_pieces.push(itoa::Buffer::new().format(cardinality).to_owned());
+                    if self.which.approx_cardinality {
+                        // the exact cardinality has no error
+                        mc_pieces.push("0".to_string());
+                    }
                 }
                 if self.which.mode {
                     // get the modes separator
@@ -1616,12 +2107,23 @@ impl Stats {
                             .unwrap_or_else(|_| DEFAULT_MODES_SEPARATOR.to_string())
                     });
 
+                    // with --weight, the values are unique, but their weights are not,
+                    // so we can't short-circuit the modes/antimodes calculation
+                    let weighted_modes = self.weighted_modes.as_ref();
+
                     // mode/s
-                    if cardinality == record_count {
+                    if cardinality == record_count && weighted_modes.is_none() {
                         // all values unique, short-circuit modes calculation as there is none
                         mc_pieces.extend_from_slice(&[empty(), "0".to_string(), "0".to_string()]);
                     } else {
-                        let (modes_result, modes_count, mode_occurrences) = v.modes();
+                        let (modes_result, modes_count, mode_occurrences) =
+                            if let Some(wm) = weighted_modes {
+                                let (modes, count, weight) = wm.modes();
+                                (modes, count, util::round_num(weight, round_places))
+                            } else {
+                                let (modes, count, occurrences) = v.modes();
+                                (modes, count, occurrences.to_string())
+                            };
                         let modes_list = modes_result
                             .iter()
                             .map(|c| String::from_utf8_lossy(c))
@@ -1629,12 +2131,12 @@ impl Stats {
                         mc_pieces.extend_from_slice(&[
                             modes_list,
                             modes_count.to_string(),
-                            mode_occurrences.to_string(),
+                            mode_occurrences,
                         ]);
                     }
 
                     // antimode/s
-                    if cardinality == record_count {
+                    if cardinality == record_count && weighted_modes.is_none() {
                         // all the values are unique
                         // so instead of returning everything, just say *ALL
                         mc_pieces.extend_from_slice(&[
@@ -1644,7 +2146,13 @@ impl Stats {
                         ]);
                     } else {
                         let (antimodes_result, antimodes_count, antimode_occurrences) =
-                            v.antimodes();
+                            if let Some(wm) = weighted_modes {
+                                let (antimodes, count, weight) = wm.antimodes();
+                                (antimodes, count, util::round_num(weight, round_places))
+                            } else {
+                                let (antimodes, count, occurrences) = v.antimodes();
+                                (antimodes, count, occurrences.to_string())
+                            };
 
                         let antimodes_len = ANTIMODES_LEN.get_or_init(|| {
                             std::env::var("QSV_ANTIMODES_LEN")
@@ -1690,7 +2198,7 @@ impl Stats {
                         mc_pieces.extend_from_slice(&[
                             antimodes_list,
                             antimodes_count.to_string(),
-                            antimode_occurrences.to_string(),
+                            antimode_occurrences,
                         ]);
                     }
                 }
@@ -1727,7 +2235,13 @@ impl Stats {
         // sum
         let stotlen =
             if let Some((stotlen_work, sum)) = self.sum.as_ref().and_then(|sum| sum.show(typ)) {
-                if typ == FieldType::TFloat {
+                if let Some(v) = self
+                    .weighted_online
+                    .as_ref()
+                    .filter(|_| typ == TFloat || typ == TInteger)
+                {
+                    pieces.push(util::round_num(v.sum, round_places));
+                } else if typ == FieldType::TFloat {
                     if let Ok(f64_val) = fast_float2::parse::<f64, &[u8]>(sum.as_bytes()) {
                         pieces.push(util::round_num(f64_val, round_places));
                     } else {
@@ -1819,14 +2333,10 @@ impl Stats {
                 empty(),
                 empty(),
             ]);
-        } else if let Some(ref v) = self.online {
-            let std_dev = v.stddev();
-            #[allow(clippy::cast_precision_loss)]
-            let sem = std_dev / (v.len() as f64).sqrt();
-            let mean = v.mean();
+        } else if let Some((mean, sem, geometric_mean, harmonic_mean, std_dev, variance)) =
+            self.moments()
+        {
             let cv = (std_dev / mean) * 100_f64;
-            let geometric_mean = v.geometric_mean();
-            let harmonic_mean = v.harmonic_mean();
             if self.typ == TFloat || self.typ == TInteger {
                 pieces.extend_from_slice(&[
                     util::round_num(mean, round_places),
@@ -1834,7 +2344,7 @@ impl Stats {
                     util::round_num(geometric_mean, round_places),
                     util::round_num(harmonic_mean, round_places),
                     util::round_num(std_dev, round_places),
-                    util::round_num(v.variance(), round_places),
+                    util::round_num(variance, round_places),
                     util::round_num(cv, round_places),
                 ]);
             } else {
@@ -1860,7 +2370,7 @@ impl Stats {
                     u32::max(round_places, DAY_DECIMAL_PLACES),
                 ));
                 pieces.push(util::round_num(
-                    v.variance() / (MS_IN_DAY * MS_IN_DAY),
+                    variance / (MS_IN_DAY * MS_IN_DAY),
                     u32::max(round_places, DAY_DECIMAL_PLACES),
                 ));
                 pieces.push(util::round_num(cv, round_places));
@@ -1889,19 +2399,37 @@ impl Stats {
 
         // sparsity
         #[allow(clippy::cast_precision_loss)]
-        let sparsity: f64 = self.nullcount as f64 / *RECORD_COUNT.get().unwrap_or(&1) as f64;
+        let sparsity: f64 = self.nullcount as f64 / record_count as f64;
         pieces.push(util::round_num(sparsity, round_places));
 
+        // exact percentiles. The samples are then moved to the median or quartiles,
+        // which share them (see Stats::new)
+        let mut exact_percentiles = None;
+        if let Some(samples) = self.percentiles.as_mut() {
+            if !matches!(typ, TNull | TString) {
+                // safety: PERCENTILE_LIST is set when --percentiles is used
+                exact_percentiles = samples.percentiles(PERCENTILE_LIST.get().unwrap());
+            }
+            let data: Unsorted<f64> = std::mem::take(&mut samples.data).into_iter().collect();
+            if self.which.quartiles {
+                self.quartiles = Some(data);
+            } else if self.which.median {
+                self.median = Some(data);
+            }
+        }
+
         // median
         let mut existing_median = None;
-        if let Some(v) = self.median.as_mut().and_then(|v| {
-            if let TNull | TString = typ {
-                None
-            } else {
+        let median = match (typ, self.tdigest.as_mut(), self.weighted_samples.as_mut()) {
+            (TNull | TString, ..) => None,
+            (_, Some(v), _) if self.which.median => v.quantile(0.5),
+            (_, _, Some(v)) if self.which.median => v.quantile(0.5),
+            _ => self.median.as_mut().and_then(|v| {
                 existing_median = v.median();
                 existing_median
-            }
-        }) {
+            }),
+        };
+        if let Some(v) = median {
             if typ == TDateTime || typ == TDate {
                 pieces.push(timestamp_ms_to_rfc3339(v as i64, typ));
             } else {
@@ -1912,13 +2440,13 @@ impl Stats {
         }
 
         // median absolute deviation (MAD)
-        if let Some(v) = self.mad.as_mut().and_then(|v| {
-            if let TNull | TString = typ {
-                None
-            } else {
-                v.mad(existing_median)
-            }
-        }) {
+        let mad = match (typ, self.tdigest.as_mut(), self.weighted_samples.as_mut()) {
+            (TNull | TString, ..) => None,
+            (_, Some(v), _) if self.which.mad => v.mad(),
+            (_, _, Some(v)) if self.which.mad => v.mad(),
+            _ => self.mad.as_mut().and_then(|v| v.mad(existing_median)),
+        };
+        if let Some(v) = mad {
             if typ == TDateTime || typ == TDate {
                 // like stddev, return MAD in days
                 pieces.push(util::round_num(
@@ -1933,10 +2461,13 @@ impl Stats {
         }
 
         // quartiles
-        match self.quartiles.as_mut().and_then(|v| match typ {
-            TInteger | TFloat | TDate | TDateTime => v.quartiles(),
-            _ => None,
-        }) {
+        let quartiles = match (typ, self.tdigest.as_mut(), self.weighted_samples.as_mut()) {
+            (TNull | TString, ..) => None,
+            (_, Some(v), _) if self.which.quartiles => v.quartiles(),
+            (_, _, Some(v)) if self.which.quartiles => v.quartiles(),
+            _ => self.quartiles.as_mut().and_then(Unsorted::quartiles),
+        };
+        match quartiles {
             None => {
                 if self.which.quartiles {
                     pieces.extend_from_slice(&[
@@ -2017,6 +2548,41 @@ impl Stats {
         // append it here to preserve legacy ordering of columns
         pieces.extend_from_slice(&mc_pieces);
 
+        // percentiles
+        if self.which.percentiles {
+            // safety: PERCENTILE_LIST is set when --percentiles is used
+            let percentile_list = PERCENTILE_LIST.get().unwrap();
+            let values = match (typ, self.tdigest.as_mut(), self.weighted_samples.as_mut()) {
+                (TNull | TString, ..) => None,
+                (_, Some(v), _) => percentile_list
+                    .iter()
+                    .map(|p| v.quantile(p / 100.0))
+                    .collect::<Option<Vec<f64>>>(),
+                (_, _, Some(v)) => v.percentiles(percentile_list),
+                _ => exact_percentiles,
+            };
+            if let Some(values) = values {
+                let separator = ANTIMODES_SEPARATOR.get_or_init(|| {
+                    std::env::var("QSV_MODES_SEPARATOR")
+                        .unwrap_or_else(|_| DEFAULT_MODES_SEPARATOR.to_string())
+                });
+                pieces.push(
+                    values
+                        .into_iter()
+                        .map(|v| {
+                            if typ == TDateTime || typ == TDate {
+                                timestamp_ms_to_rfc3339(v as i64, typ)
+                            } else {
+                                util::round_num(v, round_places)
+                            }
+                        })
+                        .join(separator),
+                );
+            } else {
+                pieces.push(empty());
+            }
+        }
+
         // add an empty field for qsv__value
         pieces.push(empty());
 
@@ -2037,8 +2603,14 @@ impl Commute for Stats {
         self.nullcount += other.nullcount;
         self.max_precision = std::cmp::max(self.max_precision, other.max_precision);
         self.modes.merge(other.modes);
+        self.hll.merge(other.hll);
         self.median.merge(other.median);
         self.quartiles.merge(other.quartiles);
+        self.percentiles.merge(other.percentiles);
+        self.tdigest.merge(other.tdigest);
+        self.weighted_online.merge(other.weighted_online);
+        self.weighted_samples.merge(other.weighted_samples);
+        self.weighted_modes.merge(other.weighted_modes);
         self.which.merge(other.which);
     }
 }
@@ -2402,3 +2974,556 @@ impl Commute for TypedMinMax {
         self.dates.merge(other.dates);
     }
 }
+
+/// `Samples` keeps all the samples of a column in memory to compute exact percentiles,
+/// and the exact median & quartiles when --percentiles is used.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq)]
+struct Samples {
+    data:   Vec<f64>,
+    sorted: bool,
+}
+
+impl Samples {
+    #[inline]
+    fn add(&mut self, sample: f64) {
+        self.data.push(sample);
+        self.sorted = false;
+    }
+
+    /// returns the given percentiles (0-100) using the nearest-rank method
+    fn percentiles(&mut self, percentile_list: &[f64]) -> Option<Vec<f64>> {
+        if self.data.is_empty() {
+            return None;
+        }
+        if !self.sorted {
+            self.data.sort_unstable_by(f64::total_cmp);
+            self.sorted = true;
+        }
+        let len = self.data.len();
+        #[allow(clippy::cast_precision_loss)]
+        let len_f64 = len as f64;
+        Some(
+            percentile_list
+                .iter()
+                .map(|p| {
+                    let rank = ((p / 100.0) * len_f64).ceil() as usize;
+                    self.data[rank.clamp(1, len) - 1]
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Commute for Samples {
+    #[inline]
+    fn merge(&mut self, other: Samples) {
+        self.data.extend(other.data);
+        self.sorted = false;
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq)]
+struct Centroid {
+    mean:   f64,
+    weight: f64,
+}
+
+/// `TDigest` is a merging t-digest (https://arxiv.org/abs/1902.04023) that estimates
+/// quantiles in bounded memory for --approx-quantiles. Samples are buffered, and then
+/// merged into centroids that are small at the tails and larger towards the median.
+/// Digests from parallel chunks are merged by re-merging their centroids.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq)]
+struct TDigest {
+    centroids: Vec<Centroid>,
+    buffer:    Vec<Centroid>,
+    min:       f64,
+    max:       f64,
+}
+
+impl TDigest {
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.centroids.is_empty() && self.buffer.is_empty()
+    }
+
+    #[inline]
+    fn add(&mut self, sample: f64, weight: f64) {
+        // zero weights (from --weight) don't change the quantiles
+        if weight > 0.0 {
+            self.add_centroid(Centroid {
+                mean: sample,
+                weight,
+            });
+        }
+    }
+
+    #[inline]
+    fn add_centroid(&mut self, centroid: Centroid) {
+        if self.is_empty() {
+            self.min = centroid.mean;
+            self.max = centroid.mean;
+        } else {
+            self.min = self.min.min(centroid.mean);
+            self.max = self.max.max(centroid.mean);
+        }
+        self.buffer.push(centroid);
+        if self.buffer.len() >= TDIGEST_BUFFER_SIZE {
+            self.compress();
+        }
+    }
+
+    /// the k1 scale function, which limits the size of a centroid by the quantile it covers
+    #[inline]
+    fn k_scale(q: f64) -> f64 {
+        let normalizer = TDIGEST_COMPRESSION / (2.0 * std::f64::consts::PI);
+        normalizer * 2.0f64.mul_add(q.min(1.0), -1.0).asin()
+    }
+
+    /// merges the buffered samples into the centroids
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut points = std::mem::take(&mut self.buffer);
+        points.append(&mut self.centroids);
+        points.sort_unstable_by(|a, b| a.mean.total_cmp(&b.mean));
+        let total_weight: f64 = points.iter().map(|c| c.weight).sum();
+
+        let mut merged = Vec::with_capacity(points.len().min(TDIGEST_COMPRESSION as usize));
+        let mut current = points[0];
+        // the weight of the centroids before the current one
+        let mut weight_so_far = 0.0;
+        let mut k_lower = Self::k_scale(0.0);
+        for point in points.into_iter().skip(1) {
+            let q = (weight_so_far + current.weight + point.weight) / total_weight;
+            if Self::k_scale(q) - k_lower <= 1.0 {
+                current.weight += point.weight;
+                current.mean += (point.mean - current.mean) * point.weight / current.weight;
+            } else {
+                weight_so_far += current.weight;
+                k_lower = Self::k_scale(weight_so_far / total_weight);
+                merged.push(current);
+                current = point;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+
+    /// estimates the value at quantile q (0.0 - 1.0) by interpolating between
+    /// the centers of the centroids around it
+    fn quantile(&mut self, q: f64) -> Option<f64> {
+        self.compress();
+        let (first, last) = (*self.centroids.first()?, *self.centroids.last()?);
+        let total_weight: f64 = self.centroids.iter().map(|c| c.weight).sum();
+        let target = q.clamp(0.0, 1.0) * total_weight;
+
+        // the tails interpolate between the min/max and the first/last centroid
+        if target <= first.weight / 2.0 {
+            return Some((first.mean - self.min).mul_add(target / (first.weight / 2.0), self.min));
+        }
+        if target >= total_weight - last.weight / 2.0 {
+            return Some((self.max - last.mean).mul_add(
+                (target - (total_weight - last.weight / 2.0)) / (last.weight / 2.0),
+                last.mean,
+            ));
+        }
+
+        // the position of the center of the left centroid
+        let mut center = first.weight / 2.0;
+        for pair in self.centroids.windows(2) {
+            let gap = (pair[0].weight + pair[1].weight) / 2.0;
+            if target < center + gap {
+                return Some(
+                    (pair[1].mean - pair[0].mean).mul_add((target - center) / gap, pair[0].mean),
+                );
+            }
+            center += gap;
+        }
+        Some(last.mean)
+    }
+
+    fn quartiles(&mut self) -> Option<(f64, f64, f64)> {
+        Some((
+            self.quantile(0.25)?,
+            self.quantile(0.5)?,
+            self.quantile(0.75)?,
+        ))
+    }
+
+    /// estimates the median absolute deviation with a digest of the
+    /// centroids' absolute deviations from the median
+    fn mad(&mut self) -> Option<f64> {
+        let median = self.quantile(0.5)?;
+        let mut deviations = TDigest::default();
+        for c in &self.centroids {
+            deviations.add_centroid(Centroid {
+                mean:   (c.mean - median).abs(),
+                weight: c.weight,
+            });
+        }
+        deviations.quantile(0.5)
+    }
+}
+
+impl Commute for TDigest {
+    #[inline]
+    fn merge(&mut self, other: TDigest) {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            *self = other;
+            return;
+        }
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.buffer.extend(other.centroids);
+        self.buffer.extend(other.buffer);
+        self.compress();
+    }
+}
+
+/// `HyperLogLog` estimates the cardinality of a column in bounded memory for
+/// --approx-cardinality. It counts exactly by keeping the hashes of the distinct values
+/// until there are more than HLL_SPARSE_MAX of them, and then switches to 2^HLL_PRECISION
+/// registers holding the maximum number of leading zeros seen in the hashes.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq)]
+struct HyperLogLog {
+    sparse:    HashSet<u64>,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    #[inline]
+    fn add(&mut self, sample: &[u8]) {
+        self.add_hash(xxh3_64(sample));
+    }
+
+    #[inline]
+    fn add_hash(&mut self, hash: u64) {
+        if self.registers.is_empty() {
+            self.sparse.insert(hash);
+            if self.sparse.len() > HLL_SPARSE_MAX {
+                self.use_registers();
+            }
+        } else {
+            // the first HLL_PRECISION bits select the register,
+            // the rest are used to count the leading zeros
+            let idx = (hash >> (64 - HLL_PRECISION)) as usize;
+            let rank = ((hash << HLL_PRECISION)
+                .leading_zeros()
+                .min(64 - HLL_PRECISION)
+                + 1) as u8;
+            // safety: idx is less than 2^HLL_PRECISION, the number of registers
+            let register = unsafe { self.registers.get_unchecked_mut(idx) };
+            *register = (*register).max(rank);
+        }
+    }
+
+    fn use_registers(&mut self) {
+        self.registers = vec![0; 1 << HLL_PRECISION];
+        for hash in std::mem::take(&mut self.sparse) {
+            self.add_hash(hash);
+        }
+    }
+
+    /// returns the estimated cardinality
+    #[allow(clippy::cast_precision_loss)]
+    fn estimate(&self) -> u64 {
+        if self.registers.is_empty() {
+            return self.sparse.len() as u64;
+        }
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let (sum, zeros) = self
+            .registers
+            .iter()
+            .fold((0.0, 0_u32), |(sum, zeros), &r| {
+                (sum + 2.0_f64.powi(-i32::from(r)), zeros + u32::from(r == 0))
+            });
+        let raw_estimate = alpha * m * m / sum;
+        // use linear counting for small cardinalities, where HLL is biased
+        if raw_estimate <= 2.5 * m && zeros > 0 {
+            (m * (m / f64::from(zeros)).ln()).round() as u64
+        } else {
+            raw_estimate.round() as u64
+        }
+    }
+
+    /// returns the relative standard error of the estimate - 0 when counted exactly
+    fn relative_error(&self) -> f64 {
+        if self.registers.is_empty() {
+            0.0
+        } else {
+            1.04 / f64::from(1_u32 << HLL_PRECISION).sqrt()
+        }
+    }
+}
+
+impl Commute for HyperLogLog {
+    #[inline]
+    fn merge(&mut self, other: HyperLogLog) {
+        if other.registers.is_empty() {
+            for hash in other.sparse {
+                self.add_hash(hash);
+            }
+        } else {
+            if self.registers.is_empty() {
+                self.use_registers();
+            }
+            for (r, o) in self.registers.iter_mut().zip(other.registers) {
+                *r = (*r).max(o);
+            }
+        }
+    }
+}
+
+/// `WeightedOnlineStats` computes the weighted sum, mean, variance, geometric & harmonic means
+/// of a column with --weight in a single pass. The mean & variance are updated with West's
+/// weighted incremental algorithm (https://doi.org/10.1145/359146.359153).
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq)]
+struct WeightedOnlineStats {
+    sum_weights:    f64,
+    sum_sq_weights: f64,
+    sum:            f64,
+    mean:           f64,
+    // the weighted sum of the squared differences from the mean
+    m2:             f64,
+    log_sum:        f64,
+    reciprocal_sum: f64,
+}
+
+impl WeightedOnlineStats {
+    #[inline]
+    fn add(&mut self, sample: f64, weight: f64) {
+        if weight <= 0.0 {
+            return;
+        }
+        self.sum_weights += weight;
+        self.sum_sq_weights = weight.mul_add(weight, self.sum_sq_weights);
+        self.sum = weight.mul_add(sample, self.sum);
+        let delta = sample - self.mean;
+        self.mean += delta * weight / self.sum_weights;
+        self.m2 = (weight * delta).mul_add(sample - self.mean, self.m2);
+        self.log_sum = weight.mul_add(sample.ln(), self.log_sum);
+        self.reciprocal_sum += weight / sample;
+    }
+
+    /// like OnlineStats, NULLs are added as zeros
+    #[inline]
+    fn add_null(&mut self, weight: f64) {
+        self.add(0.0, weight);
+    }
+
+    /// returns the weighted (mean, sem, geometric_mean, harmonic_mean, stddev, variance).
+    /// The sem uses the effective sample size - (sum of weights)^2 / sum of squared weights.
+    fn moments(&self) -> Option<(f64, f64, f64, f64, f64, f64)> {
+        if self.sum_weights <= 0.0 {
+            return None;
+        }
+        let variance = self.m2 / self.sum_weights;
+        let stddev = variance.sqrt();
+        let effective_len = self.sum_weights * self.sum_weights / self.sum_sq_weights;
+        Some((
+            self.mean,
+            stddev / effective_len.sqrt(),
+            (self.log_sum / self.sum_weights).exp(),
+            self.sum_weights / self.reciprocal_sum,
+            stddev,
+            variance,
+        ))
+    }
+}
+
+impl Commute for WeightedOnlineStats {
+    #[inline]
+    fn merge(&mut self, other: WeightedOnlineStats) {
+        if other.sum_weights <= 0.0 {
+            return;
+        }
+        if self.sum_weights <= 0.0 {
+            *self = other;
+            return;
+        }
+        let sum_weights = self.sum_weights + other.sum_weights;
+        let delta = other.mean - self.mean;
+        self.m2 +=
+            (delta * delta).mul_add(self.sum_weights * other.sum_weights / sum_weights, other.m2);
+        self.mean += delta * other.sum_weights / sum_weights;
+        self.sum_weights = sum_weights;
+        self.sum_sq_weights += other.sum_sq_weights;
+        self.sum += other.sum;
+        self.log_sum += other.log_sum;
+        self.reciprocal_sum += other.reciprocal_sum;
+    }
+}
+
+/// `WeightedSamples` keeps the samples of a column with --weight along with their weights,
+/// to compute the exact weighted median, MAD, quartiles & percentiles.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq)]
+struct WeightedSamples {
+    data:   Vec<(f64, f64)>,
+    sorted: bool,
+}
+
+impl WeightedSamples {
+    #[inline]
+    fn add(&mut self, sample: f64, weight: f64) {
+        if weight > 0.0 {
+            self.data.push((sample, weight));
+            self.sorted = false;
+        }
+    }
+
+    fn sort(&mut self) {
+        if !self.sorted {
+            self.data.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+            self.sorted = true;
+        }
+    }
+
+    /// returns the value at quantile q (0.0 - 1.0). Each sample is placed at the midpoint of
+    /// its cumulative weight, and the quantile is interpolated between the samples around it.
+    /// With equal weights, the median is the same as the unweighted median.
+    fn quantile(&mut self, q: f64) -> Option<f64> {
+        self.sort();
+        let total_weight: f64 = self.data.iter().map(|(_, w)| w).sum();
+        let target = q.clamp(0.0, 1.0) * total_weight;
+
+        // the position & value of the previous sample
+        let mut previous: Option<(f64, f64)> = None;
+        let mut weight_so_far = 0.0;
+        for &(value, weight) in &self.data {
+            let position = weight.mul_add(0.5, weight_so_far);
+            if target <= position {
+                return Some(match previous {
+                    Some((prev_position, prev_value)) => (value - prev_value).mul_add(
+                        (target - prev_position) / (position - prev_position),
+                        prev_value,
+                    ),
+                    None => value,
+                });
+            }
+            previous = Some((position, value));
+            weight_so_far += weight;
+        }
+        previous.map(|(_, value)| value)
+    }
+
+    fn quartiles(&mut self) -> Option<(f64, f64, f64)> {
+        Some((
+            self.quantile(0.25)?,
+            self.quantile(0.5)?,
+            self.quantile(0.75)?,
+        ))
+    }
+
+    /// returns the weighted median of the samples' absolute deviations from the median
+    fn mad(&mut self) -> Option<f64> {
+        let median = self.quantile(0.5)?;
+        WeightedSamples {
+            data:   self
+                .data
+                .iter()
+                .map(|(value, weight)| ((value - median).abs(), *weight))
+                .collect(),
+            sorted: false,
+        }
+        .quantile(0.5)
+    }
+
+    /// returns the given percentiles (0-100) using the weighted nearest-rank method,
+    /// i.e. the first value whose cumulative weight reaches the percentile of the total weight
+    fn percentiles(&mut self, percentile_list: &[f64]) -> Option<Vec<f64>> {
+        self.sort();
+        let last = self.data.last()?.0;
+        let total_weight: f64 = self.data.iter().map(|(_, w)| w).sum();
+        Some(
+            percentile_list
+                .iter()
+                .map(|p| {
+                    let target = p / 100.0 * total_weight;
+                    let mut weight_so_far = 0.0;
+                    self.data
+                        .iter()
+                        .find(|(_, weight)| {
+                            weight_so_far += weight;
+                            weight_so_far >= target
+                        })
+                        .map_or(last, |(value, _)| *value)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Commute for WeightedSamples {
+    #[inline]
+    fn merge(&mut self, other: WeightedSamples) {
+        self.data.extend(other.data);
+        self.sorted = false;
+    }
+}
+
+/// `WeightedModes` sums the weights of each distinct value of a column with --weight,
+/// to compute the weighted modes & antimodes.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq)]
+struct WeightedModes(HashMap<Vec<u8>, f64>);
+
+impl WeightedModes {
+    #[inline]
+    fn add(&mut self, sample: &[u8], weight: f64) {
+        if weight <= 0.0 {
+            return;
+        }
+        if let Some(total_weight) = self.0.get_mut(sample) {
+            *total_weight += weight;
+        } else {
+            self.0.insert(sample.to_vec(), weight);
+        }
+    }
+
+    /// returns the sorted values with the given total weight, within WEIGHT_EPSILON
+    fn values_with_weight(&self, weight: f64) -> Vec<Vec<u8>> {
+        let tolerance = weight.abs() * WEIGHT_EPSILON;
+        let mut values: Vec<Vec<u8>> = self
+            .0
+            .iter()
+            .filter(|(_, w)| (**w - weight).abs() <= tolerance)
+            .map(|(value, _)| value.clone())
+            .collect();
+        values.sort_unstable();
+        values
+    }
+
+    /// returns the values with the largest total weight, their count & their weight
+    fn modes(&self) -> (Vec<Vec<u8>>, usize, f64) {
+        let max_weight = self.0.values().copied().fold(0.0, f64::max);
+        let modes = self.values_with_weight(max_weight);
+        let count = modes.len();
+        (modes, count, max_weight)
+    }
+
+    /// returns the first MAX_ANTIMODES values with the smallest total weight,
+    /// their count & their weight
+    fn antimodes(&self) -> (Vec<Vec<u8>>, usize, f64) {
+        if self.0.is_empty() {
+            return (Vec::new(), 0, 0.0);
+        }
+        let min_weight = self.0.values().copied().fold(f64::INFINITY, f64::min);
+        let mut antimodes = self.values_with_weight(min_weight);
+        let count = antimodes.len();
+        antimodes.truncate(MAX_ANTIMODES);
+        (antimodes, count, min_weight)
+    }
+}
+
+impl Commute for WeightedModes {
+    #[inline]
+    fn merge(&mut self, other: WeightedModes) {
+        for (value, weight) in other.0 {
+            *self.0.entry(value).or_default() += weight;
+        }
+    }
+}
@@ -9,8 +9,11 @@ use log::{debug, info, warn};
 use qsv_sniffer::{SampleSize, Sniffer};
 use serde::de::{Deserialize, Deserializer, Error};
 
+#[cfg(feature = "polars")]
+use crate::formats::PolarsReader;
 use crate::{
-    index::Indexed,
+    formats::{InputFormat, JsonlReader},
+    index::{self, BlockReader, Indexed, IndexedSource},
     select::{SelectColumns, Selection},
     util, CliResult,
 };
@@ -64,6 +67,193 @@ impl Delimiter {
     }
 }
 
+/// Compression represents the stream compression formats qsv can transparently
+/// decompress on input (and compress on output), as determined by the file extension.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    Snappy,
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    /// Returns the compression format for a lowercased file extension (without the dot).
+    pub fn from_extension(ext: &str) -> Compression {
+        match ext {
+            "sz" => Compression::Snappy,
+            "gz" => Compression::Gzip,
+            "zst" => Compression::Zstd,
+            "bz2" => Compression::Bzip2,
+            "xz" => Compression::Xz,
+            _ => Compression::None,
+        }
+    }
+
+    /// Returns the compression format of a file based on its path's extension.
+    pub fn from_path(path: &Path) -> Compression {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map_or(Compression::None, |ext| {
+                Compression::from_extension(&ext.to_ascii_lowercase())
+            })
+    }
+
+    /// Returns the file extension (without the dot) associated with the compression format.
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Snappy => "sz",
+            Compression::Gzip => "gz",
+            Compression::Zstd => "zst",
+            Compression::Bzip2 => "bz2",
+            Compression::Xz => "xz",
+        }
+    }
+
+    pub const fn is_compressed(self) -> bool {
+        !matches!(self, Compression::None)
+    }
+
+    /// Returns true if a file compressed with this format can be indexed for random access,
+    /// i.e. it is made up of independently compressed blocks (BGZF/multi-member gzip or
+    /// multi-frame zstd).
+    pub const fn is_block_seekable(self) -> bool {
+        matches!(self, Compression::Gzip | Compression::Zstd)
+    }
+
+    /// Returns the compression level to use when writing, as set by `QSV_COMPRESSION_LEVEL`,
+    /// clamped to the valid range of the compression format. If not set or invalid,
+    /// the format's default level is used.
+    fn level(self) -> u32 {
+        let (min, max, default) = match self {
+            Compression::Zstd => (1, 22, 3),
+            Compression::Bzip2 => (1, 9, 6),
+            Compression::Gzip | Compression::Xz => (0, 9, 6),
+            Compression::None | Compression::Snappy => return 0,
+        };
+        env::var("QSV_COMPRESSION_LEVEL")
+            .ok()
+            .and_then(|level| level.trim().parse::<u32>().ok())
+            .map_or(default, |level| level.clamp(min, max))
+    }
+}
+
+/// An encoder of a compressed stream whose trailer is written by `finish_stream`.
+trait FinishWrite: io::Write {
+    fn finish_stream(self: Box<Self>) -> io::Result<fs::File>;
+}
+
+impl FinishWrite for flate2::write::GzEncoder<fs::File> {
+    fn finish_stream(self: Box<Self>) -> io::Result<fs::File> {
+        self.finish()
+    }
+}
+
+impl FinishWrite for zstd::stream::write::Encoder<'static, fs::File> {
+    fn finish_stream(self: Box<Self>) -> io::Result<fs::File> {
+        self.finish()
+    }
+}
+
+impl FinishWrite for bzip2::write::BzEncoder<fs::File> {
+    fn finish_stream(self: Box<Self>) -> io::Result<fs::File> {
+        self.finish()
+    }
+}
+
+impl FinishWrite for xz2::write::XzEncoder<fs::File> {
+    fn finish_stream(self: Box<Self>) -> io::Result<fs::File> {
+        self.finish()
+    }
+}
+
+/// A gzip, zstd, bzip2 or xz compressed output file.
+///
+/// The compressed stream is finished when the writer is flushed, so errors writing its
+/// trailer (e.g. a full disk or a broken pipe) are returned by `flush()` instead of being
+/// silently dropped by the encoder's `Drop`. Writes after a flush start a new compressed
+/// stream, as the decoders of all these formats read concatenated streams.
+struct CompressedWriter {
+    compression: Compression,
+    level:       u32,
+    encoder:     Option<Box<dyn FinishWrite>>,
+    // the file, while there is no current compressed stream
+    file:        Option<fs::File>,
+}
+
+impl CompressedWriter {
+    fn new(file: fs::File, compression: Compression, level: u32) -> io::Result<Self> {
+        let mut wtr = CompressedWriter {
+            compression,
+            level,
+            encoder: None,
+            file: Some(file),
+        };
+        // start the first stream right away, so an empty output is still a valid stream
+        wtr.encoder()?;
+        Ok(wtr)
+    }
+
+    /// the encoder of the current compressed stream, starting a new stream if needed
+    fn encoder(&mut self) -> io::Result<&mut Box<dyn FinishWrite>> {
+        if self.encoder.is_none() {
+            let Some(file) = self.file.take() else {
+                return Err(io::Error::other(
+                    "cannot write to a compressed file that failed to finish",
+                ));
+            };
+            let level = self.level;
+            let encoder: Box<dyn FinishWrite> = match self.compression {
+                Compression::Gzip => Box::new(flate2::write::GzEncoder::new(
+                    file,
+                    flate2::Compression::new(level),
+                )),
+                Compression::Zstd => {
+                    #[allow(clippy::cast_possible_wrap)]
+                    let encoder = zstd::stream::write::Encoder::new(file, level as i32)?;
+                    Box::new(encoder)
+                },
+                Compression::Bzip2 => Box::new(bzip2::write::BzEncoder::new(
+                    file,
+                    bzip2::Compression::new(level),
+                )),
+                Compression::Xz => Box::new(xz2::write::XzEncoder::new(file, level)),
+                Compression::None | Compression::Snappy => {
+                    unreachable!("CompressedWriter is only used for gzip, zstd, bzip2 & xz")
+                },
+            };
+            self.encoder = Some(encoder);
+        }
+        Ok(self.encoder.as_mut().unwrap())
+    }
+}
+
+impl io::Write for CompressedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.encoder()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(encoder) = self.encoder.take() {
+            let mut file = encoder.finish_stream()?;
+            file.flush()?;
+            self.file = Some(file);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for CompressedWriter {
+    fn drop(&mut self) {
+        // best effort for writers that were never flushed. Errors can't be returned here.
+        let _ = io::Write::flush(self);
+    }
+}
+
 impl<'de> Deserialize<'de> for Delimiter {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Delimiter, D::Error> {
         let s = String::deserialize(d)?;
@@ -93,7 +283,8 @@ pub struct Config {
     pub autoindex_size:    u64,
     prefer_dmy:            bool,
     pub comment:           Option<u8>,
-    snappy:                bool, // flag to enable snappy compression/decompression
+    compression:           Compression, // stream compression format, based on file extension
+    format:                InputFormat, // JSONL/Parquet/Arrow input is streamed as CSV
     pub read_buffer:       u32,
     pub write_buffer:      u32,
     pub skip_format_check: bool,
@@ -121,7 +312,10 @@ impl Config {
     ///
     /// This function initializes a `Config` with the following behavior:
     /// - Uses env var `QSV_DEFAULT_DELIMITER` for default delimiter, or ',' if not set
-    /// - Determines delimiter and Snappy compression based on file extension.
+    /// - Determines delimiter and compression format (Snappy, gzip, zstd, bzip2 or xz) based on
+    ///   file extension.
+    /// - Determines if the input is a JSONL, Parquet or Arrow file based on file extension, in
+    ///   which case it is streamed as CSV by `io_reader`.
     /// - Supports sniffing delimiter and preamble rows if `QSV_SNIFF_DELIMITER` or
     ///   `QSV_SNIFF_PREAMBLE` is set.
     /// - Sets comment character from `QSV_COMMENT_CHAR` environment variable.
@@ -149,8 +343,8 @@ impl Config {
             || util::get_envvar_flag("QSV_SNIFF_PREAMBLE");
         let mut skip_format_check = true;
         let mut format_error = None;
-        let (path, mut delim, snappy) = match path {
-            None => (None, default_delim, false),
+        let (path, mut delim, compression, format) = match path {
+            None => (None, default_delim, Compression::None, InputFormat::Csv),
             // WIP: support remote files; currently only http(s) is supported
             // Some(ref s) if s.starts_with("http") && Url::parse(s).is_ok() => {
             //     let mut snappy = false;
@@ -167,14 +361,17 @@ impl Config {
             //     util::download_file()
             //     (Some(PathBuf::from(s)), delim, snappy)
             // },
-            Some(s) if s == "-" => (None, default_delim, false),
+            Some(s) if s == "-" => (None, default_delim, Compression::None, InputFormat::Csv),
             Some(ref s) => {
                 let path = PathBuf::from(s);
                 let (file_extension, delim, snappy) = get_delim_by_extension(&path, default_delim);
+                let format = InputFormat::from_extension(&file_extension);
                 skip_format_check = sniff || util::get_envvar_flag("QSV_SKIP_FORMAT_CHECK");
                 if !skip_format_check {
                     format_error = match file_extension.as_str() {
-                        "csv" | "tsv" | "tab" | "ssv" => None,
+                        "csv" | "tsv" | "tab" | "ssv" | "jsonl" | "ndjson" => None,
+                        #[cfg(feature = "polars")]
+                        "parquet" | "pqt" | "arrow" | "ipc" | "feather" => None,
                         ext => Some(format!(
                             "{} is using an unsupported file format: {ext}. Set \
                              QSV_SKIP_FORMAT_CHECK to skip input format checking.",
@@ -182,7 +379,12 @@ impl Config {
                         )),
                     };
                 }
-                (Some(path), delim, snappy || file_extension.ends_with("sz"))
+                let compression = if snappy || file_extension.ends_with("sz") {
+                    Compression::Snappy
+                } else {
+                    Compression::from_path(&path)
+                };
+                (Some(path), delim, compression, format)
             },
         };
         let sniff = util::get_envvar_flag("QSV_SNIFF_DELIMITER")
@@ -193,7 +395,8 @@ impl Config {
         };
         let no_headers = util::get_envvar_flag("QSV_NO_HEADERS");
         let mut preamble = 0_u64;
-        if sniff && path.is_some() {
+        // qsv_sniffer reads the raw file, so we can't sniff compressed or non-CSV files
+        if sniff && path.is_some() && !compression.is_compressed() && format == InputFormat::Csv {
             let sniff_path = path.as_ref().unwrap().to_str().unwrap();
 
             match Sniffer::new()
@@ -237,7 +440,8 @@ impl Config {
                 .unwrap_or(0),
             prefer_dmy: util::get_envvar_flag("QSV_PREFER_DMY"),
             comment,
-            snappy,
+            compression,
+            format,
             read_buffer: std::env::var("QSV_RDR_BUFFER_CAPACITY")
                 .unwrap_or_else(|_| DEFAULT_RDR_BUFFER_CAPACITY.to_string())
                 .parse()
@@ -356,9 +560,45 @@ impl Config {
         self.path.is_none()
     }
 
-    #[cfg(feature = "polars")]
-    pub const fn is_snappy(&self) -> bool {
-        self.snappy
+    /// Returns true if the input/output is compressed (Snappy, gzip, zstd, bzip2 or xz).
+    pub const fn is_compressed(&self) -> bool {
+        self.compression.is_compressed()
+    }
+
+    /// Returns the input format. Non-CSV formats (JSONL, Parquet & Arrow) are streamed as CSV.
+    pub const fn input_format(&self) -> InputFormat {
+        self.format
+    }
+
+    /// Returns an error if the input is compressed, as compressed streams are not seekable.
+    /// Block-compressed files can only be read with random access through an index.
+    fn check_seekable(&self) -> io::Result<()> {
+        let path = self
+            .path
+            .as_ref()
+            .map_or_else(|| "<stdin>".to_string(), |p| p.display().to_string());
+        if self.format != InputFormat::Csv {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "{path} is a {} file and cannot be read with random access. Convert it to \
+                     CSV first (e.g. `qsv input {path} -o <file>.csv`).",
+                    self.format.name()
+                ),
+            ));
+        }
+        if !self.compression.is_compressed() {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "{path} is {}-compressed and cannot be read with random access. Decompress it \
+                 first (e.g. `qsv input {path} -o <file>.csv`), or if it is block-compressed \
+                 (BGZF or multi-frame zstd), run `qsv index` on it.",
+                self.compression.extension()
+            ),
+        ))
     }
 
     #[inline]
@@ -439,6 +679,7 @@ impl Config {
                         self.format_error.clone().unwrap(),
                     ))
                 } else {
+                    self.check_seekable()?;
                     fs::File::open(p).map(|f| self.from_reader(f))
                 }
             },
@@ -462,6 +703,7 @@ impl Config {
                         self.format_error.clone().unwrap(),
                     ));
                 }
+                self.check_seekable()?;
                 self.from_reader(Box::new(fs::File::open(p)?))
             },
         })
@@ -475,7 +717,9 @@ impl Config {
     ///
     /// # Behavior
     ///
-    /// - If the file is Snappy-compressed, the function returns immediately w/o creating an index.
+    /// - If the file is compressed, but not block-compressed (BGZF or multi-frame zstd), the
+    ///   function returns immediately w/o creating an index.
+    /// - Block-compressed files are indexed with `index::create_block_index()`.
     /// - If `self.path` is `None`, the function returns without action.
     /// - The function creates an index file using `util::idx_path()` to determine index file path.
     /// - It uses `csv_index::RandomAccessSimple::create()` to generate the index.
@@ -486,16 +730,36 @@ impl Config {
     /// While this function doesn't return any errors, it logs debug messages for both successful
     /// and failed index creation attempts.
     fn autoindex_file(&self) {
-        if self.snappy {
+        if self.format != InputFormat::Csv
+            || (self.compression.is_compressed() && !self.compression.is_block_seekable())
+        {
             return;
         }
 
         let Some(path_buf) = &self.path else { return };
 
         let pidx = util::idx_path(Path::new(path_buf));
-        let Ok(idxfile) = fs::File::create(pidx) else {
+        let Ok(idxfile) = fs::File::create(&pidx) else {
             return;
         };
+        if self.compression.is_compressed() {
+            let mut wtr = io::BufWriter::with_capacity(DEFAULT_WTR_BUFFER_CAPACITY, idxfile);
+            match index::create_block_index(self, path_buf, self.compression, &mut wtr)
+                .and_then(|()| Ok(io::Write::flush(&mut wtr)?))
+            {
+                Ok(()) => {
+                    debug!("autoindex of block-compressed {path_buf:?} successful.");
+                    AUTO_INDEXED.store(true, Ordering::Relaxed);
+                },
+                Err(e) => {
+                    debug!("autoindex of block-compressed {path_buf:?} failed: {e}");
+                    // don't leave a partial index behind
+                    drop(wtr);
+                    let _ = fs::remove_file(&pidx);
+                },
+            }
+            return;
+        }
         let Ok(mut rdr) = self.reader_file() else {
             return;
         };
@@ -516,7 +780,7 @@ impl Config {
     /// If so, return the CSV file handle and the index file handle. If not, return None.
     /// Unless the CSV's file size >= QSV_AUTOINDEX_SIZE, then we'll create an index automatically.
     /// This will also automatically update stale indices (i.e. the CSV is newer than the index )
-    pub fn index_files(&self) -> io::Result<Option<(csv::Reader<fs::File>, fs::File)>> {
+    pub fn index_files(&self) -> io::Result<Option<(csv::Reader<IndexedSource>, fs::File)>> {
         let mut data_modified = 0_u64;
         let data_fsize;
         let mut idx_path_work = PathBuf::new();
@@ -542,6 +806,8 @@ impl Config {
                     ));
                 },
                 (Some(p), Some(ip)) => (fs::File::open(p)?, fs::File::open(ip)?),
+                // non-CSV formats are converted to CSV on the fly, so they can't be indexed
+                (Some(_), &None) if self.format != InputFormat::Csv => return Ok(None),
                 (Some(p), &None) => {
                     // We generally don't want to report an error here, since we're
                     // passively trying to find an index.
@@ -551,8 +817,10 @@ impl Config {
                     let idx_file = match fs::File::open(&idx_path_work) {
                         Err(_) => {
                             // the index file doesn't exist
-                            if self.snappy {
-                                // cannot index snappy compressed files
+                            if self.compression.is_compressed()
+                                && !self.compression.is_block_seekable()
+                            {
+                                // cannot index compressed files that are not block-compressed
                                 return Ok(None);
                             } else if self.autoindex_size > 0 && data_fsize >= self.autoindex_size {
                                 // if CSV file size >= QSV_AUTOINDEX_SIZE, and
@@ -592,7 +860,12 @@ impl Config {
             idx_file = fs::File::open(&idx_path_work)?;
         }
 
-        let csv_rdr = self.from_reader(csv_file);
+        let source = if self.compression.is_compressed() {
+            IndexedSource::Blocked(BlockReader::new(csv_file, self.compression)?)
+        } else {
+            IndexedSource::Plain(csv_file)
+        };
+        let csv_rdr = self.from_reader(source);
         Ok(Some((csv_rdr, idx_file)))
     }
 
@@ -602,23 +875,77 @@ impl Config {
     /// Unless QSV_AUTOINDEX is set, in which case, we'll recreate the
     /// stale index automatically
     #[inline]
-    pub fn indexed(&self) -> CliResult<Option<Indexed<fs::File, fs::File>>> {
+    pub fn indexed(&self) -> CliResult<Option<Indexed<IndexedSource, fs::File>>> {
         match self.index_files()? {
             None => Ok(None),
             Some((r, i)) => Ok(Some(Indexed::open(r, i)?)),
         }
     }
 
+    /// Returns the raw input reader. Compressed files are decompressed, and JSONL, Parquet &
+    /// Arrow files are converted to CSV using the configured delimiter & quote character.
     pub fn io_reader(&self) -> io::Result<Box<dyn io::Read + Send + 'static>> {
+        match (self.format, &self.path) {
+            (InputFormat::Parquet | InputFormat::Arrow, Some(p)) => {
+                info!("reading {} file as CSV: {}", self.format.name(), p.display());
+                self.polars_reader(p)
+            },
+            (InputFormat::Jsonl, Some(_)) => {
+                let rdr =
+                    io::BufReader::with_capacity(self.read_buffer as usize, self.raw_reader()?);
+                Ok(Box::new(JsonlReader::new(rdr, self.delimiter, self.quote)))
+            },
+            _ => self.raw_reader(),
+        }
+    }
+
+    #[cfg(feature = "polars")]
+    fn polars_reader(&self, path: &Path) -> io::Result<Box<dyn io::Read + Send + 'static>> {
+        Ok(Box::new(PolarsReader::new(
+            path,
+            self.format,
+            self.delimiter,
+            self.quote,
+        )?))
+    }
+
+    #[cfg(not(feature = "polars"))]
+    fn polars_reader(&self, _path: &Path) -> io::Result<Box<dyn io::Read + Send + 'static>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "Reading {} files requires qsv to be built with the polars feature.",
+                self.format.name()
+            ),
+        ))
+    }
+
+    /// Returns the input reader, decompressing it if required.
+    fn raw_reader(&self) -> io::Result<Box<dyn io::Read + Send + 'static>> {
         Ok(match self.path {
             None => Box::new(io::stdin()),
             Some(ref p) => match fs::File::open(p) {
                 Ok(x) => {
-                    if self.snappy {
-                        info!("decoding snappy-compressed file: {}", p.display());
-                        Box::new(snap::read::FrameDecoder::new(x))
-                    } else {
-                        Box::new(x)
+                    if self.compression.is_compressed() {
+                        info!(
+                            "decoding {}-compressed file: {}",
+                            self.compression.extension(),
+                            p.display()
+                        );
+                    }
+                    match self.compression {
+                        Compression::None => Box::new(x),
+                        Compression::Snappy => Box::new(snap::read::FrameDecoder::new(x)),
+                        Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(
+                            io::BufReader::new(x),
+                        )),
+                        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(x)?),
+                        Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(
+                            io::BufReader::new(x),
+                        )),
+                        Compression::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(
+                            io::BufReader::new(x),
+                        )),
                     }
                 },
                 Err(err) => {
@@ -651,12 +978,21 @@ impl Config {
                 let p_str = p.as_os_str();
                 if p_str == "sink" {
                     // sink is /dev/null
-                    Box::new(io::sink())
-                } else if self.snappy {
-                    info!("writing snappy-compressed file: {p:?}");
-                    Box::new(snap::write::FrameEncoder::new(fs::File::create(p)?))
-                } else {
-                    Box::new(fs::File::create(p)?)
+                    return Ok(Box::new(io::sink()));
+                }
+                let f = fs::File::create(p)?;
+                if self.compression.is_compressed() {
+                    info!(
+                        "writing {}-compressed file: {p:?}",
+                        self.compression.extension()
+                    );
+                }
+                match self.compression {
+                    Compression::None => Box::new(f),
+                    Compression::Snappy => Box::new(snap::write::FrameEncoder::new(f)),
+                    compression => {
+                        Box::new(CompressedWriter::new(f, compression, compression.level())?)
+                    },
                 }
             },
         })
@@ -681,7 +1017,7 @@ impl Config {
     }
 }
 
-/// Determines the delimiter and compression status based on the file extension.
+/// Determines the delimiter and Snappy compression status based on the file extension.
 ///
 /// # Arguments
 ///
@@ -700,7 +1036,9 @@ impl Config {
 /// This function examines the file extension to determine:
 /// 1. The appropriate delimiter (tab for .tsv/.tab, semicolon for .ssv, comma for .csv).
 /// 2. Whether the file is Snappy-compressed (indicated by a .sz extension).
-/// 3. For Snappy-compressed files, it checks the extension before .sz to determine the delimiter.
+/// 3. For compressed files (.sz, .gz, .zst, .bz2 or .xz), it checks the extension before the
+///    compression extension to determine the delimiter. Use `Compression::from_path` to get the
+///    compression format itself.
 ///
 /// If the file extension doesn't match known types, it returns the default delimiter.
 pub fn get_delim_by_extension(path: &Path, default_delim: u8) -> (String, u8, bool) {
@@ -710,13 +1048,17 @@ pub fn get_delim_by_extension(path: &Path, default_delim: u8) -> (String, u8, bo
     #[allow(clippy::case_sensitive_file_extension_comparisons)]
     let snappy = path_str.ends_with(".sz");
 
-    // Get the extension before .sz if it's a snappy file, otherwise get the normal extension
-    let file_extension = if snappy {
-        path_str
-            .strip_suffix(".sz")
-            .and_then(|s| s.split('.').next_back())
-            .unwrap_or("")
-            .to_string()
+    // Get the extension before the compression extension if it's a compressed file,
+    // otherwise get the normal extension
+    let compression = Compression::from_path(path);
+    let file_extension = if compression.is_compressed() {
+        path.file_stem()
+            .map(Path::new)
+            .and_then(Path::extension)
+            .unwrap_or_default()
+            .to_str()
+            .unwrap_or_default()
+            .to_ascii_lowercase()
     } else {
         path.extension()
             .unwrap_or_default()
@@ -786,6 +1128,31 @@ mod tests {
         assert!(snappy);
     }
 
+    #[test]
+    fn test_gzip_csv_extension() {
+        let path = PathBuf::from("test.csv.gz");
+        let (ext, delim, snappy) = get_delim_by_extension(&path, b',');
+        assert_eq!(ext, "csv");
+        assert_eq!(delim, b',');
+        assert!(!snappy);
+        assert_eq!(Compression::from_path(&path), Compression::Gzip);
+    }
+
+    #[test]
+    fn test_compressed_tsv_extensions() {
+        for (fname, compression) in [
+            ("test.tsv.zst", Compression::Zstd),
+            ("test.TAB.bz2", Compression::Bzip2),
+            ("test.tsv.xz", Compression::Xz),
+        ] {
+            let path = PathBuf::from(fname);
+            let (_, delim, snappy) = get_delim_by_extension(&path, b',');
+            assert_eq!(delim, b'\t');
+            assert!(!snappy);
+            assert_eq!(Compression::from_path(&path), compression);
+        }
+    }
+
     #[test]
     fn test_unknown_extension() {
         let path = PathBuf::from("test.unknown");
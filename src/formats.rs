@@ -0,0 +1,310 @@
+use std::{
+    io::{self, BufRead, Read},
+    mem,
+};
+
+use serde_json::Value;
+
+/// The number of JSONL lines converted to CSV per batch.
+const JSONL_BATCH_LINES: usize = 1_000;
+
+/// The number of rows read from a Parquet/Arrow file per batch.
+#[cfg(feature = "polars")]
+const POLARS_BATCH_ROWS: polars::prelude::IdxSize = 50_000;
+
+/// InputFormat represents the non-CSV file formats that `Config::reader` can read,
+/// streaming them as CSV, as determined by the file extension.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputFormat {
+    #[default]
+    Csv,
+    Jsonl,
+    Parquet,
+    Arrow,
+}
+
+impl InputFormat {
+    /// Returns the input format for a lowercased file extension (without the dot).
+    pub fn from_extension(ext: &str) -> InputFormat {
+        match ext {
+            "jsonl" | "ndjson" => InputFormat::Jsonl,
+            "parquet" | "pqt" => InputFormat::Parquet,
+            "arrow" | "ipc" | "feather" => InputFormat::Arrow,
+            _ => InputFormat::Csv,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            InputFormat::Csv => "CSV",
+            InputFormat::Jsonl => "JSONL",
+            InputFormat::Parquet => "Parquet",
+            InputFormat::Arrow => "Arrow",
+        }
+    }
+}
+
+fn recurse_to_infer_headers(value: &Value, headers: &mut Vec<Vec<String>>, path: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match value {
+                    Value::Null
+                    | Value::Bool(_)
+                    | Value::Number(_)
+                    | Value::String(_)
+                    | Value::Array(_) => {
+                        let mut full_path = path.to_owned();
+                        full_path.push(key.to_string());
+
+                        headers.push(full_path);
+                    },
+                    Value::Object(_) => {
+                        let mut new_path = path.to_owned();
+                        new_path.push(key.to_string());
+
+                        recurse_to_infer_headers(value, headers, &new_path);
+                    },
+                    #[allow(unreachable_patterns)]
+                    _ => {},
+                }
+            }
+        },
+        _ => {
+            headers.push(vec![String::from("value")]);
+        },
+    }
+}
+
+/// Infers the CSV headers of a JSON value. Nested objects are flattened,
+/// with each header being the path of keys to a scalar or array value.
+pub fn infer_headers(value: &Value) -> Vec<Vec<String>> {
+    let mut headers: Vec<Vec<String>> = Vec::new();
+
+    recurse_to_infer_headers(value, &mut headers, &Vec::new());
+
+    headers
+}
+
+fn get_value_at_path(value: &Value, path: &[String]) -> Option<Value> {
+    let mut current = value;
+
+    for key in path {
+        match current.get(key) {
+            Some(new_value) => {
+                current = new_value;
+            },
+            None => {
+                return None;
+            },
+        }
+    }
+
+    Some(current.clone())
+}
+
+/// Converts a JSON value to a CSV record, with a field for each of the `headers` paths.
+#[inline]
+pub fn json_line_to_csv_record(value: &Value, headers: &[Vec<String>]) -> csv::StringRecord {
+    let mut record = csv::StringRecord::new();
+
+    for path in headers {
+        let value = get_value_at_path(value, path);
+
+        if let Some(value) = value {
+            record.push_field(&match value {
+                Value::Bool(v) => {
+                    if v {
+                        String::from("true")
+                    } else {
+                        String::from("false")
+                    }
+                },
+                Value::Number(v) => v.to_string(),
+                Value::String(v) => v,
+                Value::Array(v) => v
+                    .iter()
+                    .map(std::string::ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+                _ => String::new(),
+            });
+        } else {
+            record.push_field("");
+        }
+    }
+
+    record
+}
+
+/// JsonlReader streams newline-delimited JSON as CSV.
+/// Like the `jsonl` command, the first JSON line is used to infer the headers.
+pub struct JsonlReader<R> {
+    rdr:     R,
+    delim:   u8,
+    quote:   u8,
+    headers: Option<Vec<Vec<String>>>,
+    line:    String,
+    line_no: u64,
+    buf:     Vec<u8>,
+    pos:     usize,
+}
+
+impl<R: BufRead> JsonlReader<R> {
+    pub fn new(rdr: R, delim: u8, quote: u8) -> JsonlReader<R> {
+        JsonlReader {
+            rdr,
+            delim,
+            quote,
+            headers: None,
+            line: String::new(),
+            line_no: 0,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Converts the next batch of JSON lines to CSV. Leaves the buffer empty at EOF.
+    fn fill(&mut self) -> io::Result<()> {
+        let mut buf = mem::take(&mut self.buf);
+        buf.clear();
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(self.delim)
+            .quote(self.quote)
+            .from_writer(buf);
+
+        let mut converted = 0;
+        while converted < JSONL_BATCH_LINES {
+            self.line.clear();
+            if self.rdr.read_line(&mut self.line)? == 0 {
+                break;
+            }
+            self.line_no += 1;
+            if self.line.trim().is_empty() {
+                continue;
+            }
+            let value: Value = serde_json::from_str(&self.line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("cannot parse JSONL line {}: {e}", self.line_no),
+                )
+            })?;
+            if self.headers.is_none() {
+                let headers = infer_headers(&value);
+                wtr.write_record(headers.iter().map(|h| h.join(".")))?;
+                self.headers = Some(headers);
+            }
+            // safety: the headers were inferred above
+            let headers = self.headers.as_ref().unwrap();
+            wtr.write_record(&json_line_to_csv_record(&value, headers))?;
+            converted += 1;
+        }
+
+        self.buf = wtr.into_inner().map_err(|e| e.into_error())?;
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<R: BufRead> io::Read for JsonlReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.buf.len() {
+            self.fill()?;
+        }
+        let n = (&self.buf[self.pos..]).read(buf)?;
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// PolarsReader streams a Parquet or Arrow IPC file as CSV, reading it in batches of
+/// `POLARS_BATCH_ROWS` rows so only one batch is in memory at a time. Each batch is a
+/// sliced scan of the file, and as the slice is pushed down to the reader, only the
+/// row groups/record batches of that slice are read.
+#[cfg(feature = "polars")]
+pub struct PolarsReader {
+    lf:     polars::prelude::LazyFrame,
+    delim:  u8,
+    quote:  u8,
+    offset: i64,
+    done:   bool,
+    buf:    Vec<u8>,
+    pos:    usize,
+}
+
+#[cfg(feature = "polars")]
+impl PolarsReader {
+    pub fn new(
+        path: &std::path::Path,
+        format: InputFormat,
+        delim: u8,
+        quote: u8,
+    ) -> io::Result<PolarsReader> {
+        use polars::prelude::{LazyFrame, ScanArgsIpc, ScanArgsParquet};
+
+        let lf = match format {
+            InputFormat::Parquet => LazyFrame::scan_parquet(path, ScanArgsParquet::default()),
+            _ => LazyFrame::scan_ipc(path, ScanArgsIpc::default()),
+        }
+        .map_err(polars_to_io_error)?;
+
+        Ok(PolarsReader {
+            lf,
+            delim,
+            quote,
+            offset: 0,
+            done: false,
+            buf: Vec::new(),
+            pos: 0,
+        })
+    }
+
+    /// Writes the next batch of rows as CSV. Leaves the buffer empty when done.
+    fn fill(&mut self) -> io::Result<()> {
+        use polars::prelude::{CsvWriter, SerWriter};
+
+        self.buf.clear();
+        self.pos = 0;
+        if self.done {
+            return Ok(());
+        }
+
+        let mut df = self
+            .lf
+            .clone()
+            .slice(self.offset, POLARS_BATCH_ROWS)
+            .collect()
+            .map_err(polars_to_io_error)?;
+        let rows = df.height();
+        self.done = rows < POLARS_BATCH_ROWS as usize;
+        if rows == 0 && self.offset > 0 {
+            return Ok(());
+        }
+
+        CsvWriter::new(&mut self.buf)
+            .include_header(self.offset == 0)
+            .with_separator(self.delim)
+            .with_quote_char(self.quote)
+            .finish(&mut df)
+            .map_err(polars_to_io_error)?;
+        self.offset += rows as i64;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "polars")]
+impl io::Read for PolarsReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.buf.len() {
+            self.fill()?;
+        }
+        let n = (&self.buf[self.pos..]).read(buf)?;
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "polars")]
+fn polars_to_io_error(e: polars::prelude::PolarsError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
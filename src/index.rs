@@ -1,8 +1,25 @@
-use std::{io, ops};
+use std::{
+    fs,
+    io::{self, BufRead, Read, Seek},
+    mem, ops,
+    path::Path,
+};
 
+use byteorder::{BigEndian, WriteBytesExt};
 use csv_index::RandomAccessSimple;
 
-use crate::CliResult;
+use crate::{
+    config::{Compression, Config},
+    CliResult,
+};
+
+/// Like BGZF, the positions stored in the index of a block-compressed file are
+/// "virtual offsets" - the compressed offset of the block a record starts in,
+/// shifted left by `BLOCK_OFFSET_BITS`, OR'ed with the record's offset within the
+/// decompressed block.
+const BLOCK_OFFSET_BITS: u32 = 16;
+const MAX_BLOCK_OFFSET: u64 = (1 << BLOCK_OFFSET_BITS) - 1;
+const MAX_COMPRESSED_OFFSET: u64 = (1 << (64 - BLOCK_OFFSET_BITS)) - 1;
 
 /// Indexed composes a CSV reader with a simple random access index.
 pub struct Indexed<R, I> {
@@ -63,3 +80,233 @@ impl<R: io::Read + io::Seek, I: io::Read + io::Seek> Indexed<R, I> {
         Ok(())
     }
 }
+
+/// IndexedSource is the seekable data source of an indexed CSV - either a regular file,
+/// or a block-compressed file whose index stores virtual offsets.
+pub enum IndexedSource {
+    Plain(fs::File),
+    Blocked(BlockReader),
+}
+
+impl io::Read for IndexedSource {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            IndexedSource::Plain(f) => f.read(buf),
+            IndexedSource::Blocked(b) => b.read(buf),
+        }
+    }
+}
+
+impl io::Seek for IndexedSource {
+    #[inline]
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        match self {
+            IndexedSource::Plain(f) => f.seek(pos),
+            IndexedSource::Blocked(b) => b.seek(pos),
+        }
+    }
+}
+
+/// BlockReader decompresses a block-compressed file - BGZF/multi-member gzip or
+/// multi-frame (e.g. seekable) zstd. Seeking to a virtual offset only inflates the
+/// block the offset points into.
+pub struct BlockReader {
+    file:        fs::File,
+    compression: Compression,
+    decoder:     Box<dyn io::Read + Send>,
+}
+
+impl BlockReader {
+    pub fn new(file: fs::File, compression: Compression) -> io::Result<BlockReader> {
+        check_block_compression(compression)?;
+        let decoder = block_decoder(file.try_clone()?, compression)?;
+        Ok(BlockReader {
+            file,
+            compression,
+            decoder,
+        })
+    }
+}
+
+impl io::Read for BlockReader {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.decoder.read(buf)
+    }
+}
+
+impl io::Seek for BlockReader {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let io::SeekFrom::Start(voffset) = pos else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "block-compressed files can only seek to a virtual offset from the start",
+            ));
+        };
+        let mut file = self.file.try_clone()?;
+        file.seek(io::SeekFrom::Start(voffset >> BLOCK_OFFSET_BITS))?;
+        self.decoder = block_decoder(file, self.compression)?;
+
+        let in_block = voffset & MAX_BLOCK_OFFSET;
+        let skipped = io::copy(&mut self.decoder.by_ref().take(in_block), &mut io::sink())?;
+        if skipped < in_block {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("virtual offset {voffset} is past the end of its block"),
+            ));
+        }
+        Ok(voffset)
+    }
+}
+
+fn check_block_compression(compression: Compression) -> io::Result<()> {
+    if compression.is_block_seekable() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "{}-compressed files cannot be indexed. Only block-compressed gzip (BGZF) and \
+                 multi-frame (seekable) zstd files can be indexed.",
+                compression.extension()
+            ),
+        ))
+    }
+}
+
+fn block_decoder(file: fs::File, compression: Compression) -> io::Result<Box<dyn io::Read + Send>> {
+    Ok(match compression {
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(file)?),
+        _ => Box::new(flate2::read::MultiGzDecoder::new(io::BufReader::new(file))),
+    })
+}
+
+/// The decoder of the block `BlockScanner` is currently inflating.
+enum ScanDecoder {
+    Gzip(flate2::bufread::GzDecoder<io::BufReader<fs::File>>),
+    Zstd(zstd::stream::read::Decoder<'static, io::BufReader<fs::File>>),
+    Done,
+}
+
+impl ScanDecoder {
+    fn open(rdr: io::BufReader<fs::File>, compression: Compression) -> io::Result<ScanDecoder> {
+        Ok(if compression == Compression::Zstd {
+            ScanDecoder::Zstd(zstd::stream::read::Decoder::with_buffer(rdr)?.single_frame())
+        } else {
+            ScanDecoder::Gzip(flate2::bufread::GzDecoder::new(rdr))
+        })
+    }
+}
+
+/// BlockScanner inflates a block-compressed file one block (gzip member/zstd frame)
+/// at a time, recording where each block starts in both the compressed file and the
+/// decompressed stream, so record positions can be mapped to virtual offsets.
+struct BlockScanner {
+    compression: Compression,
+    decoder:     ScanDecoder,
+    bytes_out:   u64,
+    // (decompressed start, compressed offset) of every block read so far
+    blocks:      Vec<(u64, u64)>,
+}
+
+impl BlockScanner {
+    fn new(file: fs::File, compression: Compression) -> io::Result<BlockScanner> {
+        check_block_compression(compression)?;
+        Ok(BlockScanner {
+            compression,
+            decoder: ScanDecoder::open(io::BufReader::new(file), compression)?,
+            bytes_out: 0,
+            blocks: vec![(0, 0)],
+        })
+    }
+
+    /// Starts decoding the next block, if any.
+    fn next_block(&mut self) -> io::Result<()> {
+        let mut rdr = match mem::replace(&mut self.decoder, ScanDecoder::Done) {
+            ScanDecoder::Gzip(d) => d.into_inner(),
+            ScanDecoder::Zstd(d) => d.finish(),
+            ScanDecoder::Done => return Ok(()),
+        };
+        if rdr.fill_buf()?.is_empty() {
+            return Ok(());
+        }
+        self.blocks.push((self.bytes_out, rdr.stream_position()?));
+        self.decoder = ScanDecoder::open(rdr, self.compression)?;
+        Ok(())
+    }
+
+    /// Maps the decompressed position of a record to its virtual offset.
+    /// `block` is a cursor into the blocks seen so far, as positions are monotonic.
+    fn virtual_offset(&self, block: &mut usize, pos: u64) -> io::Result<u64> {
+        while *block + 1 < self.blocks.len() && self.blocks[*block + 1].0 <= pos {
+            *block += 1;
+        }
+        let (block_start, compressed_offset) = self.blocks[*block];
+        let in_block = pos - block_start;
+        if in_block > MAX_BLOCK_OFFSET || compressed_offset > MAX_COMPRESSED_OFFSET {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "the record at decompressed byte {pos} is {in_block} bytes into its block. \
+                     Blocks must decompress to less than 64 KiB - recompress the file with \
+                     `bgzip` or as multi-frame (seekable) zstd."
+                ),
+            ));
+        }
+        Ok((compressed_offset << BLOCK_OFFSET_BITS) | in_block)
+    }
+}
+
+impl io::Read for BlockScanner {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = match &mut self.decoder {
+                ScanDecoder::Gzip(d) => d.read(buf)?,
+                ScanDecoder::Zstd(d) => d.read(buf)?,
+                ScanDecoder::Done => return Ok(0),
+            };
+            if n > 0 || buf.is_empty() {
+                self.bytes_out += n as u64;
+                return Ok(n);
+            }
+            // the current block is exhausted
+            self.next_block()?;
+        }
+    }
+}
+
+/// Creates an index of a block-compressed CSV file at `path`, using the same format as
+/// `RandomAccessSimple`, but with virtual offsets instead of byte offsets.
+pub fn create_block_index<W: io::Write>(
+    conf: &Config,
+    path: &Path,
+    compression: Compression,
+    mut wtr: W,
+) -> CliResult<()> {
+    let scanner = BlockScanner::new(fs::File::open(path)?, compression)?;
+    let mut rdr = conf.from_reader(scanner);
+    let mut block = 0_usize;
+    let mut len = 0_u64;
+
+    if rdr.has_headers() {
+        let header = rdr.byte_headers()?;
+        if !header.is_empty() {
+            // safety: the header row always has a position
+            let pos = header.position().unwrap().byte();
+            let voffset = rdr.get_ref().virtual_offset(&mut block, pos)?;
+            wtr.write_u64::<BigEndian>(voffset)?;
+            len += 1;
+        }
+    }
+    let mut record = csv::ByteRecord::new();
+    while rdr.read_byte_record(&mut record)? {
+        // safety: records read from a reader always have a position
+        let pos = record.position().unwrap().byte();
+        let voffset = rdr.get_ref().virtual_offset(&mut block, pos)?;
+        wtr.write_u64::<BigEndian>(voffset)?;
+        len += 1;
+    }
+    wtr.write_u64::<BigEndian>(len)?;
+    Ok(())
+}
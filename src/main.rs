@@ -56,6 +56,7 @@ static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 mod clitypes;
 mod cmd;
 mod config;
+mod formats;
 mod index;
 mod lookup;
 mod odhtcache;
@@ -95,6 +96,7 @@ fn main() -> QsvExitCode {
 
     enabled_commands.push_str(
         "    behead      Drop header from CSV file
+    bins        Compute histograms or bin numeric columns
     cat         Concatenate by row or column\n",
     );
 
@@ -103,7 +105,9 @@ fn main() -> QsvExitCode {
         .push_str("    clipboard   Provide input from clipboard or output to clipboard\n");
 
     enabled_commands.push_str(
-        "    count       Count records
+        "    corr        Compute correlation or covariance matrices
+    count       Count records
+    crosstab    Compute contingency tables with chi-square tests
     datefmt     Format date/datetime strings
     dedup       Remove redundant rows
     describegpt Infer extended metadata using a LLM
@@ -133,7 +137,10 @@ fn main() -> QsvExitCode {
     #[cfg(all(feature = "foreach", feature = "feature_capable"))]
     enabled_commands.push_str("    foreach     Loop over a CSV file to execute bash commands\n");
 
-    enabled_commands.push_str("    frequency   Show frequency tables\n");
+    enabled_commands.push_str(
+        "    frequency   Show frequency tables
+    fuzzyjoin   Join CSV files by fuzzy matching\n",
+    );
 
     #[cfg(all(feature = "geocode", not(feature = "lite")))]
     enabled_commands
@@ -161,7 +168,10 @@ fn main() -> QsvExitCode {
     #[cfg(all(feature = "luau", feature = "feature_capable"))]
     enabled_commands.push_str("    luau        Execute Luau script on CSV data\n");
 
-    enabled_commands.push_str("    partition   Partition CSV data based on a column value\n");
+    enabled_commands.push_str(
+        "    partition   Partition CSV data based on a column value
+    patch       Apply a diff result to a CSV\n",
+    );
 
     #[cfg(all(feature = "polars", feature = "feature_capable"))]
     enabled_commands.push_str("    pivotp      Pivots CSV files using the Pola.rs engine\n");
@@ -339,10 +349,13 @@ enum Command {
     #[cfg(all(feature = "apply", feature = "feature_capable"))]
     Apply,
     Behead,
+    Bins,
     Cat,
     #[cfg(all(feature = "clipboard", feature = "feature_capable"))]
     Clipboard,
+    Corr,
     Count,
+    Crosstab,
     Datefmt,
     Dedup,
     Describegpt,
@@ -365,6 +378,7 @@ enum Command {
     #[cfg(all(feature = "foreach", not(feature = "lite")))]
     ForEach,
     Frequency,
+    FuzzyJoin,
     #[cfg(all(feature = "geocode", feature = "feature_capable"))]
     Geocode,
     Headers,
@@ -381,6 +395,7 @@ enum Command {
     #[cfg(all(feature = "luau", feature = "feature_capable"))]
     Luau,
     Partition,
+    Patch,
     #[cfg(all(feature = "polars", feature = "feature_capable"))]
     PivotP,
     Pro,
@@ -433,12 +448,15 @@ impl Command {
         CURRENT_COMMAND.get_or_init(|| argv[1].to_lowercase());
         match self {
             Command::Behead => cmd::behead::run(argv),
+            Command::Bins => cmd::bins::run(argv),
             #[cfg(all(feature = "apply", feature = "feature_capable"))]
             Command::Apply => cmd::apply::run(argv),
             Command::Cat => cmd::cat::run(argv),
             #[cfg(all(feature = "clipboard", feature = "feature_capable"))]
             Command::Clipboard => cmd::clipboard::run(argv),
+            Command::Corr => cmd::corr::run(argv),
             Command::Count => cmd::count::run(argv),
+            Command::Crosstab => cmd::crosstab::run(argv),
             Command::Datefmt => cmd::datefmt::run(argv),
             Command::Dedup => cmd::dedup::run(argv),
             Command::Describegpt => cmd::describegpt::run(argv),
@@ -461,6 +479,7 @@ impl Command {
             Command::Flatten => cmd::flatten::run(argv),
             Command::Fmt => cmd::fmt::run(argv),
             Command::Frequency => cmd::frequency::run(argv),
+            Command::FuzzyJoin => cmd::fuzzyjoin::run(argv),
             #[cfg(all(feature = "geocode", feature = "feature_capable"))]
             Command::Geocode => cmd::geocode::run(argv),
             Command::Headers => cmd::headers::run(argv),
@@ -481,6 +500,7 @@ impl Command {
             #[cfg(all(feature = "luau", feature = "feature_capable"))]
             Command::Luau => cmd::luau::run(argv),
             Command::Partition => cmd::partition::run(argv),
+            Command::Patch => cmd::patch::run(argv),
             #[cfg(all(feature = "polars", feature = "feature_capable"))]
             Command::PivotP => cmd::pivotp::run(argv),
             Command::Pro => cmd::pro::run(argv),
@@ -93,6 +93,7 @@ static COMMAND_LIST: &str = r#"
 mod clitypes;
 mod cmd;
 mod config;
+mod formats;
 mod index;
 mod lookup;
 mod odhtcache;
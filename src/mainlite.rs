@@ -20,8 +20,11 @@ static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
 static COMMAND_LIST: &str = r#"
     behead      Drop header from CSV file
+    bins        Compute histograms or bin numeric columns
     cat         Concatenate by row or column
+    corr        Compute correlation or covariance matrices
     count       Count records
+    crosstab    Compute contingency tables with chi-square tests
     datefmt     Format date/datetime columns
     dedup       Remove redundant rows
     describegpt Infer extended metadata using a LLM
@@ -38,6 +41,7 @@ static COMMAND_LIST: &str = r#"
     flatten     Show one field per line
     fmt         Format CSV output (change field delimiter)
     frequency   Show frequency tables
+    fuzzyjoin   Join CSV files by fuzzy matching
     headers     Show header names
     help        Show this usage message
     index       Create CSV index for faster access
@@ -46,6 +50,7 @@ static COMMAND_LIST: &str = r#"
     json        Convert JSON to CSV
     jsonl       Convert newline-delimited JSON files to CSV
     partition   Partition CSV data based on a column value
+    patch       Apply a diff result to a CSV
     pro         Interact with the qsv pro API
     pseudo      Pseudonymise the values of a column
     rename      Rename the columns of CSV data efficiently
@@ -72,6 +77,7 @@ static COMMAND_LIST: &str = r#"
 mod clitypes;
 mod cmd;
 mod config;
+mod formats;
 mod index;
 mod odhtcache;
 mod select;
@@ -226,8 +232,11 @@ fn main() -> QsvExitCode {
 #[serde(rename_all = "lowercase")]
 enum Command {
     Behead,
+    Bins,
     Cat,
+    Corr,
     Count,
+    Crosstab,
     Datefmt,
     Dedup,
     Describegpt,
@@ -244,6 +253,7 @@ enum Command {
     Flatten,
     Fmt,
     Frequency,
+    FuzzyJoin,
     Headers,
     Help,
     Index,
@@ -252,6 +262,7 @@ enum Command {
     Json,
     Jsonl,
     Partition,
+    Patch,
     Pro,
     Pseudo,
     Rename,
@@ -292,8 +303,11 @@ impl Command {
         CURRENT_COMMAND.get_or_init(|| argv[1].to_lowercase());
         match self {
             Command::Behead => cmd::behead::run(argv),
+            Command::Bins => cmd::bins::run(argv),
             Command::Cat => cmd::cat::run(argv),
+            Command::Corr => cmd::corr::run(argv),
             Command::Count => cmd::count::run(argv),
+            Command::Crosstab => cmd::crosstab::run(argv),
             Command::Datefmt => cmd::datefmt::run(argv),
             Command::Dedup => cmd::dedup::run(argv),
             Command::Describegpt => cmd::describegpt::run(argv),
@@ -310,6 +324,7 @@ impl Command {
             Command::Flatten => cmd::flatten::run(argv),
             Command::Fmt => cmd::fmt::run(argv),
             Command::Frequency => cmd::frequency::run(argv),
+            Command::FuzzyJoin => cmd::fuzzyjoin::run(argv),
             Command::Headers => cmd::headers::run(argv),
             Command::Help => {
                 wout!("{USAGE}\n\n{SPONSOR_MESSAGE}");
@@ -322,6 +337,7 @@ impl Command {
             Command::Json => cmd::json::run(argv),
             Command::Jsonl => cmd::jsonl::run(argv),
             Command::Partition => cmd::partition::run(argv),
+            Command::Patch => cmd::patch::run(argv),
             Command::Pro => cmd::pro::run(argv),
             Command::Pseudo => cmd::pseudo::run(argv),
             Command::Rename => cmd::rename::run(argv),
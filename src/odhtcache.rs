@@ -1,5 +1,5 @@
 // blatantly copied from https://github.com/race604/dedup/blob/master/src/cache.rs
-use std::collections::HashSet;
+use std::{collections::HashSet, fs, io, path::Path};
 
 use log::debug;
 use odht::{Config, FxHashFn, HashTableOwned};
@@ -57,6 +57,44 @@ impl ExtDedupCache {
         }
     }
 
+    /// loads a cache persisted with `save`, or creates an empty one if the file
+    /// doesn't exist
+    pub fn load(path: &Path, memo_limit: u64) -> io::Result<Self> {
+        let mut cache = Self::new(memo_limit);
+        if path.exists() {
+            let bytes = fs::read(path)?;
+            let disk = HashTableOwned::<ExtDedupConfig>::from_raw_bytes(&bytes).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid extdedup cache file {}: {e}", path.display()),
+                )
+            })?;
+            debug!("Loaded {} keys from {}", disk.len(), path.display());
+            cache.disk = Some(disk);
+        }
+        Ok(cache)
+    }
+
+    /// persists the cache to a file, after dumping the in-memory items to the on-disk
+    /// hash table. It's written to a temporary file that is then renamed, so a failed
+    /// save doesn't corrupt an existing cache file.
+    pub fn save(&mut self, path: &Path) -> io::Result<()> {
+        if self.disk.is_none() {
+            // size the table for the items so far, instead of the default capacity
+            self.disk = Some(HashTableOwned::<ExtDedupConfig>::with_capacity(
+                self.memo.len().max(1024),
+                95,
+            ));
+        }
+        self.dump_to_disk();
+
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        // safety: the disk table was created above if it didn't exist
+        fs::write(&tmp_path, self.disk.as_ref().unwrap().raw_bytes())?;
+        fs::rename(&tmp_path, path)
+    }
+
     #[inline]
     pub fn insert(&mut self, item: &str) -> bool {
         if self.memo_size >= self.memo_limit {
@@ -152,6 +190,37 @@ mod tests {
         assert!(cache.disk.unwrap().len() > 0);
     }
 
+    #[test]
+    fn test_save_load_cache() {
+        let path = std::env::temp_dir().join(format!("qsv-odhtcache-{}.odht", rand_string(8)));
+        let long_item = rand_string(300);
+
+        let mut cache = ExtDedupCache::new(0);
+        cache.insert("hello");
+        cache.insert(&long_item);
+        cache.save(&path).unwrap();
+
+        let mut cache = ExtDedupCache::load(&path, 0).unwrap();
+        assert!(cache.contains("hello"));
+        assert!(cache.contains(&long_item));
+        assert!(!cache.contains("world"));
+        cache.insert("world");
+        cache.save(&path).unwrap();
+
+        let cache = ExtDedupCache::load(&path, 0).unwrap();
+        assert!(cache.contains("hello"));
+        assert!(cache.contains("world"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_invalid_cache() {
+        let path = std::env::temp_dir().join(format!("qsv-odhtcache-{}.odht", rand_string(8)));
+        fs::write(&path, b"not a hash table").unwrap();
+        assert!(ExtDedupCache::load(&path, 0).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
     fn rand_string(len: usize) -> String {
         thread_rng()
             .sample_iter(&Alphanumeric)
@@ -430,6 +430,17 @@ impl Selection {
     pub fn len(&self) -> usize {
         self.0.len()
     }
+
+    /// Returns this selection without the columns that are also in `other`.
+    pub fn without(&self, other: &Selection) -> Selection {
+        Selection(
+            self.0
+                .iter()
+                .filter(|i| !other.0.contains(i))
+                .copied()
+                .collect(),
+        )
+    }
 }
 
 impl ops::Deref for Selection {
@@ -28,6 +28,8 @@ use sysinfo::System;
 
 #[cfg(feature = "polars")]
 use crate::cmd::count::polars_count_input;
+#[cfg(feature = "polars")]
+use crate::formats::InputFormat;
 use crate::{
     cmd::stats::{get_stats_data_types, JsonTypes, StatsData},
     config,
@@ -59,9 +61,12 @@ pub enum StatsMode {
     Schema,
     Frequency,
     FrequencyForceStats,
+    ApproxCardinality,
     #[cfg(feature = "polars")]
     PolarsSchema,
     Outliers,
+    Bins,
+    Corr,
     None,
 }
 
@@ -391,7 +396,7 @@ pub fn count_rows(conf: &Config) -> Result<u64, CliError> {
 
 #[cfg(feature = "polars")]
 fn count_rows_with_best_method(conf: &Config) -> Option<u64> {
-    if !conf.no_headers {
+    if !conf.no_headers && !conf.is_compressed() && conf.input_format() == InputFormat::Csv {
         // Try polars first for files with headers
         if let Ok(count) = polars_count_input(conf, false) {
             return Some(count);
@@ -1347,6 +1352,16 @@ pub fn round_num(dec_f64: f64, places: u32) -> String {
         .to_string()
 }
 
+/// Parses the value of a --weight column, treating empty, non-numeric, negative
+/// and non-finite weights as zero.
+#[inline]
+pub fn parse_weight(weight: &[u8]) -> f64 {
+    fast_float2::parse::<f64, &[u8]>(weight)
+        .ok()
+        .filter(|w| w.is_finite() && *w > 0.0)
+        .unwrap_or_default()
+}
+
 #[inline]
 /// Transforms a byte slice into a ByteString with optional case-insensitive conversion.
 ///
@@ -1379,6 +1394,28 @@ pub fn transform(bs: &[u8], casei: bool) -> ByteString {
     }
 }
 
+#[cfg(all(feature = "apply", feature = "feature_capable"))]
+pub(crate) use crate::cmd::apply::KeyTransform;
+
+/// Without the apply feature, there are no apply operations to normalize join keys
+/// with, so --key-transform is rejected.
+#[cfg(not(all(feature = "apply", feature = "feature_capable")))]
+pub(crate) struct KeyTransform;
+
+#[cfg(not(all(feature = "apply", feature = "feature_capable")))]
+#[allow(clippy::unused_self)]
+impl KeyTransform {
+    pub(crate) fn new(_operations: &str) -> CliResult<Self> {
+        fail_incorrectusage_clierror!(
+            "--key-transform requires qsv to be built with the apply feature."
+        )
+    }
+
+    pub(crate) fn apply(&self, value: &str) -> String {
+        value.to_owned()
+    }
+}
+
 pub fn load_dotenv() -> CliResult<()> {
     // First, check if there is a QSV_DOTENV_PATH environment variable set
     // if there is, use that as the .env file.
@@ -2061,29 +2098,34 @@ pub fn get_stats_records(
     // otherwise, run stats command to generate stats.csv.data.jsonl file
     if !stats_data_loaded {
         let stats_args = crate::cmd::stats::Args {
-            arg_input:            args.arg_input.as_ref().map(String::from),
-            flag_select:          crate::select::SelectColumns::parse("").unwrap(),
-            flag_everything:      false,
-            flag_typesonly:       false,
-            flag_infer_boolean:   false,
-            flag_mode:            false,
-            flag_cardinality:     true,
-            flag_median:          false,
-            flag_quartiles:       false,
-            flag_mad:             false,
-            flag_nulls:           false,
-            flag_round:           4,
-            flag_infer_dates:     true,
-            flag_dates_whitelist: args.flag_dates_whitelist.to_string(),
-            flag_prefer_dmy:      args.flag_prefer_dmy,
-            flag_force:           args.flag_force,
-            flag_jobs:            Some(njobs(args.flag_jobs)),
-            flag_stats_jsonl:     true,
-            flag_cache_threshold: 1, // force the creation of stats cache files
-            flag_output:          None,
-            flag_no_headers:      args.flag_no_headers,
-            flag_delimiter:       args.flag_delimiter,
-            flag_memcheck:        args.flag_memcheck,
+            arg_input:               args.arg_input.as_ref().map(String::from),
+            flag_select:             crate::select::SelectColumns::parse("").unwrap(),
+            flag_groupby:            None,
+            flag_weight:             None,
+            flag_everything:         false,
+            flag_typesonly:          false,
+            flag_infer_boolean:      false,
+            flag_mode:               false,
+            flag_cardinality:        true,
+            flag_approx_cardinality: false,
+            flag_median:             false,
+            flag_quartiles:          false,
+            flag_mad:                false,
+            flag_percentiles:        None,
+            flag_approx_quantiles:   false,
+            flag_nulls:              false,
+            flag_round:              4,
+            flag_infer_dates:        true,
+            flag_dates_whitelist:    args.flag_dates_whitelist.to_string(),
+            flag_prefer_dmy:         args.flag_prefer_dmy,
+            flag_force:              args.flag_force,
+            flag_jobs:               Some(njobs(args.flag_jobs)),
+            flag_stats_jsonl:        true,
+            flag_cache_threshold:    1, // force the creation of stats cache files
+            flag_output:             None,
+            flag_no_headers:         args.flag_no_headers,
+            flag_delimiter:          args.flag_delimiter,
+            flag_memcheck:           args.flag_memcheck,
         };
 
         let tempfile = tempfile::Builder::new()
@@ -2123,6 +2165,12 @@ pub fn get_stats_records(
                     "stats\t{input}\t--cardinality\t--stats-jsonl\t--force\t--output\t{tempfile_path}"
                 )
             },
+            StatsMode::ApproxCardinality => {
+                // StatsMode::ApproxCardinality
+                // estimated cardinalities are enough, so use HyperLogLog
+                // so we don't load the whole file into memory
+                format!("stats\t{input}\t--approx-cardinality\t--stats-jsonl\t--output\t{tempfile_path}")
+            },
             #[cfg(feature = "polars")]
             StatsMode::PolarsSchema => {
                 // StatsMode::PolarsSchema
@@ -2134,6 +2182,24 @@ pub fn get_stats_records(
                 // we need data types, ranges, cardinality, quartiles, mad and modes/antimodes
                 format!("stats\t{input}\t--cardinality\t--quartiles\t--mad\t--mode\t--stats-jsonl\t--output\t{tempfile_path}")
             },
+            StatsMode::Bins => {
+                // StatsMode::Bins
+                // we need data types, ranges, nullcounts & the quartiles for the IQR
+                if args.flag_force {
+                    format!("stats\t{input}\t--quartiles\t--stats-jsonl\t--force\t--output\t{tempfile_path}")
+                } else {
+                    format!("stats\t{input}\t--quartiles\t--stats-jsonl\t--output\t{tempfile_path}")
+                }
+            },
+            StatsMode::Corr => {
+                // StatsMode::Corr
+                // we just need the data types
+                if args.flag_force {
+                    format!("stats\t{input}\t--stats-jsonl\t--force\t--output\t{tempfile_path}")
+                } else {
+                    format!("stats\t{input}\t--stats-jsonl\t--output\t{tempfile_path}")
+                }
+            },
             StatsMode::None => unreachable!(), // we returned early on None earlier
         };
         if args.flag_prefer_dmy {
@@ -2220,7 +2286,7 @@ pub fn get_stats_records(
 /// no type inferencing is done unlike tojsonl, so all fields are strings
 pub fn csv_to_jsonl(
     input_csv: &str,
-    csv_types: &[JsonTypes],
+    csv_types: &[(&str, JsonTypes)],
     output_jsonl: &PathBuf,
 ) -> CliResult<()> {
     let file = File::open(input_csv)?;
@@ -2234,6 +2300,17 @@ pub fn csv_to_jsonl(
         .iter()
         .map(std::string::ToString::to_string)
         .collect();
+    // look up the JSON type of each column by name, as the columns present
+    // depend on the stats options used. Unknown columns are treated as strings.
+    let type_vec: Vec<&JsonTypes> = key_vec
+        .iter()
+        .map(|key| {
+            csv_types
+                .iter()
+                .find(|(name, _)| *name == key.as_str())
+                .map_or(&JsonTypes::String, |(_, json_type)| json_type)
+        })
+        .collect();
 
     let output = File::create(output_jsonl)?;
     let mut writer = BufWriter::new(output);
@@ -2247,14 +2324,10 @@ pub fn csv_to_jsonl(
     while rdr.read_record(&mut record)? {
         json_object.clear();
 
-        // safety: we know the record length is the same as the key_vec length
+        // safety: we know the record length is the same as the key_vec & type_vec length
         for (i, val) in record.iter().enumerate() {
             let key = unsafe { key_vec.get_unchecked(i) };
-            let data_type = if key == "cardinality" {
-                &JsonTypes::Int
-            } else {
-                csv_types.get(i).unwrap_or(&JsonTypes::String)
-            };
+            let data_type = unsafe { *type_vec.get_unchecked(i) };
             let value = if val.is_empty() && data_type != &JsonTypes::Bool {
                 continue;
             } else {
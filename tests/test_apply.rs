@@ -1433,6 +1433,36 @@ fn apply_ops_squeeze0() {
     assert_eq!(got, expected);
 }
 
+#[test]
+fn apply_ops_strip_punct_unaccent_ltrim0() {
+    let wrk = Workdir::new("apply");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "id"],
+            svec!["ACME, Inc.", "00042"],
+            svec!["Crème Brûlée", "000"],
+            svec!["Ångström «Labs»", "0"],
+            svec!["São-Paulo", "100"],
+        ],
+    );
+    let mut cmd = wrk.command("apply");
+    cmd.arg("operations")
+        .arg("strip_punct,unaccent,ltrim0")
+        .arg("name,id")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "id"],
+        svec!["ACME Inc", "42"],
+        svec!["Creme Brulee", "0"],
+        svec!["Angstrom Labs", "0"],
+        svec!["SaoPaulo", "100"],
+    ];
+    assert_eq!(got, expected);
+}
+
 #[test]
 fn apply_ops_chain_strip() {
     let wrk = Workdir::new("apply");
@@ -0,0 +1,142 @@
+use std::process;
+
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> (Workdir, process::Command) {
+    let wrk = Workdir::new(name);
+    let mut rows = vec![svec!["n", "s"]];
+    for i in 1..=10 {
+        rows.push(vec![i.to_string(), format!("s{i}")]);
+    }
+    rows.push(svec!["", "null"]);
+    wrk.create("in.csv", rows);
+
+    let mut cmd = wrk.command("bins");
+    cmd.arg("in.csv");
+
+    (wrk, cmd)
+}
+
+#[test]
+fn bins_fixed() {
+    let (wrk, mut cmd) = setup("bins_fixed");
+    cmd.args(["--method", "fixed"]).args(["--bins", "3"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "field",
+            "bin",
+            "lower",
+            "upper",
+            "count",
+            "percentage",
+            "cumulative_percentage"
+        ],
+        svec!["n", "[1, 4)", "1", "4", "3", "30", "30"],
+        svec!["n", "[4, 7)", "4", "7", "3", "30", "60"],
+        svec!["n", "[7, 10]", "7", "10", "4", "40", "100"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn bins_fixed_width() {
+    let (wrk, mut cmd) = setup("bins_fixed_width");
+    cmd.args(["--method", "fixed"]).args(["--width", "5"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "field",
+            "bin",
+            "lower",
+            "upper",
+            "count",
+            "percentage",
+            "cumulative_percentage"
+        ],
+        svec!["n", "[1, 6)", "1", "6", "5", "50", "50"],
+        svec!["n", "[6, 11]", "6", "11", "5", "50", "100"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn bins_fixed_width_too_many_bins() {
+    let (wrk, mut cmd) = setup("bins_fixed_width_too_many_bins");
+    // a range of 9 in bins 0.001 wide would create 9,000 bins
+    cmd.args(["--method", "fixed"]).args(["--width", "0.001"]);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn bins_sturges() {
+    let (wrk, mut cmd) = setup("bins_sturges");
+    cmd.args(["--method", "sturges"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "field",
+            "bin",
+            "lower",
+            "upper",
+            "count",
+            "percentage",
+            "cumulative_percentage"
+        ],
+        svec!["n", "[1, 2.8)", "1", "2.8", "2", "20", "20"],
+        svec!["n", "[2.8, 4.6)", "2.8", "4.6", "2", "20", "40"],
+        svec!["n", "[4.6, 6.4)", "4.6", "6.4", "2", "20", "60"],
+        svec!["n", "[6.4, 8.2)", "6.4", "8.2", "2", "20", "80"],
+        svec!["n", "[8.2, 10]", "8.2", "10", "2", "20", "100"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn bins_fd() {
+    let (wrk, mut cmd) = setup("bins_fd");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let bins = &got[1..];
+    assert!(!bins.is_empty());
+    assert!(bins.iter().all(|bin| bin[0] == "n"));
+    let total: u64 = bins.iter().map(|bin| bin[4].parse::<u64>().unwrap()).sum();
+    assert_eq!(total, 10);
+    assert_eq!(bins.last().unwrap()[6], "100");
+}
+
+#[test]
+fn bins_quantile_label() {
+    let (wrk, mut cmd) = setup("bins_quantile_label");
+    cmd.args(["--method", "quantile"])
+        .args(["--bins", "2"])
+        .arg("--label");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[0], svec!["n", "s", "n_bin"]);
+    assert_eq!(got[1], svec!["1", "s1", "[1, 5.5)"]);
+    assert_eq!(got[5], svec!["5", "s5", "[1, 5.5)"]);
+    assert_eq!(got[6], svec!["6", "s6", "[5.5, 10]"]);
+    assert_eq!(got[10], svec!["10", "s10", "[5.5, 10]"]);
+    assert_eq!(got[11], svec!["", "null", ""]);
+}
+
+#[test]
+fn bins_no_numeric_columns() {
+    let (wrk, mut cmd) = setup("bins_no_numeric_columns");
+    cmd.args(["--select", "s"]);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn bins_stdin() {
+    let wrk = Workdir::new("bins_stdin");
+    let mut cmd = wrk.command("bins");
+
+    wrk.assert_err(&mut cmd);
+}
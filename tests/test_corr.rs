@@ -0,0 +1,138 @@
+use std::process;
+
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> (Workdir, process::Command) {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["x", "s", "y", "z"],
+            svec!["1", "a", "2", "5"],
+            svec!["2", "b", "4", "3"],
+            svec!["3", "c", "6", "4"],
+            svec!["4", "d", "8", "1"],
+            svec!["5", "e", "10", "2"],
+            svec!["", "f", "12", "6"],
+        ],
+    );
+
+    let mut cmd = wrk.command("corr");
+    cmd.arg("in.csv");
+
+    (wrk, cmd)
+}
+
+#[test]
+fn corr_pearson() {
+    let (wrk, mut cmd) = setup("corr_pearson");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "x", "y", "z"],
+        svec!["x", "1", "1", "-0.8"],
+        svec!["y", "1", "1", "-0.0286"],
+        svec!["z", "-0.8", "-0.0286", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn corr_spearman_long() {
+    let (wrk, mut cmd) = setup("corr_spearman_long");
+    cmd.args(["--method", "spearman"])
+        .args(["--format", "long"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field1", "field2", "n", "spearman"],
+        svec!["x", "y", "5", "1"],
+        svec!["x", "z", "5", "-0.8"],
+        svec!["y", "z", "6", "-0.0286"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn corr_kendall_long() {
+    let (wrk, mut cmd) = setup("corr_kendall_long");
+    cmd.args(["--method", "kendall"]).args(["--format", "long"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field1", "field2", "n", "kendall"],
+        svec!["x", "y", "5", "1"],
+        svec!["x", "z", "5", "-0.6"],
+        svec!["y", "z", "6", "-0.0667"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn corr_covariance() {
+    let (wrk, mut cmd) = setup("corr_covariance");
+    cmd.args(["--method", "covariance"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "x", "y", "z"],
+        svec!["x", "2.5", "5", "-2"],
+        svec!["y", "5", "14", "-0.2"],
+        svec!["z", "-2", "-0.2", "3.5"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn corr_json() {
+    let (wrk, mut cmd) = setup("corr_json");
+    cmd.args(["--select", "x,z"]).args(["--format", "json"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let json: serde_json::Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "method": "pearson",
+            "fields": ["x", "z"],
+            "matrix": [[1.0, -0.8], [-0.8, 1.0]],
+        })
+    );
+}
+
+#[test]
+fn corr_parallel() {
+    let wrk = Workdir::new("corr_parallel");
+    let mut rows = vec![svec!["a", "b", "c"]];
+    for i in 0..1000_u64 {
+        rows.push(vec![
+            i.to_string(),
+            ((i * 7919) % 1000).to_string(),
+            (i % 13).to_string(),
+        ]);
+    }
+    wrk.create_indexed("in.csv", rows);
+
+    let mut cmd = wrk.command("corr");
+    cmd.args(["--format", "long"])
+        .args(["--jobs", "1"])
+        .arg("in.csv");
+    let sequential: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    let mut cmd = wrk.command("corr");
+    cmd.args(["--format", "long"])
+        .args(["--jobs", "4"])
+        .arg("in.csv");
+    let parallel: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    assert_eq!(sequential.len(), 4);
+    assert_eq!(sequential, parallel);
+}
+
+#[test]
+fn corr_not_enough_numeric_columns() {
+    let (wrk, mut cmd) = setup("corr_not_enough_numeric_columns");
+    cmd.args(["--select", "x,s"]);
+
+    wrk.assert_err(&mut cmd);
+}
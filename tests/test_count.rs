@@ -443,3 +443,35 @@ fn show_version() {
     let expected = format!(" {}", env!("CARGO_PKG_VERSION"));
     assert!(got.contains(&expected));
 }
+
+#[test]
+fn count_gzip_input() {
+    use std::io::Write;
+
+    let wrk = Workdir::new("count_gzip_input");
+    let mut gz = flate2::write::GzEncoder::new(
+        std::fs::File::create(wrk.path("in.csv.gz")).unwrap(),
+        flate2::Compression::default(),
+    );
+    gz.write_all(b"letter,number\nalpha,13\nbeta,24\ngamma,37\n").unwrap();
+    gz.finish().unwrap();
+
+    let mut cmd = wrk.command("count");
+    cmd.arg("in.csv.gz");
+
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, "3".to_string());
+}
+
+#[test]
+fn count_zstd_input() {
+    let wrk = Workdir::new("count_zstd_input");
+    let compressed = zstd::encode_all(&b"letter\tnumber\nalpha\t13\nbeta\t24\n"[..], 0).unwrap();
+    std::fs::write(wrk.path("in.tsv.zst"), compressed).unwrap();
+
+    let mut cmd = wrk.command("count");
+    cmd.arg("in.tsv.zst");
+
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, "2".to_string());
+}
@@ -0,0 +1,142 @@
+use std::process;
+
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> (Workdir, process::Command) {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["region", "product"],
+            svec!["east", "a"],
+            svec!["east", "a"],
+            svec!["east", "b"],
+            svec!["west", "b"],
+            svec!["east", "a"],
+            svec!["west", "a"],
+            svec!["west", "b"],
+            svec!["west", "b"],
+        ],
+    );
+
+    let mut cmd = wrk.command("crosstab");
+    cmd.arg("region").arg("product").arg("in.csv");
+
+    (wrk, cmd)
+}
+
+#[test]
+fn crosstab_counts() {
+    let (wrk, mut cmd) = setup("crosstab_counts");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "a", "b", "Total"],
+        svec!["east", "3", "1", "4"],
+        svec!["west", "1", "3", "4"],
+        svec!["Total", "4", "4", "8"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn crosstab_row_pct_no_margins() {
+    let (wrk, mut cmd) = setup("crosstab_row_pct_no_margins");
+    cmd.args(["--values", "row_pct"]).arg("--no-margins");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "a", "b"],
+        svec!["east", "75", "25"],
+        svec!["west", "25", "75"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn crosstab_col_pct() {
+    let (wrk, mut cmd) = setup("crosstab_col_pct");
+    cmd.args(["--values", "col_pct"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "a", "b", "Total"],
+        svec!["east", "75", "25", "50"],
+        svec!["west", "25", "75", "50"],
+        svec!["Total", "100", "100", "100"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn crosstab_long() {
+    let (wrk, mut cmd) = setup("crosstab_long");
+    cmd.arg("--long");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "region",
+            "product",
+            "count",
+            "expected",
+            "row_pct",
+            "col_pct",
+            "total_pct"
+        ],
+        svec!["east", "a", "3", "2", "75", "75", "37.5"],
+        svec!["east", "b", "1", "2", "25", "25", "12.5"],
+        svec!["west", "a", "1", "2", "25", "25", "12.5"],
+        svec!["west", "b", "3", "2", "75", "75", "37.5"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn crosstab_chi2() {
+    let (wrk, mut cmd) = setup("crosstab_chi2");
+    cmd.arg("--chi2");
+
+    let got = wrk.output_stderr(&mut cmd);
+    let json: serde_json::Value = serde_json::from_str(got.trim()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "chi2": 2.0,
+            "dof": 1,
+            "p_value": 0.1573,
+            "cramers_v": 0.5,
+            "n": 8,
+        })
+    );
+}
+
+#[test]
+fn crosstab_chi2_empty() {
+    let wrk = Workdir::new("crosstab_chi2_empty");
+    wrk.create("in.csv", vec![svec!["region", "product"]]);
+    let mut cmd = wrk.command("crosstab");
+    cmd.arg("--chi2").arg("region").arg("product").arg("in.csv");
+
+    let got = wrk.output_stderr(&mut cmd);
+    let json: serde_json::Value = serde_json::from_str(got.trim()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "chi2": null,
+            "dof": 0,
+            "p_value": null,
+            "cramers_v": null,
+            "n": 0,
+        })
+    );
+}
+
+#[test]
+fn crosstab_multiple_columns() {
+    let (wrk, _) = setup("crosstab_multiple_columns");
+    let mut cmd = wrk.command("crosstab");
+    cmd.arg("region,product").arg("product").arg("in.csv");
+
+    wrk.assert_err(&mut cmd);
+}
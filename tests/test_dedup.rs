@@ -287,3 +287,275 @@ fn dedup_not_sorted2() {
     let got: String = wrk.output_stderr(&mut cmd);
     assert!(got.contains("Aborting! Input not sorted!"));
 }
+
+fn fuzzy_vendors(wrk: &Workdir) {
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["name", "city"],
+            svec!["Acme Corp", "Boston"],
+            svec!["ACME Corp.", "Boston"],
+            svec!["Globex", "Springfield"],
+            svec!["Acme Corp", "Chicago"],
+            svec!["Initech", "Austin"],
+            svec!["Initec", "Austin"],
+        ],
+    );
+}
+
+#[test]
+fn dedup_fuzzy() {
+    let wrk = Workdir::new("dedup_fuzzy");
+    fuzzy_vendors(&wrk);
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--fuzzy", "jw:0.9"])
+        .args(["--select", "name"])
+        .arg("--ignore-case")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "city"],
+        svec!["Acme Corp", "Boston"],
+        svec!["Globex", "Springfield"],
+        svec!["Initech", "Austin"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(got_err, "3\n");
+}
+
+#[test]
+fn dedup_fuzzy_blocking_dupes_output() {
+    let wrk = Workdir::new("dedup_fuzzy_blocking_dupes_output");
+    fuzzy_vendors(&wrk);
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--fuzzy", "jaro-winkler:0.9"])
+        .args(["--select", "name"])
+        .args(["--blocking", "city"])
+        .args(["--dupes-output", "dupes.csv"])
+        .arg("-i")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "city"],
+        svec!["Acme Corp", "Boston"],
+        svec!["Globex", "Springfield"],
+        svec!["Acme Corp", "Chicago"],
+        svec!["Initech", "Austin"],
+    ];
+    assert_eq!(got, expected);
+
+    let dupes: String = wrk.from_str(&wrk.path("dupes.csv"));
+    let expected_dupes = "cluster_id,similarity,name,city
+1,1,Acme Corp,Boston
+1,0.98,ACME Corp.,Boston
+2,1,Initech,Austin
+2,0.9714,Initec,Austin
+";
+    assert_eq!(dupes, expected_dupes);
+}
+
+#[test]
+fn dedup_fuzzy_case_sensitive_levenshtein() {
+    let wrk = Workdir::new("dedup_fuzzy_case_sensitive_levenshtein");
+    fuzzy_vendors(&wrk);
+
+    // without --ignore-case, "ACME Corp." is only 60% similar to "Acme Corp"
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--fuzzy", "lev:0.8"])
+        .args(["--select", "name"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "city"],
+        svec!["Acme Corp", "Boston"],
+        svec!["ACME Corp.", "Boston"],
+        svec!["Globex", "Springfield"],
+        svec!["Initech", "Austin"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn dedup_fuzzy_invalid() {
+    let wrk = Workdir::new("dedup_fuzzy_invalid");
+    fuzzy_vendors(&wrk);
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--fuzzy", "soundex:0.9"]).arg("in.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--fuzzy", "jw:1.5"]).arg("in.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--fuzzy", "jw:0.9"])
+        .arg("--sorted")
+        .arg("in.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+fn cdc_snapshots(wrk: &Workdir) {
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["id", "name", "email", "updated_at"],
+            svec!["1", "Ann", "", "2024-01-01"],
+            svec!["2", "Bob", "bob@x.com", "2024-01-03"],
+            svec!["1", "Ann B", "ann@x.com", "2024-01-05"],
+            svec!["2", "", "bob@y.com", "2024-01-02"],
+            svec!["3", "Cy", "cy@x.com", ""],
+            svec!["1", "", "", "2024-01-04"],
+        ],
+    );
+}
+
+#[test]
+fn dedup_keep_max() {
+    let wrk = Workdir::new("dedup_keep_max");
+    cdc_snapshots(&wrk);
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--select", "id"])
+        .args(["--keep", "max:updated_at"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "email", "updated_at"],
+        svec!["1", "Ann B", "ann@x.com", "2024-01-05"],
+        svec!["2", "Bob", "bob@x.com", "2024-01-03"],
+        svec!["3", "Cy", "cy@x.com", ""],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(got_err, "3\n");
+}
+
+#[test]
+fn dedup_keep_first_dupes_output() {
+    let wrk = Workdir::new("dedup_keep_first_dupes_output");
+    cdc_snapshots(&wrk);
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--select", "id"])
+        .args(["--keep", "first"])
+        .args(["--dupes-output", "dupes.csv"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "email", "updated_at"],
+        svec!["1", "Ann", "", "2024-01-01"],
+        svec!["2", "Bob", "bob@x.com", "2024-01-03"],
+        svec!["3", "Cy", "cy@x.com", ""],
+    ];
+    assert_eq!(got, expected);
+
+    let dupes: String = wrk.from_str(&wrk.path("dupes.csv"));
+    let expected_dupes = "id,name,email,updated_at
+1,Ann B,ann@x.com,2024-01-05
+1,,,2024-01-04
+2,,bob@y.com,2024-01-02
+";
+    assert_eq!(dupes, expected_dupes);
+}
+
+#[test]
+fn dedup_keep_last_merge() {
+    let wrk = Workdir::new("dedup_keep_last_merge");
+    cdc_snapshots(&wrk);
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--select", "id"])
+        .args(["--keep", "last"])
+        .arg("--merge")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "email", "updated_at"],
+        svec!["1", "Ann B", "ann@x.com", "2024-01-04"],
+        svec!["2", "Bob", "bob@y.com", "2024-01-02"],
+        svec!["3", "Cy", "cy@x.com", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn dedup_keep_min_sorted() {
+    let wrk = Workdir::new("dedup_keep_min_sorted");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["id", "price"],
+            svec!["a", "10"],
+            svec!["a", "9.5"],
+            svec!["a", "100"],
+            svec!["b", ""],
+            svec!["b", "20"],
+        ],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--select", "id"])
+        .args(["--keep", "min:price"])
+        .arg("--sorted")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "price"], svec!["a", "9.5"], svec!["b", "20"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn dedup_keep_invalid() {
+    let wrk = Workdir::new("dedup_keep_invalid");
+    cdc_snapshots(&wrk);
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--keep", "newest"]).arg("in.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--keep", "max:nonexistent"]).arg("in.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn dedup_sorted_collation_primary() {
+    let wrk = Workdir::new("dedup_sorted_collation_primary");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["word", "n"],
+            svec!["apfel", "1"],
+            svec!["Äpfel", "2"],
+            svec!["Zebra", "3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--collation", "de:primary"])
+        .arg("--sorted")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["word", "n"], svec!["apfel", "1"], svec!["Zebra", "3"]];
+    assert_eq!(got, expected);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(got, "1\n");
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--collation", "de", "--numeric"]).arg("in.csv");
+    wrk.assert_err(&mut cmd);
+}
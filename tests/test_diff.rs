@@ -689,6 +689,341 @@ diffresult,h1,h2,h3,h4
     assert_eq!(got.as_str(), expected);
 }
 
+fn create_tolerance_files(wrk: &Workdir) {
+    let left = vec![
+        svec!["id", "name", "price", "updated_at"],
+        svec!["1", "apple", "1.0", "2024-01-01"],
+        svec!["2", "Banana ", "2.50", "2024-01-01"],
+        svec!["3", "cherry", "3", "2024-01-01"],
+        svec!["4", "Durian", "4", "2024-01-01"],
+    ];
+    wrk.create("left.csv", left);
+
+    let right = vec![
+        svec!["id", "name", "price", "updated_at"],
+        svec!["1", "apple", "1.00", "2024-02-01"],
+        svec!["2", "banana", "2.5", "2024-01-01"],
+        svec!["3", "cherry", "3.2", "2024-02-01"],
+        svec!["4", "Durian", "4", "2024-01-01"],
+    ];
+    wrk.create("right.csv", right);
+}
+
+#[test]
+fn diff_without_comparison_options() {
+    let wrk = Workdir::new("diff_without_comparison_options");
+    create_tolerance_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "\
+diffresult,id,name,price,updated_at
+-,1,apple,1.0,2024-01-01
++,1,apple,1.00,2024-02-01
+-,2,Banana ,2.50,2024-01-01
++,2,banana,2.5,2024-01-01
+-,3,cherry,3,2024-01-01
++,3,cherry,3.2,2024-02-01";
+    assert_eq!(got.as_str(), expected);
+}
+
+#[test]
+fn diff_numeric_tolerance_ignore_case_trim_ignore_columns() {
+    let wrk = Workdir::new("diff_numeric_tolerance_ignore_case_trim_ignore_columns");
+    create_tolerance_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv"])
+        .args(["--numeric-tolerance", "0.1"])
+        .arg("--ignore-case")
+        .arg("--trim")
+        .args(["--ignore-columns", "updated_at"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "\
+diffresult,id,name,price,updated_at
+-,3,cherry,3,2024-01-01
++,3,cherry,3.2,2024-02-01";
+    assert_eq!(got.as_str(), expected);
+}
+
+#[test]
+fn diff_numeric_tolerance_only() {
+    let wrk = Workdir::new("diff_numeric_tolerance_only");
+    create_tolerance_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv"])
+        .args(["--numeric-tolerance", "0"])
+        .arg("--drop-equal-fields");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "\
+diffresult,id,name,price,updated_at
+-,1,,,2024-01-01
++,1,,,2024-02-01
+-,2,Banana ,,
++,2,banana,,
+-,3,,3,2024-01-01
++,3,,3.2,2024-02-01";
+    assert_eq!(got.as_str(), expected);
+}
+
+#[test]
+fn diff_ignore_case_and_trim_on_key() {
+    let wrk = Workdir::new("diff_ignore_case_and_trim_on_key");
+
+    let left = vec![svec!["code", "value"], svec!["ab", "1"], svec!["cd", "2"]];
+    wrk.create("left.csv", left);
+
+    let right = vec![svec!["code", "value"], svec!["AB ", "1"], svec!["CD", "3"]];
+    wrk.create("right.csv", right);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--ignore-case", "--trim"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "\
+diffresult,code,value
+-,cd,2
++,CD,3";
+    assert_eq!(got.as_str(), expected);
+}
+
+#[test]
+fn diff_summary() {
+    let wrk = Workdir::new("diff_summary");
+    create_tolerance_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--summary"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "\
+type,name,count
+rows,added,0
+rows,deleted,0
+rows,modified,3
+column,price,3
+column,updated_at,2
+column,name,1";
+    assert_eq!(got.as_str(), expected);
+}
+
+#[test]
+fn diff_summary_with_added_and_deleted_rows() {
+    let wrk = Workdir::new("diff_summary_with_added_and_deleted_rows");
+    create_tolerance_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--summary"])
+        .args(["--ignore-columns", "updated_at"])
+        .args(["--key", "1"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "\
+type,name,count
+rows,added,1
+rows,deleted,1
+rows,modified,2
+column,price,2";
+    assert_eq!(got.as_str(), expected);
+}
+
+#[test]
+fn diff_negative_numeric_tolerance() {
+    let wrk = Workdir::new("diff_negative_numeric_tolerance");
+    create_tolerance_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--numeric-tolerance=-1"]);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn diff_format_jsonl() {
+    let wrk = Workdir::new("diff_format_jsonl");
+    create_tolerance_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--format", "jsonl"])
+        .args(["--ignore-columns", "updated_at"])
+        .args(["--key", "1"]);
+
+    // the deleted and added rows are on the same line, so sort the lines
+    let got: String = wrk.stdout(&mut cmd);
+    let mut got: Vec<&str> = got.lines().collect();
+    got.sort_unstable();
+    let expected = r#"{"op":"modify","key":{"name":"apple"},"changes":{"price":{"old":"1.0","new":"1.00"}}}
+{"op":"delete","key":{"name":"Banana "},"row":{"id":"2","name":"Banana ","price":"2.50","updated_at":"2024-01-01"}}
+{"op":"add","key":{"name":"banana"},"row":{"id":"2","name":"banana","price":"2.5","updated_at":"2024-01-01"}}
+{"op":"modify","key":{"name":"cherry"},"changes":{"price":{"old":"3","new":"3.2"}}}"#;
+    let mut expected: Vec<&str> = expected.lines().collect();
+    expected.sort_unstable();
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_format_json_patch() {
+    let wrk = Workdir::new("diff_format_json_patch");
+    wrk.create(
+        "left.csv",
+        vec![svec!["id", "a/b"], svec!["1", "x"], svec!["2", "y"]],
+    );
+    wrk.create(
+        "right.csv",
+        vec![svec!["id", "a/b"], svec!["1", "z"], svec!["3", "w"]],
+    );
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--format", "json-patch"]);
+
+    let got: serde_json::Value = serde_json::from_str(&wrk.stdout::<String>(&mut cmd)).unwrap();
+    let expected = serde_json::json!([
+        {"op": "test", "path": "/1/a~1b", "value": "x"},
+        {"op": "replace", "path": "/1/a~1b", "value": "z"},
+        {"op": "test", "path": "/2", "value": {"id": "2", "a/b": "y"}},
+        {"op": "remove", "path": "/2"},
+        {"op": "add", "path": "/3", "value": {"id": "3", "a/b": "w"}},
+    ]);
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_format_html() {
+    let wrk = Workdir::new("diff_format_html");
+    wrk.create(
+        "left.csv",
+        vec![svec!["id", "name"], svec!["1", "a<b"], svec!["2", "gone"]],
+    );
+    wrk.create("right.csv", vec![svec!["id", "name"], svec!["1", "a&b"]]);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--format", "html"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    assert!(got.starts_with("<!DOCTYPE html>"));
+    assert!(got.contains("<tr><th>diffresult</th><th>id</th><th>name</th></tr>"));
+    assert!(got.contains(
+        r#"<tr class="modified"><td>~</td><td>1</td><td class="modified"><del>a&lt;b</del> <ins>a&amp;b</ins></td></tr>"#
+    ));
+    assert!(got.contains(r#"<tr class="deleted"><td>-</td><td>2</td><td>gone</td></tr>"#));
+    assert!(got.ends_with("</html>"));
+}
+
+#[test]
+fn diff_format_invalid() {
+    let wrk = Workdir::new("diff_format_invalid");
+    create_tolerance_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--format", "xml"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--format", "jsonl", "--summary"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn diff_external() {
+    let wrk = Workdir::new("diff_external");
+    wrk.create(
+        "left.csv",
+        vec![
+            svec!["id", "name", "price"],
+            svec!["3", "cherry", "3"],
+            svec!["1", "apple", "1.0"],
+            svec!["4", "durian", "4"],
+            svec!["2", "banana", "2.5"],
+        ],
+    );
+    wrk.create(
+        "right.csv",
+        vec![
+            svec!["id", "name", "price"],
+            svec!["5", "elderberry", "5"],
+            svec!["4", "Durian", "4"],
+            svec!["3", "cherry", "3"],
+            svec!["1", "apple", "1.00"],
+        ],
+    );
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--external", "--tmp-dir"])
+        .arg(wrk.path("."));
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "\
+diffresult,id,name,price
+-,1,apple,1.0
++,1,apple,1.00
+-,2,banana,2.5
+-,4,durian,4
++,4,Durian,4
++,5,elderberry,5";
+    assert_eq!(got.as_str(), expected);
+
+    // with comparison options, and a summary
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--external", "--tmp-dir"])
+        .arg(wrk.path("."))
+        .args(["--numeric-tolerance", "0", "--ignore-case", "--summary"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "\
+type,name,count
+rows,added,1
+rows,deleted,1
+rows,modified,0";
+    assert_eq!(got.as_str(), expected);
+}
+
+#[test]
+fn diff_external_multiline_fields_no_headers() {
+    let wrk = Workdir::new("diff_external_multiline_fields_no_headers");
+    wrk.create(
+        "left.csv",
+        vec![svec!["b", "line 1\nline 2"], svec!["a", "x\x1fy"]],
+    );
+    wrk.create(
+        "right.csv",
+        vec![svec!["a", "x\x1fy"], svec!["b", "line 1\nline 3"]],
+    );
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--external", "--tmp-dir"])
+        .arg(wrk.path("."))
+        .args([
+            "--no-headers-left",
+            "--no-headers-right",
+            "--format",
+            "jsonl",
+        ]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"{"op":"modify","key":{"_col_1":"b"},"changes":{"_col_2":{"old":"line 1\nline 2","new":"line 1\nline 3"}}}"#;
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_external_errors() {
+    let wrk = Workdir::new("diff_external_errors");
+    create_tolerance_files(&wrk);
+
+    // both inputs can't be stdin
+    let mut cmd = wrk.command("diff");
+    cmd.arg("--external");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "--external", "--sort-columns", "0"]);
+    wrk.assert_err(&mut cmd);
+}
+
 fn create_file_with_delim(wrk: &Workdir, file_path_new: &str, file_path: &str, delimiter: u8) {
     let mut select_cmd = wrk.command("select");
     select_cmd.args(["1-", file_path]);
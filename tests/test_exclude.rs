@@ -196,3 +196,30 @@ fn exclude_1497_empty_fields() {
 
     assert_eq!(got, expected);
 }
+
+#[test]
+#[cfg(feature = "apply")]
+fn exclude_key_transform() {
+    let wrk = Workdir::new("exclude_key_transform");
+    wrk.create(
+        "records.csv",
+        vec![
+            svec!["name", "city"],
+            svec!["ACME Inc.", "Boston"],
+            svec!["Crème Brûlée Ltd", "Paris"],
+            svec!["Initech", "Austin"],
+        ],
+    );
+    wrk.create(
+        "processed.csv",
+        vec![svec!["name"], svec!["acme  inc"], svec!["CREME BRULEE LTD"]],
+    );
+
+    let mut cmd = wrk.command("exclude");
+    cmd.args(["name", "records.csv", "name", "processed.csv"])
+        .args(["--key-transform", "strip_punct,squeeze,unaccent,lower"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["name", "city"], svec!["Initech", "Austin"]];
+    assert_eq!(got, expected);
+}
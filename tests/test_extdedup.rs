@@ -150,3 +150,136 @@ fn extdedupe_csvmode_neighborhood() {
     // 81 duplicates should be removed
     assert!(String::from_utf8_lossy(&output.stderr).contains("81\n"));
 }
+
+#[test]
+fn extdedup_csvmode_keep_last_merge() {
+    let wrk = Workdir::new("extdedup_csvmode_keep_last_merge");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["id", "name", "email", "updated_at"],
+            svec!["1", "Ann", "", "2024-01-01"],
+            svec!["2", "Bob", "bob@x.com", "2024-01-03"],
+            svec!["1", "Ann B", "ann@x.com", "2024-01-05"],
+            svec!["2", "", "bob@y.com", "2024-01-02"],
+            svec!["3", "Cy", "cy@x.com", ""],
+            svec!["1", "", "", "2024-01-04"],
+        ],
+    );
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "id"])
+        .args(["--keep", "last"])
+        .arg("--merge")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "email", "updated_at"],
+        svec!["2", "Bob", "bob@y.com", "2024-01-02"],
+        svec!["3", "Cy", "cy@x.com", ""],
+        svec!["1", "Ann B", "ann@x.com", "2024-01-04"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn extdedup_linemode_keep() {
+    let wrk = Workdir::new("extdedup_linemode_keep");
+    wrk.create("in.csv", vec![svec!["a"], svec!["a"]]);
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--keep", "last"]).arg("in.csv");
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn extdedup_csvmode_cache_file() {
+    let wrk = Workdir::new("extdedup_csvmode_cache_file");
+    wrk.create(
+        "day1.csv",
+        vec![
+            svec!["id", "value"],
+            svec!["1", "a"],
+            svec!["2", "b"],
+            svec!["1", "c"],
+        ],
+    );
+    wrk.create(
+        "day2.csv",
+        vec![
+            svec!["id", "value"],
+            svec!["2", "d"],
+            svec!["3", "e"],
+            svec!["4", "f"],
+            svec!["3", "g"],
+        ],
+    );
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "id"])
+        .args(["--cache-file", "ids.odht"])
+        .arg("day1.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "value"], svec!["1", "a"], svec!["2", "b"]];
+    assert_eq!(got, expected);
+
+    // day2 is deduped against the keys seen in day1
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "id"])
+        .args(["--cache-file", "ids.odht"])
+        .arg("--update")
+        .arg("day2.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "value"], svec!["3", "e"], svec!["4", "f"]];
+    assert_eq!(got, expected);
+
+    // all of day2's keys are now in the cache
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "id"])
+        .args(["--cache-file", "ids.odht"])
+        .arg("--check-only")
+        .arg("day2.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "value"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn extdedup_linemode_cache_file_check_only() {
+    let wrk = Workdir::new("extdedup_linemode_cache_file_check_only");
+    wrk.create_from_string("seen.txt", "apple\nbanana\n");
+    wrk.create_from_string("new.txt", "banana\ncherry\ncherry\n");
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--cache-file", "lines.odht"]).arg("seen.txt");
+    wrk.assert_success(&mut cmd);
+
+    // --check-only doesn't insert the new lines, so cherry is written twice
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--cache-file", "lines.odht"])
+        .arg("--check-only")
+        .arg("new.txt");
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, "cherry\ncherry");
+
+    // ...and the cache is unchanged
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--cache-file", "lines.odht"])
+        .arg("--check-only")
+        .arg("new.txt");
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, "cherry\ncherry");
+}
+
+#[test]
+fn extdedup_check_only_without_cache_file() {
+    let wrk = Workdir::new("extdedup_check_only_without_cache_file");
+    wrk.create_from_string("in.txt", "a\na\n");
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.arg("--check-only").arg("in.txt");
+
+    wrk.assert_err(&mut cmd);
+}
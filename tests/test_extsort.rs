@@ -118,3 +118,124 @@ fn extsort_issue_2391() {
     ];
     assert_eq!(got, expected);
 }
+
+#[test]
+fn extsort_by_spec() {
+    let wrk = Workdir::new("extsort_by_spec");
+    wrk.create(
+        "sales.csv",
+        vec![
+            svec!["date", "amount", "name"],
+            svec!["2024-03-01", "10", "file10"],
+            svec!["March 1, 2024", "9.5", "file2"],
+            svec!["", "5", "zeta"],
+            svec!["2024-01-15", "10", "file1"],
+            svec!["2024-03-01", "10", "file9"],
+        ],
+    );
+    let mut cmd = wrk.command("index");
+    cmd.arg("sales.csv");
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("extsort");
+    cmd.arg("sales.csv")
+        .args(["--by", "date:desc:date,amount:asc:num,name:asc:natural"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date", "amount", "name"],
+        svec!["March 1, 2024", "9.5", "file2"],
+        svec!["2024-03-01", "10", "file9"],
+        svec!["2024-03-01", "10", "file10"],
+        svec!["2024-01-15", "10", "file1"],
+        svec!["", "5", "zeta"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("extsort");
+    cmd.arg("sales.csv")
+        .args(["--by", "amount:num", "--reverse"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn extsort_by_spec_control_chars() {
+    let wrk = Workdir::new("extsort_by_spec_control_chars");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["name", "n"],
+            svec!["ab", "1"],
+            svec!["a\\b", "2"],
+            svec!["a b", "3"],
+            svec!["a\nb", "4"],
+            svec!["a\tb", "5"],
+            svec!["a", "6"],
+        ],
+    );
+    let mut cmd = wrk.command("index");
+    cmd.arg("in.csv");
+    wrk.assert_success(&mut cmd);
+
+    // the encoded sort values keep the byte order of values with control characters
+    let mut cmd = wrk.command("extsort");
+    cmd.arg("in.csv").args(["--by", "name:str"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "n"],
+        svec!["a", "6"],
+        svec!["a\tb", "5"],
+        svec!["a\nb", "4"],
+        svec!["a b", "3"],
+        svec!["a\\b", "2"],
+        svec!["ab", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn extsort_collation() {
+    let wrk = Workdir::new("extsort_collation");
+    wrk.create(
+        "words.csv",
+        vec![
+            svec!["word", "n"],
+            svec!["Zebra", "1"],
+            svec!["Äpfel", "2"],
+            svec!["apfel", "3"],
+            svec!["Apfel", "4"],
+        ],
+    );
+
+    let mut cmd = wrk.command("extsort");
+    cmd.arg("words.csv").args(["--collation", "de"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["word", "n"],
+        svec!["apfel", "3"],
+        svec!["Apfel", "4"],
+        svec!["Äpfel", "2"],
+        svec!["Zebra", "1"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("index");
+    cmd.arg("words.csv");
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("extsort");
+    cmd.arg("words.csv")
+        .args(["--select", "word", "--collation", "de:primary"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["word", "n"],
+        svec!["Äpfel", "2"],
+        svec!["apfel", "3"],
+        svec!["Apfel", "4"],
+        svec!["Zebra", "1"],
+    ];
+    assert_eq!(got, expected);
+}
@@ -289,6 +289,116 @@ fn frequency_negative_limit() {
     assert_eq!(got, expected);
 }
 
+#[test]
+fn frequency_weight() {
+    let wrk = Workdir::new("frequency_weight");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["fruit", "w"],
+            svec!["apple", "2"],
+            svec!["banana", "1.5"],
+            svec!["apple", "0.5"],
+            svec!["cherry", ""],
+            svec!["banana", "abc"],
+            svec!["cherry", "4"],
+            svec!["durian", "-1"],
+        ],
+    );
+    let mut cmd = wrk.command("frequency");
+    cmd.args(["--weight", "w"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "value", "count", "percentage"],
+        svec!["fruit", "cherry", "4", "50"],
+        svec!["fruit", "apple", "2.5", "31.25"],
+        svec!["fruit", "banana", "1.5", "18.75"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn frequency_weight_limit_other() {
+    let wrk = Workdir::new("frequency_weight_limit_other");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["fruit", "w"],
+            svec!["apple", "2"],
+            svec!["banana", "1.5"],
+            svec!["apple", "0.5"],
+            svec!["cherry", "4"],
+        ],
+    );
+    let mut cmd = wrk.command("frequency");
+    cmd.args(["--weight", "w"])
+        .args(["--limit", "1"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "value", "count", "percentage"],
+        svec!["fruit", "cherry", "4", "50"],
+        svec!["fruit", "Other (2)", "4", "50"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn frequency_approx() {
+    let (wrk, mut cmd) = setup("frequency_approx");
+    cmd.arg("--approx")
+        .args(["--limit", "0"])
+        .args(["--select", "h2"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "value", "count", "percentage", "count_error"],
+        svec!["h2", "z", "3", "42.85714", "0"],
+        svec!["h2", "y", "2", "28.57143", "0"],
+        svec!["h2", "Y", "1", "14.28571", "0"],
+        svec!["h2", "x", "1", "14.28571", "0"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn frequency_approx_bounded_capacity() {
+    let wrk = Workdir::new("frequency_approx_bounded_capacity");
+    let mut rows = vec![svec!["street"]];
+    for i in 0..300 {
+        if i % 3 == 0 {
+            rows.push(svec!["Main St"]);
+        } else {
+            rows.push(vec![format!("{i} Side St")]);
+        }
+    }
+    wrk.create("in.csv", rows);
+
+    let mut cmd = wrk.command("frequency");
+    cmd.arg("--approx")
+        .args(["--approx-capacity", "5"])
+        .args(["--limit", "1"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got.len(), 2);
+    assert_eq!(got[1][1], "Main St");
+    let count: u64 = got[1][2].parse().unwrap();
+    let error: u64 = got[1][4].parse().unwrap();
+    // the true frequency is within the error bounds
+    assert!(count >= 100 && count - error <= 100);
+}
+
+#[test]
+fn frequency_approx_asc() {
+    let (wrk, mut cmd) = setup("frequency_approx_asc");
+    cmd.arg("--approx").arg("--asc");
+
+    wrk.assert_err(&mut cmd);
+}
+
 #[test]
 fn frequency_limit_threshold() {
     let (wrk, mut cmd) = setup("frequency_limit_threshold");
@@ -545,6 +655,31 @@ fn frequency_all_unique_force_stats_cache() {
     assert_eq!(got, expected);
 }
 
+#[test]
+fn frequency_all_unique_with_approx_cardinality_stats_cache() {
+    let wrk = Workdir::new("frequency_all_unique_with_approx_cardinality_stats_cache");
+    let testdata = wrk.load_test_file("boston311-100.csv");
+
+    // small cardinalities are counted exactly, so they can still be short-circuited
+    let mut stats_cmd = wrk.command("stats");
+    stats_cmd
+        .arg(testdata.clone())
+        .arg("--approx-cardinality")
+        .arg("--stats-jsonl");
+
+    wrk.assert_success(&mut stats_cmd);
+
+    let mut cmd = wrk.command("frequency");
+    cmd.args(["--select", "1"]).arg(testdata);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "value", "count", "percentage"],
+        svec!["case_enquiry_id", "<ALL_UNIQUE>", "100", "100"],
+    ];
+    assert_eq!(got, expected);
+}
+
 #[test]
 fn frequency_all_unique_stats_mode_none() {
     let wrk = Workdir::new("frequency_all_unique_stats_mode_none");
@@ -778,3 +913,156 @@ fn assert_eq_ftables(got: &FTables, expected: &FTables) -> bool {
     }
     true
 }
+
+#[test]
+fn frequency_json() {
+    let (wrk, mut cmd) = setup("frequency_json");
+    cmd.arg("--json").args(["--limit", "1"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let json: serde_json::Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "fieldcount": 2,
+            "fields": [
+                {
+                    "field": "h1",
+                    "cardinality": 4,
+                    "nullcount": 1,
+                    "frequencies": [
+                        {"value": "a", "count": 4, "percentage": 57.14286},
+                        {"value": "Other (3)", "count": 3, "percentage": 42.85714},
+                    ],
+                },
+                {
+                    "field": "h2",
+                    "cardinality": 4,
+                    "nullcount": 0,
+                    "frequencies": [
+                        {"value": "z", "count": 3, "percentage": 42.85714},
+                        {"value": "Other (3)", "count": 4, "percentage": 57.14286},
+                    ],
+                },
+            ],
+        })
+    );
+}
+
+fn setup_groupby(name: &str) -> (Workdir, process::Command) {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["region", "product"],
+            svec!["west", "pear"],
+            svec!["east", "apple"],
+            svec!["east", "pear"],
+            svec!["west", ""],
+            svec!["east", "apple"],
+            svec!["north", ""],
+            svec!["west", "pear"],
+        ],
+    );
+
+    let mut cmd = wrk.command("frequency");
+    cmd.args(["--groupby", "region"]).arg("in.csv");
+
+    (wrk, cmd)
+}
+
+#[test]
+fn frequency_groupby() {
+    let (wrk, mut cmd) = setup_groupby("frequency_groupby");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "field", "value", "count", "percentage"],
+        svec!["east", "product", "apple", "2", "66.66667"],
+        svec!["east", "product", "pear", "1", "33.33333"],
+        svec!["north", "product", "(NULL)", "1", "100"],
+        svec!["west", "product", "pear", "2", "66.66667"],
+        svec!["west", "product", "(NULL)", "1", "33.33333"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn frequency_groupby_no_nulls() {
+    let (wrk, mut cmd) = setup_groupby("frequency_groupby_no_nulls");
+    cmd.arg("--no-nulls");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "field", "value", "count", "percentage"],
+        svec!["east", "product", "apple", "2", "66.66667"],
+        svec!["east", "product", "pear", "1", "33.33333"],
+        svec!["west", "product", "pear", "2", "100"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn frequency_groupby_json() {
+    let (wrk, mut cmd) = setup_groupby("frequency_groupby_json");
+    cmd.arg("--json").args(["--limit", "1"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let json: serde_json::Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(json["groupby"], serde_json::json!(["region"]));
+    assert_eq!(json["groups"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        json["groups"][2],
+        serde_json::json!({
+            "group": {"region": "west"},
+            "rowcount": 3,
+            "fields": [
+                {
+                    "field": "product",
+                    "cardinality": 2,
+                    "nullcount": 1,
+                    "frequencies": [
+                        {"value": "pear", "count": 2, "percentage": 66.66667},
+                        {"value": "Other (1)", "count": 1, "percentage": 33.33333},
+                    ],
+                },
+            ],
+        })
+    );
+}
+
+#[test]
+fn frequency_groupby_parallel() {
+    let wrk = Workdir::new("frequency_groupby_parallel");
+    let mut rows = vec![svec!["g", "v"]];
+    for i in 0..1000_u64 {
+        rows.push(vec![(i % 3).to_string(), (i % 7).to_string()]);
+    }
+    wrk.create_indexed("in.csv", rows);
+
+    let mut cmd = wrk.command("frequency");
+    cmd.args(["--groupby", "g"])
+        .args(["--jobs", "1"])
+        .arg("in.csv");
+    let mut sequential: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    let mut cmd = wrk.command("frequency");
+    cmd.args(["--groupby", "g"])
+        .args(["--jobs", "4"])
+        .arg("in.csv");
+    let mut parallel: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    // values with the same count may be listed in any order
+    sequential.sort_unstable();
+    parallel.sort_unstable();
+    assert_eq!(sequential.len(), 22);
+    assert_eq!(sequential, parallel);
+}
+
+#[test]
+fn frequency_groupby_weight() {
+    let (wrk, mut cmd) = setup_groupby("frequency_groupby_weight");
+    cmd.args(["--weight", "product"]);
+
+    wrk.assert_err(&mut cmd);
+}
@@ -0,0 +1,132 @@
+use crate::workdir::Workdir;
+
+fn create_vendors(wrk: &Workdir) {
+    wrk.create(
+        "vendors.csv",
+        vec![
+            svec!["name", "city"],
+            svec!["Acme Corp", "Boston"],
+            svec!["Initech", "Austin"],
+            svec!["Nothing Matches", "Boston"],
+        ],
+    );
+    wrk.create(
+        "registry.csv",
+        vec![
+            svec!["company", "city"],
+            svec!["Umbrella", "Boston"],
+            svec!["Acme Corp.", "Boston"],
+            svec!["Initek", "Austin"],
+            svec!["acme corp", "Denver"],
+        ],
+    );
+}
+
+#[test]
+fn fuzzyjoin_levenshtein_top_k() {
+    let wrk = Workdir::new("fuzzyjoin_levenshtein_top_k");
+    create_vendors(&wrk);
+
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["name", "vendors.csv", "company", "registry.csv"])
+        .args(["--metric", "lev", "--threshold", "0.7", "--top-k", "2"])
+        .arg("--ignore-case");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "name",
+            "city",
+            "company",
+            "city",
+            "match_score",
+            "match_rank"
+        ],
+        svec!["Acme Corp", "Boston", "acme corp", "Denver", "1", "1"],
+        svec!["Acme Corp", "Boston", "Acme Corp.", "Boston", "0.9", "2"],
+        svec!["Initech", "Austin", "Initek", "Austin", "0.7143", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzyjoin_left_blocking() {
+    let wrk = Workdir::new("fuzzyjoin_left_blocking");
+    create_vendors(&wrk);
+
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["name", "vendors.csv", "company", "registry.csv"])
+        .args(["--blocking", "city"])
+        .arg("--left");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "name",
+            "city",
+            "company",
+            "city",
+            "match_score",
+            "match_rank"
+        ],
+        svec!["Acme Corp", "Boston", "Acme Corp.", "Boston", "0.98", "1"],
+        svec!["Initech", "Austin", "Initek", "Austin", "0.9095", "1"],
+        svec!["Nothing Matches", "Boston", "", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzyjoin_tokenset() {
+    let wrk = Workdir::new("fuzzyjoin_tokenset");
+    wrk.create(
+        "left.csv",
+        vec![svec!["name"], svec!["Acme Corp"], svec!["Globex"]],
+    );
+    wrk.create(
+        "right.csv",
+        vec![
+            svec!["name"],
+            svec!["Corp Acme Inc"],
+            svec!["Corp Acme"],
+            svec!["Initech"],
+        ],
+    );
+
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["1", "left.csv", "1", "right.csv"])
+        .args(["--metric", "tokenset", "--top-k", "5"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "name", "match_score", "match_rank"],
+        svec!["Acme Corp", "Corp Acme Inc", "1", "1"],
+        svec!["Acme Corp", "Corp Acme", "1", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzyjoin_invalid_options() {
+    let wrk = Workdir::new("fuzzyjoin_invalid_options");
+    create_vendors(&wrk);
+
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["name", "vendors.csv", "company", "registry.csv"])
+        .args(["--metric", "soundex"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["name", "vendors.csv", "company", "registry.csv"])
+        .args(["--threshold", "1.5"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["name", "vendors.csv", "company", "registry.csv"])
+        .args(["--top-k", "0"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["name,city", "vendors.csv", "company", "registry.csv"]);
+    wrk.assert_err(&mut cmd);
+}
@@ -355,3 +355,101 @@ fn future_time(ft: FileTime) -> FileTime {
     let secs = ft.unix_seconds();
     FileTime::from_unix_time(secs + 10_000, 0)
 }
+
+#[test]
+fn index_compressed_err() {
+    use std::io::Write;
+
+    let wrk = Workdir::new("index_compressed_err");
+    let mut xz = xz2::write::XzEncoder::new(fs::File::create(wrk.path("in.csv.xz")).unwrap(), 6);
+    xz.write_all(b"letter,number\na,13\nb,24\n").unwrap();
+    xz.finish().unwrap();
+
+    let mut cmd = wrk.command("index");
+    cmd.arg("in.csv.xz");
+
+    wrk.assert_err(&mut cmd);
+}
+
+/// creates a multi-member gzip file, with each member holding `rows_per_block` rows,
+/// just like a BGZF file
+fn create_block_gzip(wrk: &Workdir, name: &str, rows: usize, rows_per_block: usize) {
+    use std::io::Write;
+
+    let mut data = Vec::new();
+    let mut block = String::from("id,name\n");
+    for i in 0..rows {
+        block.push_str(&format!("{i},\"name {i}\"\n"));
+        if (i + 1) % rows_per_block == 0 {
+            let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            gz.write_all(block.as_bytes()).unwrap();
+            data.extend(gz.finish().unwrap());
+            block.clear();
+        }
+    }
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gz.write_all(block.as_bytes()).unwrap();
+    data.extend(gz.finish().unwrap());
+    fs::write(wrk.path(name), data).unwrap();
+}
+
+#[test]
+fn index_block_gzip_slice() {
+    let wrk = Workdir::new("index_block_gzip_slice");
+    create_block_gzip(&wrk, "in.csv.gz", 1000, 64);
+
+    let mut cmd = wrk.command("index");
+    cmd.arg("in.csv.gz");
+    wrk.assert_success(&mut cmd);
+    assert!(wrk.path("in.csv.gz.idx").exists());
+
+    let mut cmd = wrk.command("count");
+    cmd.arg("in.csv.gz");
+    let got: usize = wrk.stdout(&mut cmd);
+    assert_eq!(got, 1000);
+
+    let mut cmd = wrk.command("slice");
+    cmd.args(["--start", "638", "--len", "2"]).arg("in.csv.gz");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name"],
+        svec!["638", "name 638"],
+        svec!["639", "name 639"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn index_block_zstd_reverse() {
+    let wrk = Workdir::new("index_block_zstd_reverse");
+    let mut data = zstd::encode_all(&b"letter,number\na,1\n"[..], 0).unwrap();
+    data.extend(zstd::encode_all(&b"b,2\nc,3\n"[..], 0).unwrap());
+    fs::write(wrk.path("in.csv.zst"), data).unwrap();
+
+    let mut cmd = wrk.command("index");
+    cmd.arg("in.csv.zst");
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("reverse");
+    cmd.arg("in.csv.zst");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["letter", "number"],
+        svec!["c", "3"],
+        svec!["b", "2"],
+        svec!["a", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn index_gzip_blocks_too_large() {
+    let wrk = Workdir::new("index_gzip_blocks_too_large");
+    // a single gzip member holding ~170KB of decompressed data
+    create_block_gzip(&wrk, "in.csv.gz", 10_000, 10_000);
+
+    let mut cmd = wrk.command("index");
+    cmd.arg("in.csv.gz");
+    wrk.assert_err(&mut cmd);
+    assert!(!wrk.path("in.csv.gz.idx").exists());
+}
@@ -1012,3 +1012,425 @@ fn join_right_anti_different_fields_issue_2437() {
     let expected = vec![svec!["id", "PA", "PB"], svec!["4", "105", "0101"]];
     assert_eq!(got, expected);
 }
+
+join_test!(
+    join_spill_inner_casei,
+    |wrk: Workdir, mut cmd: process::Command, headers: bool| {
+        // with a single partition, the rows are joined in the order of the inputs
+        cmd.args(["--spill", "--spill-partitions", "1"])
+            .arg("--ignore-case");
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = make_rows(
+            headers,
+            false,
+            vec![
+                svec!["Boston", "MA", "Boston", "Logan Airport"],
+                svec!["Boston", "MA", "Boston", "Boston Garden"],
+                svec!["Boston", "MA", "BOSTON", "BOSTON COMMON"],
+                svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium"],
+            ],
+        );
+        assert_eq!(got, expected);
+    }
+);
+
+join_test!(
+    join_spill_outer_full,
+    |wrk: Workdir, mut cmd: process::Command, headers: bool| {
+        cmd.args(["--spill", "--spill-partitions", "1"])
+            .arg("--full");
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = make_rows(
+            headers,
+            false,
+            vec![
+                svec!["Boston", "MA", "Boston", "Logan Airport"],
+                svec!["Boston", "MA", "Boston", "Boston Garden"],
+                svec!["New York", "NY", "", ""],
+                svec!["San Francisco", "CA", "", ""],
+                svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium"],
+                svec!["", "", "Orlando", "Disney World"],
+                svec!["", "", "BOSTON", "BOSTON COMMON"],
+            ],
+        );
+        assert_eq!(got, expected);
+    }
+);
+
+fn create_spill_inputs(wrk: &Workdir) {
+    let mut left = vec![svec!["id", "left"]];
+    let mut right = vec![svec!["id", "right"]];
+    for i in 0..200 {
+        left.push(vec![(i % 150).to_string(), format!("l{i}")]);
+        right.push(vec![(i % 170 + 50).to_string(), format!("r{i}")]);
+    }
+    left.push(svec!["", "l-null"]);
+    right.push(svec!["", "r-null"]);
+    wrk.create("left.csv", left);
+    wrk.create("right.csv", right);
+}
+
+#[test]
+fn join_spill_matches_in_memory_join() {
+    let wrk = Workdir::new("join_spill_matches_in_memory_join");
+    create_spill_inputs(&wrk);
+
+    for join in [
+        vec![],
+        vec!["--nulls"],
+        vec!["--left"],
+        vec!["--right"],
+        vec!["--full"],
+        vec!["--left-anti"],
+        vec!["--left-semi"],
+        vec!["--right-anti"],
+        vec!["--right-semi"],
+    ] {
+        let mut cmd = wrk.command("join");
+        cmd.args(["id", "left.csv", "id", "right.csv"]).args(&join);
+        let mut expected: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+        let mut cmd = wrk.command("join");
+        cmd.args(["id", "left.csv", "id", "right.csv"])
+            .args(&join)
+            .args(["--spill", "--spill-partitions", "7"]);
+        let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+        // the headers stay first, the rows are in partition order
+        got[1..].sort();
+        expected[1..].sort();
+        assert_eq!(got, expected, "{join:?}");
+    }
+}
+
+#[test]
+fn join_spill_keys_output() {
+    let wrk = Workdir::new("join_spill_keys_output");
+    create_spill_inputs(&wrk);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["id", "left.csv", "id", "right.csv"])
+        .args(["--spill", "--tmp-dir", "."])
+        .args(["--left-anti", "--keys-output", "keys.csv"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut got: Vec<String> = wrk
+        .read_to_string("keys.csv")
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect();
+    got.sort();
+    // the unmatched ids 0-49 are in the left CSV twice, the empty id once
+    let mut expected: Vec<String> = (0..50)
+        .chain(0..50)
+        .map(|i| i.to_string())
+        .chain(["\"\"".to_string()])
+        .collect();
+    expected.sort();
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_spill_invalid_options() {
+    let wrk = Workdir::new("join_spill_invalid_options");
+    create_spill_inputs(&wrk);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["id", "left.csv", "id", "right.csv"])
+        .args(["--spill", "--cross"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["id", "left.csv", "id", "right.csv"])
+        .args(["--spill", "--spill-partitions", "0"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["id", "left.csv", "id", "right.csv"]).args([
+        "--spill",
+        "--tmp-dir",
+        "does-not-exist",
+    ]);
+    wrk.assert_err(&mut cmd);
+}
+
+fn create_key_transform_inputs(wrk: &Workdir) {
+    wrk.create(
+        "vendors.csv",
+        vec![
+            svec!["name", "id"],
+            svec!["ACME Inc.", "007"],
+            svec!["Crème Brûlée Ltd", "12"],
+            svec!["Initech", "3"],
+        ],
+    );
+    wrk.create(
+        "registry.csv",
+        vec![
+            svec!["company", "reg_id"],
+            svec!["acme   inc", "7"],
+            svec!["creme brulee, ltd", "0012"],
+            svec!["Globex", "3"],
+        ],
+    );
+}
+
+#[test]
+#[cfg(feature = "apply")]
+fn join_key_transform() {
+    let wrk = Workdir::new("join_key_transform");
+    create_key_transform_inputs(&wrk);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["name,id", "vendors.csv", "company,reg_id", "registry.csv"])
+        .args([
+            "--key-transform",
+            "lower,strip_punct,squeeze,unaccent,ltrim0",
+        ]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "id", "company", "reg_id"],
+        svec!["ACME Inc.", "007", "acme   inc", "7"],
+        svec!["Crème Brûlée Ltd", "12", "creme brulee, ltd", "0012"],
+    ];
+    assert_eq!(got, expected);
+
+    // the same keys with a disk-backed join
+    let mut cmd = wrk.command("join");
+    cmd.args(["name,id", "vendors.csv", "company,reg_id", "registry.csv"])
+        .args([
+            "--key-transform",
+            "lower,strip_punct,squeeze,unaccent,ltrim0",
+        ])
+        .args(["--spill", "--spill-partitions", "1"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected);
+}
+
+#[test]
+#[cfg(feature = "apply")]
+fn join_key_transform_invalid_operation() {
+    let wrk = Workdir::new("join_key_transform_invalid_operation");
+    create_key_transform_inputs(&wrk);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["name", "vendors.csv", "company", "registry.csv"])
+        .args(["--key-transform", "lower,censor"]);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("Invalid --key-transform operation 'censor'"));
+    wrk.assert_err(&mut cmd);
+}
+
+fn create_range_inputs(wrk: &Workdir) {
+    wrk.create(
+        "readings.csv",
+        vec![
+            svec!["sensor", "temp"],
+            svec!["a", "-5"],
+            svec!["b", "12.5"],
+            svec!["c", "20"],
+            svec!["d", "35"],
+            svec!["e", "n/a"],
+        ],
+    );
+    wrk.create(
+        "bands.csv",
+        vec![
+            svec!["band", "low", "high"],
+            svec!["cold", "-20", "10"],
+            svec!["mild", "10", "20"],
+            svec!["warm", "20", "30"],
+            svec!["comfortable", "12", "25"],
+            svec!["unknown", "", ""],
+        ],
+    );
+}
+
+#[test]
+fn join_range_numbers() {
+    let wrk = Workdir::new("join_range_numbers");
+    create_range_inputs(&wrk);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["temp", "readings.csv", "low,high", "bands.csv"])
+        .arg("--range");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["sensor", "temp", "band", "low", "high"],
+        svec!["a", "-5", "cold", "-20", "10"],
+        svec!["b", "12.5", "mild", "10", "20"],
+        svec!["b", "12.5", "comfortable", "12", "25"],
+        svec!["c", "20", "warm", "20", "30"],
+        svec!["c", "20", "comfortable", "12", "25"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_range_closed_full() {
+    let wrk = Workdir::new("join_range_closed_full");
+    create_range_inputs(&wrk);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["temp", "readings.csv", "low,high", "bands.csv"])
+        .args(["--range", "--range-closed", "--full"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["sensor", "temp", "band", "low", "high"],
+        svec!["a", "-5", "cold", "-20", "10"],
+        svec!["b", "12.5", "mild", "10", "20"],
+        svec!["b", "12.5", "comfortable", "12", "25"],
+        svec!["c", "20", "mild", "10", "20"],
+        svec!["c", "20", "warm", "20", "30"],
+        svec!["c", "20", "comfortable", "12", "25"],
+        svec!["d", "35", "", "", ""],
+        svec!["e", "n/a", "", "", ""],
+        svec!["", "", "unknown", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_range_left_anti_keys_output() {
+    let wrk = Workdir::new("join_range_left_anti_keys_output");
+    create_range_inputs(&wrk);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["temp", "readings.csv", "low,high", "bands.csv"])
+        .args(["--range", "--left-anti", "--keys-output", "keys.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["sensor", "temp"], svec!["d", "35"], svec!["e", "n/a"]];
+    assert_eq!(got, expected);
+
+    let keys = wrk.read_to_string("keys.csv").unwrap();
+    assert_eq!(keys.lines().collect::<Vec<_>>(), vec!["35", "n/a"]);
+}
+
+#[test]
+fn join_range_ip() {
+    let wrk = Workdir::new("join_range_ip");
+    wrk.create(
+        "logs.csv",
+        vec![
+            svec!["ip", "path"],
+            svec!["10.0.0.255", "/a"],
+            svec!["192.168.1.10", "/b"],
+            svec!["2001:db8::1", "/c"],
+            svec!["8.8.8.8", "/d"],
+        ],
+    );
+    wrk.create(
+        "blocks.csv",
+        vec![
+            svec!["start_ip", "end_ip", "network"],
+            svec!["10.0.0.0", "10.0.0.255", "office"],
+            svec!["192.168.0.0", "192.168.255.255", "lan"],
+            svec!["2001:db8::", "2001:db8::ffff", "v6-lab"],
+        ],
+    );
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["ip", "logs.csv", "start_ip,end_ip", "blocks.csv"])
+        .args(["--range", "--range-type", "ip", "--range-closed", "--left"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["ip", "path", "start_ip", "end_ip", "network"],
+        svec!["10.0.0.255", "/a", "10.0.0.0", "10.0.0.255", "office"],
+        svec![
+            "192.168.1.10",
+            "/b",
+            "192.168.0.0",
+            "192.168.255.255",
+            "lan"
+        ],
+        svec![
+            "2001:db8::1",
+            "/c",
+            "2001:db8::",
+            "2001:db8::ffff",
+            "v6-lab"
+        ],
+        svec!["8.8.8.8", "/d", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_range_dates() {
+    let wrk = Workdir::new("join_range_dates");
+    wrk.create(
+        "orders.csv",
+        vec![
+            svec!["order", "ordered"],
+            svec!["1", "2024-01-15"],
+            svec!["2", "2024-03-31"],
+            svec!["3", "2024-04-01 09:30:00"],
+        ],
+    );
+    wrk.create(
+        "quarters.csv",
+        vec![
+            svec!["quarter", "from", "to"],
+            svec!["Q1", "2024-01-01", "2024-04-01"],
+            svec!["Q2", "2024-04-01", "2024-07-01"],
+        ],
+    );
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["ordered", "orders.csv", "from,to", "quarters.csv"])
+        .args(["--range", "--range-type", "date"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["order", "ordered", "quarter", "from", "to"],
+        svec!["1", "2024-01-15", "Q1", "2024-01-01", "2024-04-01"],
+        svec!["2", "2024-03-31", "Q1", "2024-01-01", "2024-04-01"],
+        svec!["3", "2024-04-01 09:30:00", "Q2", "2024-04-01", "2024-07-01"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_range_invalid() {
+    let wrk = Workdir::new("join_range_invalid");
+    create_range_inputs(&wrk);
+    wrk.create(
+        "bad_bands.csv",
+        vec![
+            svec!["band", "low", "high"],
+            svec!["cold", "-20", "10"],
+            svec!["backwards", "30", "20"],
+        ],
+    );
+
+    // columns2 must be the start & end columns
+    let mut cmd = wrk.command("join");
+    cmd.args(["temp", "readings.csv", "low", "bands.csv"])
+        .arg("--range");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["temp", "readings.csv", "low,high", "bands.csv"])
+        .args(["--range", "--right-anti"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["temp", "readings.csv", "low,high", "bands.csv"])
+        .args(["--range", "--range-type", "time"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["temp", "readings.csv", "low,high", "bad_bands.csv"])
+        .arg("--range");
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("the start \"30\" is after the end \"20\""));
+    wrk.assert_err(&mut cmd);
+}
@@ -2508,3 +2508,86 @@ fn joinp_unicode_normalization_ligatures() {
     ];
     assert_eq!(got, expected);
 }
+
+#[test]
+#[cfg(feature = "apply")]
+fn joinp_key_transform() {
+    let wrk = Workdir::new("joinp_key_transform");
+    wrk.create(
+        "vendors.csv",
+        vec![
+            svec!["name", "state"],
+            svec!["ACME Inc.", "MA"],
+            svec!["Crème Brûlée Ltd", "NY"],
+            svec!["Initech", "TX"],
+        ],
+    );
+    wrk.create(
+        "registry.csv",
+        vec![
+            svec!["company", "reg_id"],
+            svec!["acme   inc", "A1"],
+            svec!["creme brulee, ltd", "B2"],
+            svec!["Globex", "C3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("joinp");
+    cmd.args(["name", "vendors.csv", "company", "registry.csv"])
+        .args(["--key-transform", "lower,strip_punct,squeeze,unaccent"])
+        .args(["--maintain-order", "left"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "state", "company", "reg_id"],
+        svec!["ACME Inc.", "MA", "acme   inc", "A1"],
+        svec!["Crème Brûlée Ltd", "NY", "creme brulee, ltd", "B2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+#[cfg(feature = "apply")]
+fn joinp_key_transform_matches_join() {
+    let wrk = Workdir::new("joinp_key_transform_matches_join");
+    wrk.create(
+        "left.csv",
+        vec![
+            svec!["name", "left_id"],
+            svec![" 007 ", "L1"],
+            svec!["Ünïcödé Name!!", "L2"],
+            svec!["a\tb  c", "L3"],
+            svec!["\u{a0}Café\u{a0}", "L4"],
+            svec!["no match", "L5"],
+        ],
+    );
+    wrk.create(
+        "right.csv",
+        vec![
+            svec!["company", "right_id"],
+            svec!["7", "R1"],
+            svec!["unicode name", "R2"],
+            svec!["A B C", "R3"],
+            svec!["cafe", "R4"],
+        ],
+    );
+    let key_transform = "trim,ltrim0,lower,strip_punct,squeeze,unaccent";
+
+    let mut join_cmd = wrk.command("join");
+    join_cmd
+        .args(["name", "left.csv", "company", "right.csv"])
+        .args(["--key-transform", key_transform]);
+    let mut join_rows: Vec<Vec<String>> = wrk.read_stdout(&mut join_cmd);
+
+    let mut joinp_cmd = wrk.command("joinp");
+    joinp_cmd
+        .args(["name", "left.csv", "company", "right.csv"])
+        .args(["--key-transform", key_transform]);
+    let mut joinp_rows: Vec<Vec<String>> = wrk.read_stdout(&mut joinp_cmd);
+
+    // join & joinp order their matches differently
+    join_rows.sort();
+    joinp_rows.sort();
+    assert!(join_rows.len() > 1);
+    assert_eq!(join_rows, joinp_rows);
+}
@@ -0,0 +1,120 @@
+use crate::workdir::Workdir;
+
+fn create_snapshots(wrk: &Workdir) {
+    wrk.create(
+        "left.csv",
+        vec![
+            svec!["id", "name", "price"],
+            svec!["1", "apple", "1.0"],
+            svec!["2", "banana", "2.5"],
+            svec!["3", "cherry", "3"],
+            svec!["4", "durian", "4"],
+        ],
+    );
+    wrk.create(
+        "right.csv",
+        vec![
+            svec!["id", "name", "price"],
+            svec!["1", "apple", "1.2"],
+            svec!["3", "cherry", "3"],
+            svec!["4", "Durian", "4.5"],
+            svec!["5", "elderberry", "5"],
+        ],
+    );
+}
+
+#[test]
+fn patch_reproduces_right() {
+    let wrk = Workdir::new("patch_reproduces_right");
+    create_snapshots(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "-o", "delta.csv"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("patch");
+    cmd.args(["left.csv", "delta.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "price"],
+        svec!["1", "apple", "1.2"],
+        svec!["3", "cherry", "3"],
+        svec!["4", "Durian", "4.5"],
+        svec!["5", "elderberry", "5"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn patch_no_headers() {
+    let wrk = Workdir::new("patch_no_headers");
+    wrk.create("left.csv", vec![svec!["a", "x", "1"], svec!["b", "y", "2"]]);
+    wrk.create(
+        "right.csv",
+        vec![svec!["b", "y", "3"], svec!["c", "z", "4"]],
+    );
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "-o", "delta.csv"])
+        .args([
+            "--no-headers-left",
+            "--no-headers-right",
+            "--no-headers-output",
+        ])
+        .args(["--key", "1"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("patch");
+    cmd.args(["left.csv", "delta.csv", "--no-headers", "--key", "1"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["b", "y", "3"], svec!["c", "z", "4"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn patch_wrong_base() {
+    let wrk = Workdir::new("patch_wrong_base");
+    create_snapshots(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "-o", "delta.csv"]);
+    wrk.assert_success(&mut cmd);
+
+    // the diff result does not apply to the right CSV
+    let mut cmd = wrk.command("patch");
+    cmd.args(["right.csv", "delta.csv"]);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("Diff result does not apply"));
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn patch_wrong_base_no_output() {
+    let wrk = Workdir::new("patch_wrong_base_no_output");
+    create_snapshots(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv", "-o", "delta.csv"]);
+    wrk.assert_success(&mut cmd);
+
+    // the diff result is checked before the output is created
+    let mut cmd = wrk.command("patch");
+    cmd.args(["right.csv", "delta.csv", "-o", "patched.csv"]);
+
+    wrk.assert_err(&mut cmd);
+    assert!(!wrk.path("patched.csv").exists());
+}
+
+#[test]
+fn patch_not_a_diff_result() {
+    let wrk = Workdir::new("patch_not_a_diff_result");
+    create_snapshots(&wrk);
+
+    let mut cmd = wrk.command("patch");
+    cmd.args(["left.csv", "right.csv"]);
+
+    wrk.assert_err(&mut cmd);
+}
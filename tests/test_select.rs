@@ -317,3 +317,77 @@ fn test_select_random_seeded_subset() {
     ];
     assert_eq!(got, expected);
 }
+
+#[test]
+fn test_select_compressed_output_roundtrip() {
+    let wrk = Workdir::new("test_select_compressed_output_roundtrip");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["h1", "h2", "h3"],
+            svec!["a", "b", "c"],
+            svec!["d", "e", "f"],
+        ],
+    );
+
+    for out_file in ["out.csv.gz", "out.csv.zst", "out.csv.bz2", "out.csv.xz"] {
+        let mut cmd = wrk.command("select");
+        cmd.arg("h3,h1")
+            .arg("data.csv")
+            .args(["--output", out_file])
+            .env("QSV_COMPRESSION_LEVEL", "1");
+        wrk.assert_success(&mut cmd);
+
+        let mut cmd = wrk.command("select");
+        cmd.arg("1-").arg(out_file);
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = vec![svec!["h3", "h1"], svec!["c", "a"], svec!["f", "d"]];
+        assert_eq!(got, expected, "roundtrip failed for {out_file}");
+    }
+}
+
+#[test]
+fn test_select_jsonl_input() {
+    let wrk = Workdir::new("test_select_jsonl_input");
+    wrk.create_from_string(
+        "data.jsonl",
+        r#"{"id":1,"name":"foo","meta":{"tag":"a,b"}}
+{"id":2,"name":"bar","meta":{"tag":"c"}}
+"#,
+    );
+    let mut cmd = wrk.command("select");
+    cmd.arg("meta.tag,id").arg("data.jsonl");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["meta.tag", "id"],
+        svec!["a,b", "1"],
+        svec!["c", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+#[cfg(feature = "polars")]
+fn test_select_parquet_input() {
+    let wrk = Workdir::new("test_select_parquet_input");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["h1", "h2", "h3"],
+            svec!["a", "1", "x"],
+            svec!["b", "2", "y"],
+        ],
+    );
+    let mut cmd = wrk.command("sqlp");
+    cmd.arg("data.csv")
+        .arg("SELECT * FROM data")
+        .args(["--format", "parquet"])
+        .args(["--output", "data.parquet"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("select");
+    cmd.arg("h3,h1").arg("data.parquet");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["h3", "h1"], svec!["x", "a"], svec!["y", "b"]];
+    assert_eq!(got, expected);
+}
@@ -516,6 +516,217 @@ fn sort_random_secure() {
     assert_eq!(got, expected);
 }
 
+fn create_sales(wrk: &Workdir) {
+    wrk.create(
+        "sales.csv",
+        vec![
+            svec!["date", "amount", "name"],
+            svec!["2024-03-01", "10", "file10"],
+            svec!["March 1, 2024", "9.5", "file2"],
+            svec!["", "5", "zeta"],
+            svec!["2024-01-15", "10", "file1"],
+            svec!["2024-03-01", "10", "file9"],
+        ],
+    );
+}
+
+#[test]
+fn sort_by_spec() {
+    let wrk = Workdir::new("sort_by_spec");
+    create_sales(&wrk);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--by", "date:desc:date,amount:asc:num,name:asc:natural"])
+        .arg("sales.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date", "amount", "name"],
+        svec!["March 1, 2024", "9.5", "file2"],
+        svec!["2024-03-01", "10", "file9"],
+        svec!["2024-03-01", "10", "file10"],
+        svec!["2024-01-15", "10", "file1"],
+        svec!["", "5", "zeta"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--by", "date:desc:date:nulls_first,3:natural"])
+        .arg("--faster")
+        .arg("sales.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date", "amount", "name"],
+        svec!["", "5", "zeta"],
+        svec!["March 1, 2024", "9.5", "file2"],
+        svec!["2024-03-01", "10", "file9"],
+        svec!["2024-03-01", "10", "file10"],
+        svec!["2024-01-15", "10", "file1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sort_by_version_nulls_last() {
+    let wrk = Workdir::new("sort_by_version_nulls_last");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["version"],
+            svec!["1.10.0"],
+            svec!["v1.9"],
+            svec![""],
+            svec!["2.0.0"],
+            svec!["2.0.0-rc1"],
+            svec!["1.2"],
+        ],
+    );
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--by", "version:version:nulls_last"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["version"],
+        svec!["1.2"],
+        svec!["v1.9"],
+        svec!["1.10.0"],
+        svec!["2.0.0-rc1"],
+        svec!["2.0.0"],
+        svec![""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sort_by_istr_unique() {
+    let wrk = Workdir::new("sort_by_istr_unique");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["name", "n"],
+            svec!["b", "1"],
+            svec!["a", "2"],
+            svec!["B", "3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--by", "name:istr"])
+        .arg("--unique")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["name", "n"], svec!["a", "2"], svec!["b", "1"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sort_by_invalid() {
+    let wrk = Workdir::new("sort_by_invalid");
+    create_sales(&wrk);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--by", "amount:num", "--numeric"])
+        .arg("sales.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--by", "amount:asc:fancy"]).arg("sales.csv");
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("Invalid --by sort key \"amount:asc:fancy\""));
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--by", "date-name:desc"]).arg("sales.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+fn create_words(wrk: &Workdir) {
+    wrk.create(
+        "words.csv",
+        vec![
+            svec!["word"],
+            svec!["Zebra"],
+            svec!["Äpfel"],
+            svec!["apfel"],
+            svec!["Apfel"],
+        ],
+    );
+}
+
+#[test]
+fn sort_collation() {
+    let wrk = Workdir::new("sort_collation");
+    create_words(&wrk);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--collation", "de"]).arg("words.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["word"],
+        svec!["apfel"],
+        svec!["Apfel"],
+        svec!["Äpfel"],
+        svec!["Zebra"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--collation", "de"])
+        .arg("--reverse")
+        .arg("words.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["word"],
+        svec!["Zebra"],
+        svec!["Äpfel"],
+        svec!["Apfel"],
+        svec!["apfel"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sort_collation_primary_unique() {
+    let wrk = Workdir::new("sort_collation_primary_unique");
+    create_words(&wrk);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--collation", "de:primary"])
+        .arg("--unique")
+        .arg("words.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["word"], svec!["Äpfel"], svec!["Zebra"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sort_collation_invalid() {
+    let wrk = Workdir::new("sort_collation_invalid");
+    create_words(&wrk);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--collation", "de:loud"]).arg("words.csv");
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("Invalid --collation strength"));
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--collation", "not a locale"]).arg("words.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--collation", "de", "--ignore-case"])
+        .arg("words.csv");
+    wrk.assert_err(&mut cmd);
+}
+
 /// Order `a` and `b` lexicographically using `Ord`
 pub fn iter_cmp<A, L, R>(mut a: L, mut b: R) -> cmp::Ordering
 where
@@ -21,6 +21,31 @@ fn sortcheck_select_notsorted() {
     wrk.assert_err(&mut cmd);
 }
 
+#[test]
+fn sortcheck_by_spec() {
+    let wrk = Workdir::new("sortcheck_by_spec");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["file", "size"],
+            svec!["file2", "9.5"],
+            svec!["file10", "10"],
+            svec!["file10", "2"],
+            svec!["", "1"],
+        ],
+    );
+
+    let mut cmd = wrk.command("sortcheck");
+    cmd.args(["--by", "file:natural:nulls_last,size:desc:num"])
+        .arg("in.csv");
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("sortcheck");
+    cmd.args(["--by", "file:natural,size:desc:num"])
+        .arg("in.csv");
+    wrk.assert_err(&mut cmd);
+}
+
 #[test]
 fn sortcheck_select_sorted() {
     let wrk = Workdir::new("sortcheck_select_sorted");
@@ -226,3 +251,25 @@ fn sortcheck_simple_all_json_progressbar() {
     );
     wrk.assert_err(&mut cmd);
 }
+
+#[test]
+fn sortcheck_collation() {
+    let wrk = Workdir::new("sortcheck_collation");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["word"],
+            svec!["apfel"],
+            svec!["Äpfel"],
+            svec!["Zebra"],
+        ],
+    );
+
+    let mut cmd = wrk.command("sortcheck");
+    cmd.args(["--collation", "de"]).arg("in.csv");
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("sortcheck");
+    cmd.arg("in.csv");
+    wrk.assert_err(&mut cmd);
+}
@@ -1579,6 +1579,241 @@ qsv__fingerprint_hash,,,,,,,,,,,,,,,,,,,,,,,,,14a30758a66a00ca7f90b3b763d16a4195
     assert_eq!(got, expected);
 }
 
+#[test]
+fn stats_percentiles() {
+    let wrk = Workdir::new("stats_percentiles");
+    let mut rows = vec![svec!["n"]];
+    rows.extend((1..=10).map(|i| vec![i.to_string()]));
+    wrk.create("in.csv", rows);
+
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--percentiles", "10,50,90,100"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let pos = got[0].iter().position(|h| h == "percentiles").unwrap();
+    assert_eq!(got[1][pos], "1|5|9|10");
+}
+
+#[test]
+fn stats_percentiles_invalid() {
+    let wrk = Workdir::new("stats_percentiles_invalid");
+    wrk.create("in.csv", vec![svec!["n"], svec!["1"]]);
+
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--percentiles", "5,101"]).arg("in.csv");
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn stats_approx_quantiles() {
+    let wrk = Workdir::new("stats_approx_quantiles");
+    let mut rows = vec![svec!["n"]];
+    rows.extend((1..=10_000).map(|i| vec![i.to_string()]));
+    // index the file so the t-digests of the parallel chunks are merged
+    wrk.create_indexed("in.csv", rows);
+
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--quartiles", "--mad", "--approx-quantiles"])
+        .args(["--percentiles", "5,99"])
+        .args(["--jobs", "4"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let value = |name: &str| -> Vec<f64> {
+        let pos = got[0].iter().position(|h| h == name).unwrap();
+        got[1][pos]
+            .split('|')
+            .map(|v| v.parse::<f64>().unwrap())
+            .collect()
+    };
+    let assert_approx = |name: &str, expected: &[f64]| {
+        for (got, expected) in value(name).iter().zip(expected) {
+            assert!(
+                ((got - expected) / expected).abs() < 0.01,
+                "{name}: got {got}, expected ~{expected}"
+            );
+        }
+    };
+    assert_approx("q1", &[2500.5]);
+    assert_approx("q2_median", &[5000.5]);
+    assert_approx("q3", &[7500.5]);
+    assert_approx("mad", &[2500.0]);
+    assert_approx("percentiles", &[500.0, 9900.0]);
+}
+
+#[test]
+fn stats_approx_cardinality() {
+    let wrk = Workdir::new("stats_approx_cardinality");
+    let mut rows = vec![svec!["id", "category"]];
+    rows.extend((0..20_000).map(|i| vec![format!("id{i}"), format!("cat{}", i % 10)]));
+    // index the file so the sketches of the parallel chunks are merged
+    wrk.create_indexed("in.csv", rows);
+
+    let mut cmd = wrk.command("stats");
+    cmd.arg("--approx-cardinality")
+        .args(["--jobs", "4"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let card_pos = got[0].iter().position(|h| h == "cardinality").unwrap();
+    let err_pos = got[0]
+        .iter()
+        .position(|h| h == "cardinality_error")
+        .unwrap();
+
+    // high cardinalities are estimated
+    let id_card: f64 = got[1][card_pos].parse().unwrap();
+    let id_err: f64 = got[1][err_pos].parse().unwrap();
+    assert!(id_err > 0.0);
+    assert!((id_card - 20_000.0).abs() / 20_000.0 < 4.0 * id_err);
+
+    // low cardinalities are counted exactly
+    assert_eq!(got[2][card_pos], "10");
+    assert_eq!(got[2][err_pos], "0");
+}
+
+#[test]
+fn stats_weight() {
+    let wrk = Workdir::new("stats_weight");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["value", "label", "w"],
+            svec!["1", "a", "1"],
+            svec!["2", "b", "1"],
+            svec!["3", "a", "2"],
+        ],
+    );
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--weight", "w"]).arg("--mode").arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let headers = &got[0];
+    let col = |name: &str| headers.iter().position(|h| h == name).unwrap();
+    // the weight column is excluded from the stats
+    assert_eq!(got.len(), 3);
+    assert_eq!(got[1][col("field")], "value");
+    assert_eq!(got[2][col("field")], "label");
+
+    let value = &got[1];
+    assert_eq!(value[col("sum")], "9");
+    assert_eq!(value[col("mean")], "2.25");
+    assert_eq!(value[col("variance")], "0.6875");
+    assert_eq!(value[col("mode")], "3");
+    assert_eq!(value[col("mode_occurrences")], "2");
+
+    let label = &got[2];
+    assert_eq!(label[col("mode")], "a");
+    assert_eq!(label[col("mode_occurrences")], "3");
+}
+
+#[test]
+fn stats_weight_modes_fractional() {
+    let wrk = Workdir::new("stats_weight_modes_fractional");
+    // 0.1 + 0.2 is not exactly 0.3 in floating point
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["label", "w"],
+            svec!["a", "0.1"],
+            svec!["a", "0.2"],
+            svec!["b", "0.3"],
+            svec!["c", "0.1"],
+        ],
+    );
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--weight", "w"]).arg("--mode").arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let col = |name: &str| got[0].iter().position(|h| h == name).unwrap();
+    assert_eq!(got[1][col("mode")], "a|b");
+    assert_eq!(got[1][col("mode_count")], "2");
+    assert_eq!(got[1][col("antimode")], "c");
+}
+
+#[test]
+fn stats_groupby() {
+    let wrk = Workdir::new("stats_groupby");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["region", "product", "amount"],
+            svec!["west", "b", "5"],
+            svec!["east", "a", "10"],
+            svec!["east", "b", "20"],
+            svec!["west", "a", ""],
+            svec!["east", "a", "30"],
+        ],
+    );
+
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--groupby", "region"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[0][..3], svec!["region", "field", "type"]);
+    assert!(!got[0].contains(&"qsv__value".to_string()));
+    let pos = |name: &str| got[0].iter().position(|h| h == name).unwrap();
+    let (sum, mean, nullcount, sparsity) =
+        (pos("sum"), pos("mean"), pos("nullcount"), pos("sparsity"));
+
+    // a stats block per group, sorted by the group values, without the qsv__ rows
+    assert_eq!(got.len(), 5);
+    let groups: Vec<(&str, &str)> = got[1..]
+        .iter()
+        .map(|r| (r[0].as_str(), r[1].as_str()))
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            ("east", "product"),
+            ("east", "amount"),
+            ("west", "product"),
+            ("west", "amount")
+        ]
+    );
+
+    assert_eq!(got[2][sum], "60");
+    assert_eq!(got[2][mean], "20");
+    assert_eq!(got[2][nullcount], "0");
+    assert_eq!(got[2][sparsity], "0");
+    assert_eq!(got[4][sum], "5");
+    assert_eq!(got[4][mean], "5");
+    assert_eq!(got[4][nullcount], "1");
+    assert_eq!(got[4][sparsity], "0.5");
+
+    // grouped stats are not cached
+    assert!(!wrk.path("in.stats.csv").exists());
+}
+
+#[test]
+fn stats_groupby_everything_parallel() {
+    let wrk = Workdir::new("stats_groupby_everything_parallel");
+    let mut rows = vec![svec!["group", "value"]];
+    rows.extend((1..=1000).map(|i| vec![format!("g{}", i % 2), i.to_string()]));
+    // index the file so the groups of the parallel chunks are merged
+    wrk.create_indexed("in.csv", rows);
+
+    let mut cmd = wrk.command("stats");
+    cmd.arg("--everything")
+        .args(["--groupby", "group"])
+        .args(["--jobs", "4"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let pos = |name: &str| got[0].iter().position(|h| h == name).unwrap();
+
+    assert_eq!(got.len(), 3);
+    assert_eq!(got[1][..3], svec!["g0", "value", "Integer"]);
+    assert_eq!(got[1][pos("sum")], "250500");
+    assert_eq!(got[1][pos("cardinality")], "500");
+    assert_eq!(got[1][pos("q2_median")], "501");
+    assert_eq!(got[2][..3], svec!["g1", "value", "Integer"]);
+    assert_eq!(got[2][pos("sum")], "250000");
+    assert_eq!(got[2][pos("cardinality")], "500");
+    assert_eq!(got[2][pos("q2_median")], "500");
+}
+
 mod stats_infer_nothing {
     // Only test CSV data with headers.
     // Empty CSV data with no headers won't produce any statistical analysis.
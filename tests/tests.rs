@@ -38,6 +38,8 @@ mod test_applydp;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_behead;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_bins;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_cat;
 #[cfg(all(
     any(feature = "feature_capable"),
@@ -47,7 +49,11 @@ mod test_cat;
 mod test_clipboard;
 mod test_combos;
 mod test_comments;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_corr;
 mod test_count;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_crosstab;
 mod test_datefmt;
 mod test_dedup;
 mod test_describegpt;
@@ -76,6 +82,8 @@ mod test_fmt;
 #[cfg(all(feature = "foreach"))]
 mod test_foreach;
 mod test_frequency;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_fuzzyjoin;
 #[cfg(all(feature = "feature_capable", feature = "geocode"))]
 mod test_geocode;
 mod test_headers;
@@ -93,6 +101,8 @@ mod test_jsonl;
 mod test_luau;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_partition;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_patch;
 #[cfg(feature = "polars")]
 mod test_pivotp;
 #[cfg(feature = "prompt")]